@@ -6,6 +6,40 @@ mod m20220101_000001_users;
 mod m20231103_114510_notes;
 mod m20240825_000001_add_user_id_to_notes;
 mod m20240825_000002_add_note_shares_table;
+mod m20240826_000001_add_permission_to_note_shares;
+mod m20240826_000002_add_expires_at_to_note_shares;
+mod m20240827_000001_add_note_invitations_table;
+mod m20240828_000001_add_state_to_note_shares;
+mod m20240828_000002_add_user_blocks_table;
+mod m20240829_000001_add_note_links_table;
+mod m20240830_000001_add_unique_share_target_indexes;
+mod m20240831_000001_add_allow_reshare_to_notes;
+mod m20240831_000002_add_shared_by_user_id_to_note_shares;
+mod m20240901_000001_add_groups_table;
+mod m20240901_000002_add_group_members_table;
+mod m20240901_000003_add_note_group_shares_table;
+mod m20240902_000001_add_workspaces_table;
+mod m20240902_000002_add_workspace_members_table;
+mod m20240902_000003_add_workspace_id_to_notes;
+mod m20240903_000001_add_note_revisions_table;
+mod m20240904_000001_add_deleted_at_to_notes;
+mod m20240905_000001_add_version_to_notes;
+mod m20240906_000001_add_notes_search_index;
+mod m20240907_000001_add_tags_table;
+mod m20240907_000002_add_note_tags_table;
+mod m20240908_000001_add_notebooks_table;
+mod m20240908_000002_add_notebook_id_to_notes;
+mod m20240908_000003_add_notebook_shares_table;
+mod m20240909_000001_add_pinned_and_archived_at_to_notes;
+mod m20240909_000002_add_note_favourites_table;
+mod m20240910_000001_add_attachments_table;
+mod m20240911_000001_add_format_to_notes;
+mod m20240912_000001_add_note_references_table;
+mod m20240913_000001_add_note_items_table;
+mod m20240914_000001_add_reminders_table;
+mod m20240915_000001_add_format_to_note_revisions;
+mod m20240916_000001_add_allow_reshare_to_notebooks;
+mod m20240917_000001_add_state_to_group_members;
 
 pub struct Migrator;
 
@@ -17,6 +51,40 @@ impl MigratorTrait for Migrator {
             Box::new(m20231103_114510_notes::Migration),
             Box::new(m20240825_000001_add_user_id_to_notes::Migration),
             Box::new(m20240825_000002_add_note_shares_table::Migration),
+            Box::new(m20240826_000001_add_permission_to_note_shares::Migration),
+            Box::new(m20240826_000002_add_expires_at_to_note_shares::Migration),
+            Box::new(m20240827_000001_add_note_invitations_table::Migration),
+            Box::new(m20240828_000001_add_state_to_note_shares::Migration),
+            Box::new(m20240828_000002_add_user_blocks_table::Migration),
+            Box::new(m20240829_000001_add_note_links_table::Migration),
+            Box::new(m20240830_000001_add_unique_share_target_indexes::Migration),
+            Box::new(m20240831_000001_add_allow_reshare_to_notes::Migration),
+            Box::new(m20240831_000002_add_shared_by_user_id_to_note_shares::Migration),
+            Box::new(m20240901_000001_add_groups_table::Migration),
+            Box::new(m20240901_000002_add_group_members_table::Migration),
+            Box::new(m20240901_000003_add_note_group_shares_table::Migration),
+            Box::new(m20240902_000001_add_workspaces_table::Migration),
+            Box::new(m20240902_000002_add_workspace_members_table::Migration),
+            Box::new(m20240902_000003_add_workspace_id_to_notes::Migration),
+            Box::new(m20240903_000001_add_note_revisions_table::Migration),
+            Box::new(m20240904_000001_add_deleted_at_to_notes::Migration),
+            Box::new(m20240905_000001_add_version_to_notes::Migration),
+            Box::new(m20240906_000001_add_notes_search_index::Migration),
+            Box::new(m20240907_000001_add_tags_table::Migration),
+            Box::new(m20240907_000002_add_note_tags_table::Migration),
+            Box::new(m20240908_000001_add_notebooks_table::Migration),
+            Box::new(m20240908_000002_add_notebook_id_to_notes::Migration),
+            Box::new(m20240908_000003_add_notebook_shares_table::Migration),
+            Box::new(m20240909_000001_add_pinned_and_archived_at_to_notes::Migration),
+            Box::new(m20240909_000002_add_note_favourites_table::Migration),
+            Box::new(m20240910_000001_add_attachments_table::Migration),
+            Box::new(m20240911_000001_add_format_to_notes::Migration),
+            Box::new(m20240912_000001_add_note_references_table::Migration),
+            Box::new(m20240913_000001_add_note_items_table::Migration),
+            Box::new(m20240914_000001_add_reminders_table::Migration),
+            Box::new(m20240915_000001_add_format_to_note_revisions::Migration),
+            Box::new(m20240916_000001_add_allow_reshare_to_notebooks::Migration),
+            Box::new(m20240917_000001_add_state_to_group_members::Migration),
         ]
     }
 }
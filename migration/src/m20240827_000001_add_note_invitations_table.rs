@@ -0,0 +1,68 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NoteInvitations::Table)
+                    .col(pk_auto(NoteInvitations::Id))
+                    .col(integer(NoteInvitations::NoteId))
+                    .col(string(NoteInvitations::Email))
+                    .col(string_len(NoteInvitations::Permission, 16).default("view"))
+                    .col(timestamp_with_time_zone_null(NoteInvitations::ExpiresAt))
+                    .col(string_uniq(NoteInvitations::Token))
+                    .col(integer(NoteInvitations::InvitedByUserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_invitations-note_id")
+                            .from(NoteInvitations::Table, NoteInvitations::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_invitations-invited_by_user_id")
+                            .from(NoteInvitations::Table, NoteInvitations::InvitedByUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteInvitations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteInvitations {
+    Table,
+    Id,
+    NoteId,
+    Email,
+    Permission,
+    ExpiresAt,
+    Token,
+    InvitedByUserId,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
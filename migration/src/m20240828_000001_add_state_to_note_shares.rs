@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteShares::Table)
+                    .add_column(
+                        ColumnDef::new(NoteShares::State)
+                            .string_len(16)
+                            .not_null()
+                            .default("pending")
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // shares created before recipients could accept them keep working
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(NoteShares::Table)
+                    .value(NoteShares::State, "accepted")
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteShares::Table)
+                    .drop_column(NoteShares::State)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NoteShares {
+    Table,
+    State,
+}
@@ -0,0 +1,68 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(UserBlocks::Table)
+                    .col(pk_auto(UserBlocks::Id))
+                    .col(integer(UserBlocks::UserId))
+                    .col(integer(UserBlocks::BlockedUserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user_blocks-user_id")
+                            .from(UserBlocks::Table, UserBlocks::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-user_blocks-blocked_user_id")
+                            .from(UserBlocks::Table, UserBlocks::BlockedUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-user_blocks-user_id-blocked_user_id")
+                    .table(UserBlocks::Table)
+                    .col(UserBlocks::UserId)
+                    .col(UserBlocks::BlockedUserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserBlocks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserBlocks {
+    Table,
+    Id,
+    UserId,
+    BlockedUserId,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,66 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NoteLinks::Table)
+                    .col(pk_auto(NoteLinks::Id))
+                    .col(integer_uniq(NoteLinks::NoteId))
+                    .col(string_uniq(NoteLinks::Token))
+                    .col(string_null(NoteLinks::Password))
+                    .col(timestamp_with_time_zone_null(NoteLinks::ExpiresAt))
+                    .col(integer(NoteLinks::CreatedByUserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_links-note_id")
+                            .from(NoteLinks::Table, NoteLinks::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_links-created_by_user_id")
+                            .from(NoteLinks::Table, NoteLinks::CreatedByUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteLinks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteLinks {
+    Table,
+    Id,
+    NoteId,
+    Token,
+    Password,
+    ExpiresAt,
+    CreatedByUserId,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // keep the oldest row of every duplicated share before adding the constraint
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(NoteShares::Table)
+                    .and_where(
+                        Expr::col(NoteShares::Id).not_in_subquery(
+                            Query::select()
+                                .expr(Expr::col(NoteShares::Id).min())
+                                .from(NoteShares::Table)
+                                .group_by_columns([NoteShares::NoteId, NoteShares::SharedWithUserId])
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(NoteInvitations::Table)
+                    .and_where(
+                        Expr::col(NoteInvitations::Id).not_in_subquery(
+                            Query::select()
+                                .expr(Expr::col(NoteInvitations::Id).min())
+                                .from(NoteInvitations::Table)
+                                .group_by_columns([NoteInvitations::NoteId, NoteInvitations::Email])
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_shares-note_id-shared_with_user_id")
+                    .table(NoteShares::Table)
+                    .col(NoteShares::NoteId)
+                    .col(NoteShares::SharedWithUserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_invitations-note_id-email")
+                    .table(NoteInvitations::Table)
+                    .col(NoteInvitations::NoteId)
+                    .col(NoteInvitations::Email)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-note_invitations-note_id-email")
+                    .table(NoteInvitations::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-note_shares-note_id-shared_with_user_id")
+                    .table(NoteShares::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NoteShares {
+    Table,
+    Id,
+    NoteId,
+    SharedWithUserId,
+}
+
+#[derive(Iden)]
+enum NoteInvitations {
+    Table,
+    Id,
+    NoteId,
+    Email,
+}
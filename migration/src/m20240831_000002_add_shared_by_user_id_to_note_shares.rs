@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteShares::Table)
+                    .add_column(ColumnDef::new(NoteShares::SharedByUserId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // only owners could share before, so they shared every existing row
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(NoteShares::Table)
+                    .value(
+                        NoteShares::SharedByUserId,
+                        SimpleExpr::SubQuery(
+                            None,
+                            Box::new(
+                                Query::select()
+                                    .column((Notes::Table, Notes::UserId))
+                                    .from(Notes::Table)
+                                    .and_where(
+                                        Expr::col((Notes::Table, Notes::Id))
+                                            .equals((NoteShares::Table, NoteShares::NoteId)),
+                                    )
+                                    .to_owned()
+                                    .into_sub_query_statement(),
+                            ),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteShares::Table)
+                    .drop_column(NoteShares::SharedByUserId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NoteShares {
+    Table,
+    NoteId,
+    SharedByUserId,
+}
+
+#[derive(Iden)]
+enum Notes {
+    Table,
+    Id,
+    UserId,
+}
@@ -0,0 +1,49 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(Groups::Table)
+                    .col(pk_auto(Groups::Id))
+                    .col(uuid_uniq(Groups::Pid))
+                    .col(string(Groups::Name))
+                    .col(integer(Groups::OwnerUserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-groups-owner_user_id")
+                            .from(Groups::Table, Groups::OwnerUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Groups::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Groups {
+    Table,
+    Id,
+    Pid,
+    Name,
+    OwnerUserId,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,74 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(GroupMembers::Table)
+                    .col(pk_auto(GroupMembers::Id))
+                    .col(integer(GroupMembers::GroupId))
+                    .col(integer(GroupMembers::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-group_members-group_id")
+                            .from(GroupMembers::Table, GroupMembers::GroupId)
+                            .to(Groups::Table, Groups::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-group_members-user_id")
+                            .from(GroupMembers::Table, GroupMembers::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-group_members-group_id-user_id")
+                    .table(GroupMembers::Table)
+                    .col(GroupMembers::GroupId)
+                    .col(GroupMembers::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GroupMembers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupMembers {
+    Table,
+    Id,
+    GroupId,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Groups {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
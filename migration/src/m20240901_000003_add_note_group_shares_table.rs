@@ -0,0 +1,93 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NoteGroupShares::Table)
+                    .col(pk_auto(NoteGroupShares::Id))
+                    .col(integer(NoteGroupShares::NoteId))
+                    .col(integer(NoteGroupShares::GroupId))
+                    .col(string_len(NoteGroupShares::Permission, 16).default("view"))
+                    .col(timestamp_with_time_zone_null(NoteGroupShares::ExpiresAt))
+                    .col(integer_null(NoteGroupShares::SharedByUserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_group_shares-note_id")
+                            .from(NoteGroupShares::Table, NoteGroupShares::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_group_shares-group_id")
+                            .from(NoteGroupShares::Table, NoteGroupShares::GroupId)
+                            .to(Groups::Table, Groups::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_group_shares-shared_by_user_id")
+                            .from(NoteGroupShares::Table, NoteGroupShares::SharedByUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_group_shares-note_id-group_id")
+                    .table(NoteGroupShares::Table)
+                    .col(NoteGroupShares::NoteId)
+                    .col(NoteGroupShares::GroupId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteGroupShares::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteGroupShares {
+    Table,
+    Id,
+    NoteId,
+    GroupId,
+    Permission,
+    ExpiresAt,
+    SharedByUserId,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Groups {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,34 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(Workspaces::Table)
+                    .col(pk_auto(Workspaces::Id))
+                    .col(uuid_uniq(Workspaces::Pid))
+                    .col(string(Workspaces::Name))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Workspaces::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Workspaces {
+    Table,
+    Id,
+    Pid,
+    Name,
+}
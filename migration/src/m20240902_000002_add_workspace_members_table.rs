@@ -0,0 +1,76 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(WorkspaceMembers::Table)
+                    .col(pk_auto(WorkspaceMembers::Id))
+                    .col(integer(WorkspaceMembers::WorkspaceId))
+                    .col(integer(WorkspaceMembers::UserId))
+                    .col(string_len(WorkspaceMembers::Role, 16).default("member"))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-workspace_members-workspace_id")
+                            .from(WorkspaceMembers::Table, WorkspaceMembers::WorkspaceId)
+                            .to(Workspaces::Table, Workspaces::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-workspace_members-user_id")
+                            .from(WorkspaceMembers::Table, WorkspaceMembers::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-workspace_members-workspace_id-user_id")
+                    .table(WorkspaceMembers::Table)
+                    .col(WorkspaceMembers::WorkspaceId)
+                    .col(WorkspaceMembers::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WorkspaceMembers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WorkspaceMembers {
+    Table,
+    Id,
+    WorkspaceId,
+    UserId,
+    Role,
+}
+
+#[derive(DeriveIden)]
+enum Workspaces {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,238 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+use uuid::Uuid;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Notes::Table)
+                    .add_column(ColumnDef::new(Notes::WorkspaceId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // every existing user gets a personal workspace owning their notes
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+        let users = db
+            .query_all(backend.build(
+                Query::select()
+                    .columns([Users::Id, Users::Name])
+                    .from(Users::Table)
+                    .order_by(Users::Id, Order::Asc),
+            ))
+            .await?;
+
+        for user in users {
+            let user_id: i32 = user.try_get("", "id")?;
+            let name: String = user.try_get("", "name")?;
+            let pid = Uuid::new_v4();
+
+            manager
+                .exec_stmt(
+                    Query::insert()
+                        .into_table(Workspaces::Table)
+                        .columns([Workspaces::Pid, Workspaces::Name])
+                        .values_panic([pid.into(), format!("{name}'s workspace").into()])
+                        .to_owned(),
+                )
+                .await?;
+            let workspace = db
+                .query_one(backend.build(
+                    Query::select()
+                        .column(Workspaces::Id)
+                        .from(Workspaces::Table)
+                        .and_where(Expr::col(Workspaces::Pid).eq(pid)),
+                ))
+                .await?
+                .ok_or_else(|| DbErr::RecordNotFound("workspace".to_string()))?;
+            let workspace_id: i32 = workspace.try_get("", "id")?;
+
+            manager
+                .exec_stmt(
+                    Query::insert()
+                        .into_table(WorkspaceMembers::Table)
+                        .columns([
+                            WorkspaceMembers::WorkspaceId,
+                            WorkspaceMembers::UserId,
+                            WorkspaceMembers::Role,
+                        ])
+                        .values_panic([workspace_id.into(), user_id.into(), "owner".into()])
+                        .to_owned(),
+                )
+                .await?;
+
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Notes::Table)
+                        .value(Notes::WorkspaceId, workspace_id)
+                        .and_where(Expr::col(Notes::UserId).eq(user_id))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // people notes were shared with, directly or through a group, follow
+        // the notes into their owner's workspace as guests
+        let shared_with = Query::select()
+            .distinct()
+            .column((Notes::Table, Notes::WorkspaceId))
+            .expr_as(
+                Expr::col((NoteShares::Table, NoteShares::SharedWithUserId)),
+                Alias::new("user_id"),
+            )
+            .from(NoteShares::Table)
+            .inner_join(
+                Notes::Table,
+                Expr::col((Notes::Table, Notes::Id)).equals((NoteShares::Table, NoteShares::NoteId)),
+            )
+            .union(
+                UnionType::Distinct,
+                Query::select()
+                    .column((Notes::Table, Notes::WorkspaceId))
+                    .column((GroupMembers::Table, GroupMembers::UserId))
+                    .from(NoteGroupShares::Table)
+                    .inner_join(
+                        Notes::Table,
+                        Expr::col((Notes::Table, Notes::Id))
+                            .equals((NoteGroupShares::Table, NoteGroupShares::NoteId)),
+                    )
+                    .inner_join(
+                        GroupMembers::Table,
+                        Expr::col((GroupMembers::Table, GroupMembers::GroupId))
+                            .equals((NoteGroupShares::Table, NoteGroupShares::GroupId)),
+                    )
+                    .to_owned(),
+            )
+            .to_owned();
+        for row in db.query_all(backend.build(&shared_with)).await? {
+            let workspace_id: i32 = row.try_get("", "workspace_id")?;
+            let user_id: i32 = row.try_get("", "user_id")?;
+            manager
+                .exec_stmt(
+                    Query::insert()
+                        .into_table(WorkspaceMembers::Table)
+                        .columns([
+                            WorkspaceMembers::WorkspaceId,
+                            WorkspaceMembers::UserId,
+                            WorkspaceMembers::Role,
+                        ])
+                        .values_panic([workspace_id.into(), user_id.into(), "guest".into()])
+                        // owners stay owners
+                        .on_conflict(
+                            OnConflict::columns([
+                                WorkspaceMembers::WorkspaceId,
+                                WorkspaceMembers::UserId,
+                            ])
+                            .do_nothing()
+                            .to_owned(),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // SQLite can not change columns or add foreign keys after the fact
+        if backend != DbBackend::Sqlite {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Notes::Table)
+                        .modify_column(ColumnDef::new(Notes::WorkspaceId).integer().not_null())
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name("fk-notes-workspace_id")
+                                .from_tbl(Notes::Table)
+                                .from_col(Notes::WorkspaceId)
+                                .to_tbl(Workspaces::Table)
+                                .to_col(Workspaces::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Notes::Table)
+                        .drop_foreign_key(Alias::new("fk-notes-workspace_id"))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Notes::Table)
+                    .drop_column(Notes::WorkspaceId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Notes {
+    Table,
+    Id,
+    UserId,
+    WorkspaceId,
+}
+
+#[derive(Iden)]
+enum NoteShares {
+    Table,
+    NoteId,
+    SharedWithUserId,
+}
+
+#[derive(Iden)]
+enum NoteGroupShares {
+    Table,
+    NoteId,
+    GroupId,
+}
+
+#[derive(Iden)]
+enum GroupMembers {
+    Table,
+    GroupId,
+    UserId,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(Iden)]
+enum Workspaces {
+    Table,
+    Id,
+    Pid,
+    Name,
+}
+
+#[derive(Iden)]
+enum WorkspaceMembers {
+    Table,
+    WorkspaceId,
+    UserId,
+    Role,
+}
@@ -0,0 +1,92 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NoteRevisions::Table)
+                    .col(pk_auto(NoteRevisions::Id))
+                    .col(integer(NoteRevisions::NoteId))
+                    .col(integer(NoteRevisions::Revision))
+                    .col(string_null(NoteRevisions::Title))
+                    .col(string_null(NoteRevisions::Content))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_revisions-note_id")
+                            .from(NoteRevisions::Table, NoteRevisions::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_revisions-note_id-revision")
+                    .table(NoteRevisions::Table)
+                    .col(NoteRevisions::NoteId)
+                    .col(NoteRevisions::Revision)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // existing notes start their history at revision 1
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(NoteRevisions::Table)
+                    .columns([
+                        NoteRevisions::NoteId,
+                        NoteRevisions::Revision,
+                        NoteRevisions::Title,
+                        NoteRevisions::Content,
+                    ])
+                    .select_from(
+                        Query::select()
+                            .column(Notes::Id)
+                            .expr(Expr::val(1))
+                            .column(Notes::Title)
+                            .column(Notes::Content)
+                            .from(Notes::Table)
+                            .to_owned(),
+                    )
+                    .map_err(|err| DbErr::Custom(err.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteRevisions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteRevisions {
+    Table,
+    Id,
+    NoteId,
+    Revision,
+    Title,
+    Content,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+    Title,
+    Content,
+}
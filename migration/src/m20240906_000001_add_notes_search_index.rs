@@ -0,0 +1,88 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        match manager.get_database_backend() {
+            // titles weigh more than content when ranking
+            DbBackend::Postgres => {
+                db.execute_unprepared(
+                    r"ALTER TABLE notes ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+                        setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+                        setweight(to_tsvector('english', coalesce(content, '')), 'B')
+                    ) STORED",
+                )
+                .await?;
+                db.execute_unprepared(
+                    r#"CREATE INDEX "idx-notes-search_vector" ON notes USING GIN (search_vector)"#,
+                )
+                .await?;
+            }
+            // an external content FTS5 table kept in sync with triggers
+            DbBackend::Sqlite => {
+                db.execute_unprepared(
+                    r"CREATE VIRTUAL TABLE notes_fts USING fts5(
+                        title, content, content='notes', content_rowid='id'
+                    )",
+                )
+                .await?;
+                db.execute_unprepared(
+                    r"CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+                        INSERT INTO notes_fts(rowid, title, content)
+                        VALUES (new.id, new.title, new.content);
+                    END",
+                )
+                .await?;
+                db.execute_unprepared(
+                    r"CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+                        INSERT INTO notes_fts(notes_fts, rowid, title, content)
+                        VALUES ('delete', old.id, old.title, old.content);
+                    END",
+                )
+                .await?;
+                db.execute_unprepared(
+                    r"CREATE TRIGGER notes_fts_update AFTER UPDATE ON notes BEGIN
+                        INSERT INTO notes_fts(notes_fts, rowid, title, content)
+                        VALUES ('delete', old.id, old.title, old.content);
+                        INSERT INTO notes_fts(rowid, title, content)
+                        VALUES (new.id, new.title, new.content);
+                    END",
+                )
+                .await?;
+                db.execute_unprepared("INSERT INTO notes_fts(notes_fts) VALUES ('rebuild')")
+                    .await?;
+            }
+            DbBackend::MySql => {
+                return Err(DbErr::Migration(
+                    "full-text search is only set up for Postgres and SQLite".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        match manager.get_database_backend() {
+            DbBackend::Postgres => {
+                db.execute_unprepared(r#"DROP INDEX IF EXISTS "idx-notes-search_vector""#)
+                    .await?;
+                db.execute_unprepared("ALTER TABLE notes DROP COLUMN search_vector")
+                    .await?;
+            }
+            DbBackend::Sqlite => {
+                for trigger in ["notes_fts_insert", "notes_fts_delete", "notes_fts_update"] {
+                    db.execute_unprepared(&format!("DROP TRIGGER IF EXISTS {trigger}"))
+                        .await?;
+                }
+                db.execute_unprepared("DROP TABLE IF EXISTS notes_fts").await?;
+            }
+            DbBackend::MySql => {}
+        }
+        Ok(())
+    }
+}
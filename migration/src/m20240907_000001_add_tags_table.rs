@@ -0,0 +1,61 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(Tags::Table)
+                    .col(pk_auto(Tags::Id))
+                    .col(uuid_uniq(Tags::Pid))
+                    .col(integer(Tags::UserId))
+                    .col(string(Tags::Name))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-tags-user_id")
+                            .from(Tags::Table, Tags::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-tags-user_id-name")
+                    .table(Tags::Table)
+                    .col(Tags::UserId)
+                    .col(Tags::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Tags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tags {
+    Table,
+    Id,
+    Pid,
+    UserId,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
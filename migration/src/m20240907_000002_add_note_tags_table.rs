@@ -0,0 +1,72 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NoteTags::Table)
+                    .col(pk_auto(NoteTags::Id))
+                    .col(integer(NoteTags::NoteId))
+                    .col(integer(NoteTags::TagId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_tags-note_id")
+                            .from(NoteTags::Table, NoteTags::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_tags-tag_id")
+                            .from(NoteTags::Table, NoteTags::TagId)
+                            .to(Tags::Table, Tags::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_tags-note_id-tag_id")
+                    .table(NoteTags::Table)
+                    .col(NoteTags::NoteId)
+                    .col(NoteTags::TagId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteTags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteTags {
+    Table,
+    Id,
+    NoteId,
+    TagId,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Tags {
+    Table,
+    Id,
+}
@@ -0,0 +1,83 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(Notebooks::Table)
+                    .col(pk_auto(Notebooks::Id))
+                    .col(uuid_uniq(Notebooks::Pid))
+                    .col(integer(Notebooks::UserId))
+                    .col(integer(Notebooks::WorkspaceId))
+                    .col(integer_null(Notebooks::ParentId))
+                    .col(string(Notebooks::Name))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-notebooks-user_id")
+                            .from(Notebooks::Table, Notebooks::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-notebooks-workspace_id")
+                            .from(Notebooks::Table, Notebooks::WorkspaceId)
+                            .to(Workspaces::Table, Workspaces::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-notebooks-parent_id")
+                            .from(Notebooks::Table, Notebooks::ParentId)
+                            .to(Notebooks::Table, Notebooks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-notebooks-parent_id")
+                    .table(Notebooks::Table)
+                    .col(Notebooks::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Notebooks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Notebooks {
+    Table,
+    Id,
+    Pid,
+    UserId,
+    WorkspaceId,
+    ParentId,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Workspaces {
+    Table,
+    Id,
+}
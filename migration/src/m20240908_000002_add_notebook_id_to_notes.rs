@@ -0,0 +1,91 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Notes::Table)
+                    .add_column(ColumnDef::new(Notes::NotebookId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite can not add foreign keys after the fact
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Notes::Table)
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name("fk-notes-notebook_id")
+                                .from_tbl(Notes::Table)
+                                .from_col(Notes::NotebookId)
+                                .to_tbl(Notebooks::Table)
+                                .to_col(Notebooks::Id)
+                                .on_delete(ForeignKeyAction::SetNull),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-notes-notebook_id")
+                    .table(Notes::Table)
+                    .col(Notes::NotebookId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-notes-notebook_id")
+                    .table(Notes::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        if manager.get_database_backend() != DbBackend::Sqlite {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Notes::Table)
+                        .drop_foreign_key(Alias::new("fk-notes-notebook_id"))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Notes::Table)
+                    .drop_column(Notes::NotebookId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Notes {
+    Table,
+    NotebookId,
+}
+
+#[derive(Iden)]
+enum Notebooks {
+    Table,
+    Id,
+}
@@ -0,0 +1,87 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NotebookShares::Table)
+                    .col(pk_auto(NotebookShares::Id))
+                    .col(integer(NotebookShares::NotebookId))
+                    .col(integer(NotebookShares::SharedWithUserId))
+                    .col(string_len(NotebookShares::Permission, 16).default("view"))
+                    .col(string_len(NotebookShares::State, 16).default("pending"))
+                    .col(timestamp_with_time_zone_null(NotebookShares::ExpiresAt))
+                    .col(integer_null(NotebookShares::SharedByUserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-notebook_shares-notebook_id")
+                            .from(NotebookShares::Table, NotebookShares::NotebookId)
+                            .to(Notebooks::Table, Notebooks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-notebook_shares-shared_with_user_id")
+                            .from(NotebookShares::Table, NotebookShares::SharedWithUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-notebook_shares-shared_by_user_id")
+                            .from(NotebookShares::Table, NotebookShares::SharedByUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-notebook_shares-notebook_id-shared_with_user_id")
+                    .table(NotebookShares::Table)
+                    .col(NotebookShares::NotebookId)
+                    .col(NotebookShares::SharedWithUserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NotebookShares::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NotebookShares {
+    Table,
+    Id,
+    NotebookId,
+    SharedWithUserId,
+    Permission,
+    State,
+    ExpiresAt,
+    SharedByUserId,
+}
+
+#[derive(DeriveIden)]
+enum Notebooks {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,72 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NoteFavourites::Table)
+                    .col(pk_auto(NoteFavourites::Id))
+                    .col(integer(NoteFavourites::NoteId))
+                    .col(integer(NoteFavourites::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_favourites-note_id")
+                            .from(NoteFavourites::Table, NoteFavourites::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_favourites-user_id")
+                            .from(NoteFavourites::Table, NoteFavourites::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_favourites-user_id-note_id")
+                    .table(NoteFavourites::Table)
+                    .col(NoteFavourites::UserId)
+                    .col(NoteFavourites::NoteId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteFavourites::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteFavourites {
+    Table,
+    Id,
+    NoteId,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,80 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(Attachments::Table)
+                    .col(pk_auto(Attachments::Id))
+                    .col(uuid_uniq(Attachments::Pid))
+                    .col(integer(Attachments::NoteId))
+                    .col(integer_null(Attachments::UploadedByUserId))
+                    .col(string(Attachments::FileName))
+                    .col(string(Attachments::ContentType))
+                    .col(big_integer(Attachments::Size))
+                    .col(string(Attachments::StorageKey))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachments-note_id")
+                            .from(Attachments::Table, Attachments::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-attachments-uploaded_by_user_id")
+                            .from(Attachments::Table, Attachments::UploadedByUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-attachments-note_id")
+                    .table(Attachments::Table)
+                    .col(Attachments::NoteId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Attachments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Attachments {
+    Table,
+    Id,
+    Pid,
+    NoteId,
+    UploadedByUserId,
+    FileName,
+    ContentType,
+    Size,
+    StorageKey,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
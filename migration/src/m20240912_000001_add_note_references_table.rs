@@ -0,0 +1,76 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NoteReferences::Table)
+                    .col(pk_auto(NoteReferences::Id))
+                    .col(integer(NoteReferences::SourceNoteId))
+                    .col(integer(NoteReferences::TargetNoteId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_references-source_note_id")
+                            .from(NoteReferences::Table, NoteReferences::SourceNoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_references-target_note_id")
+                            .from(NoteReferences::Table, NoteReferences::TargetNoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_references-source_note_id-target_note_id")
+                    .table(NoteReferences::Table)
+                    .col(NoteReferences::SourceNoteId)
+                    .col(NoteReferences::TargetNoteId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_references-target_note_id")
+                    .table(NoteReferences::Table)
+                    .col(NoteReferences::TargetNoteId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteReferences::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteReferences {
+    Table,
+    Id,
+    SourceNoteId,
+    TargetNoteId,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
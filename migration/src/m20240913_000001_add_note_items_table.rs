@@ -0,0 +1,81 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(NoteItems::Table)
+                    .col(pk_auto(NoteItems::Id))
+                    .col(uuid_uniq(NoteItems::Pid))
+                    .col(integer(NoteItems::NoteId))
+                    .col(text(NoteItems::Text))
+                    .col(boolean(NoteItems::Checked).default(false))
+                    .col(integer(NoteItems::Position))
+                    .col(integer_null(NoteItems::CheckedByUserId))
+                    .col(timestamp_with_time_zone_null(NoteItems::CheckedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_items-note_id")
+                            .from(NoteItems::Table, NoteItems::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-note_items-checked_by_user_id")
+                            .from(NoteItems::Table, NoteItems::CheckedByUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-note_items-note_id-position")
+                    .table(NoteItems::Table)
+                    .col(NoteItems::NoteId)
+                    .col(NoteItems::Position)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteItems::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteItems {
+    Table,
+    Id,
+    Pid,
+    NoteId,
+    Text,
+    Checked,
+    Position,
+    CheckedByUserId,
+    CheckedAt,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,80 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_auto_tz(Reminders::Table)
+                    .col(pk_auto(Reminders::Id))
+                    .col(uuid_uniq(Reminders::Pid))
+                    .col(integer(Reminders::NoteId))
+                    .col(integer(Reminders::UserId))
+                    .col(timestamp_with_time_zone(Reminders::DueAt))
+                    .col(timestamp_with_time_zone(Reminders::RemindAt))
+                    .col(string_null(Reminders::Recurrence))
+                    .col(timestamp_with_time_zone_null(Reminders::SentAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-reminders-note_id")
+                            .from(Reminders::Table, Reminders::NoteId)
+                            .to(Notes::Table, Notes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-reminders-user_id")
+                            .from(Reminders::Table, Reminders::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-reminders-remind_at")
+                    .table(Reminders::Table)
+                    .col(Reminders::RemindAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Reminders::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Reminders {
+    Table,
+    Id,
+    Pid,
+    NoteId,
+    UserId,
+    DueAt,
+    RemindAt,
+    Recurrence,
+    SentAt,
+}
+
+#[derive(DeriveIden)]
+enum Notes {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
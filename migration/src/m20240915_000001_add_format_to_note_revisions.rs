@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteRevisions::Table)
+                    .add_column(
+                        ColumnDef::new(NoteRevisions::Format)
+                            .string_len(16)
+                            .not_null()
+                            .default("plain")
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // the format was not recorded so far, the note's current one is the best guess
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(NoteRevisions::Table)
+                    .value(
+                        NoteRevisions::Format,
+                        SimpleExpr::SubQuery(
+                            None,
+                            Box::new(
+                                Query::select()
+                                    .column((Notes::Table, Notes::Format))
+                                    .from(Notes::Table)
+                                    .and_where(
+                                        Expr::col((Notes::Table, Notes::Id))
+                                            .equals((NoteRevisions::Table, NoteRevisions::NoteId)),
+                                    )
+                                    .to_owned()
+                                    .into_sub_query_statement(),
+                            ),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteRevisions::Table)
+                    .drop_column(NoteRevisions::Format)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NoteRevisions {
+    Table,
+    NoteId,
+    Format,
+}
+
+#[derive(Iden)]
+enum Notes {
+    Table,
+    Id,
+    Format,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupMembers::Table)
+                    .add_column(
+                        ColumnDef::new(GroupMembers::State)
+                            .string_len(16)
+                            .not_null()
+                            .default("pending")
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // members added before they could accept keep their access
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(GroupMembers::Table)
+                    .value(GroupMembers::State, "accepted")
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupMembers::Table)
+                    .drop_column(GroupMembers::State)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum GroupMembers {
+    Table,
+    State,
+}
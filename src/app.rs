@@ -16,9 +16,10 @@ use sea_orm::DatabaseConnection;
 
 use crate::{
     controllers,
-    models::_entities::{ note_shares, notes, users },
+    models::_entities::{ note_revisions, note_shares, notes, users, workspace_members, workspaces },
+    storage::StorageSettings,
     tasks,
-    workers::downloader::DownloadWorker,
+    workers::{downloader::DownloadWorker, reminders::ReminderWorker},
 };
 
 pub struct App;
@@ -40,31 +41,56 @@ impl Hooks for App {
         create_app::<Self, Migrator>(mode, environment).await
     }
 
+    async fn after_context(mut ctx: AppContext) -> Result<AppContext> {
+        ctx.storage = StorageSettings::from_config(&ctx.config)?.storage()?.into();
+        Ok(ctx)
+    }
+
     fn routes(_ctx: &AppContext) -> AppRoutes {
         AppRoutes::with_default_routes()
             .prefix("/api")
             .add_route(controllers::notes::routes())
             .add_route(controllers::auth::routes())
             .add_route(controllers::user::routes())
+            .add_route(controllers::groups::routes())
+            .add_route(controllers::workspaces::routes())
+            .add_route(controllers::tags::routes())
+            .add_route(controllers::notebooks::routes())
+            .add_route(controllers::public::routes())
     }
 
     fn connect_workers<'a>(p: &'a mut Processor, ctx: &'a AppContext) {
         p.register(DownloadWorker::build(ctx));
+        p.register(ReminderWorker::build(ctx));
     }
 
     fn register_tasks(tasks: &mut Tasks) {
         tasks.register(tasks::seed::SeedData);
+        tasks.register(tasks::purge_expired_shares::PurgeExpiredShares);
+        tasks.register(tasks::purge_trash::PurgeTrash);
+        tasks.register(tasks::send_reminders::SendReminders);
+        tasks.register(tasks::transfer_notes::TransferNotes);
     }
 
     async fn truncate(db: &DatabaseConnection) -> Result<()> {
         truncate_table(db, users::Entity).await?;
         truncate_table(db, notes::Entity).await?;
+        truncate_table(db, workspaces::Entity).await?;
         Ok(())
     }
 
     async fn seed(db: &DatabaseConnection, base: &Path) -> Result<()> {
         db::seed::<users::ActiveModel>(db, &base.join("users.yaml").display().to_string()).await?;
+        db::seed::<workspaces::ActiveModel>(db, &base.join("workspaces.yaml").display().to_string()).await?;
+        db::seed::<workspace_members::ActiveModel>(
+            db,
+            &base.join("workspace_members.yaml").display().to_string()
+        ).await?;
         db::seed::<notes::ActiveModel>(db, &base.join("notes.yaml").display().to_string()).await?;
+        db::seed::<note_revisions::ActiveModel>(
+            db,
+            &base.join("note_revisions.yaml").display().to_string()
+        ).await?;
         db::seed::<note_shares::ActiveModel>(
             db,
             &base.join("note_shares.yaml").display().to_string()
@@ -6,10 +6,19 @@ use crate::{
     mailers::auth::AuthMailer,
     models::{
         _entities::users,
+        note_invitations,
         users::{LoginParams, RegisterParams},
     },
     views::auth::LoginResponse,
 };
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RegisterRequest {
+    #[serde(flatten)]
+    pub user: RegisterParams,
+    /// The token from an invitation email, when signing up through one
+    pub invite_token: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct VerifyParams {
     pub token: String,
@@ -27,12 +36,15 @@ pub struct ResetParams {
 }
 
 /// Register function creates a new user with the given parameters and sends a
-/// welcome email to the user
+/// welcome email to the user. Signing up with the token from an invitation
+/// email sent to the same address turns the invitations into shares right
+/// away, since the token proves the user received it.
 #[debug_handler]
 async fn register(
     State(ctx): State<AppContext>,
-    Json(params): Json<RegisterParams>,
+    Json(request): Json<RegisterRequest>,
 ) -> Result<Response> {
+    let params = request.user;
     let res = users::Model::create_with_password(&ctx.db, &params).await;
 
     let user = match res {
@@ -54,11 +66,19 @@ async fn register(
 
     AuthMailer::send_welcome(&ctx, &user).await?;
 
+    if let Some(token) = &request.invite_token {
+        match note_invitations::Model::redeem(&ctx.db, &user, token).await {
+            Ok(_) | Err(ModelError::EntityNotFound) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
     format::json(())
 }
 
 /// Verify register user. if the user not verified his email, he can't login to
-/// the system.
+/// the system. Notes that were shared with the email address before the
+/// account existed become regular shares once it is verified.
 #[debug_handler]
 async fn verify(
     State(ctx): State<AppContext>,
@@ -71,6 +91,7 @@ async fn verify(
     } else {
         let active_model = user.into_active_model();
         let user = active_model.verified(&ctx.db).await?;
+        note_invitations::Model::accept_for_user(&ctx.db, &user).await?;
         tracing::info!(pid = user.pid.to_string(), "user verified");
     }
 
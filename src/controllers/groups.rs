@@ -0,0 +1,256 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unused_async)]
+use axum::debug_handler;
+use loco_rs::{controller::bad_request, prelude::*};
+use sea_orm::{QueryOrder, QuerySelect, QueryTrait};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{
+        _entities::{group_members, groups, sea_orm_active_enums::ShareState, users},
+        user_blocks,
+    },
+    views::groups::GroupResponse,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Params {
+    pub name: String,
+}
+
+impl Params {
+    fn update(&self, item: &mut groups::ActiveModel) {
+        item.name = Set(self.name.clone());
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MemberParams {
+    pub user_pid: String,
+}
+
+/// Loads a group the user is a member of or was invited to. Groups of other
+/// people are a 404.
+async fn load_group(ctx: &AppContext, pid: &str, user_id: i32) -> Result<groups::Model> {
+    let group = match groups::Model::find_by_pid(&ctx.db, pid).await {
+        Ok(group) => group,
+        Err(ModelError::EntityNotFound) => return not_found(),
+        Err(err) => return Err(err.into()),
+    };
+    if group.membership(&ctx.db, user_id).await?.is_none() {
+        return not_found();
+    }
+    Ok(group)
+}
+
+/// Loads a group only its owner may change
+async fn load_owned_group(ctx: &AppContext, pid: &str, user_id: i32) -> Result<groups::Model> {
+    let group = load_group(ctx, pid, user_id).await?;
+    if group.owner_user_id != user_id {
+        return unauthorized("only the group owner can do this");
+    }
+    Ok(group)
+}
+
+async fn group_response(ctx: &AppContext, group: &groups::Model) -> Result<GroupResponse> {
+    let owner = users::Entity::find_by_id(group.owner_user_id)
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| Error::NotFound)?;
+    let members = group_members::Entity::find()
+        .filter(group_members::Column::GroupId.eq(group.id))
+        .find_also_related(users::Entity)
+        .order_by_asc(group_members::Column::UserId)
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .filter_map(|(member, user)| Some((member, user?)))
+        .collect::<Vec<_>>();
+    Ok(GroupResponse::new(group, &owner, &members))
+}
+
+#[debug_handler]
+pub async fn list(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+
+    let groups = groups::Entity::find()
+        .filter(
+            groups::Column::Id.in_subquery(
+                group_members::Entity::find()
+                    .select_only()
+                    .column(group_members::Column::GroupId)
+                    .filter(group_members::Column::UserId.eq(user.id))
+                    .into_query(),
+            ),
+        )
+        .order_by_asc(groups::Column::Id)
+        .all(&ctx.db)
+        .await?;
+
+    let mut response = Vec::with_capacity(groups.len());
+    for group in &groups {
+        response.push(group_response(&ctx, group).await?);
+    }
+    format::json(response)
+}
+
+/// Creates a group with the current user as its owner and first member
+#[debug_handler]
+pub async fn add(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Json(params): Json<Params>,
+) -> Result<Response> {
+    if params.name.trim().is_empty() {
+        return bad_request("name must not be empty");
+    }
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+
+    let txn = ctx.db.begin().await?;
+    let mut item = groups::ActiveModel {
+        owner_user_id: Set(user.id),
+        ..Default::default()
+    };
+    params.update(&mut item);
+    let group = item.insert(&txn).await?;
+    group.add_member(&txn, user.id).await?;
+    txn.commit().await?;
+
+    format::json(group_response(&ctx, &group).await?)
+}
+
+#[debug_handler]
+pub async fn get_one(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let group = load_group(&ctx, &pid, user.id).await?;
+    format::json(group_response(&ctx, &group).await?)
+}
+
+#[debug_handler]
+pub async fn update(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<Params>,
+) -> Result<Response> {
+    if params.name.trim().is_empty() {
+        return bad_request("name must not be empty");
+    }
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let group = load_owned_group(&ctx, &pid, user.id).await?;
+    let mut item = group.into_active_model();
+    params.update(&mut item);
+    let group = item.update(&ctx.db).await?;
+    format::json(group_response(&ctx, &group).await?)
+}
+
+/// Deletes the group. Notes shared with it are no longer shared with its
+/// members.
+#[debug_handler]
+pub async fn remove(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let group = load_owned_group(&ctx, &pid, user.id).await?;
+    group.delete(&ctx.db).await?;
+    format::empty()
+}
+
+/// Invites a user to the group. Once they accept, they get access to every
+/// note shared with it.
+#[debug_handler]
+pub async fn add_member(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<MemberParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let group = load_owned_group(&ctx, &pid, user.id).await?;
+    let member = users::Model::find_by_pid_or_not_found(&ctx.db, &params.user_pid).await?;
+    if user_blocks::Model::exists(&ctx.db, member.id, user.id).await? {
+        return unauthorized("user does not accept invitations from you");
+    }
+    group.invite_member(&ctx.db, member.id).await?;
+    format::json(group_response(&ctx, &group).await?)
+}
+
+/// Moves the caller's membership of the group to `next`
+async fn transition_membership(
+    ctx: &AppContext,
+    pid: &str,
+    auth: &auth::JWT,
+    next: ShareState,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let group = load_group(ctx, pid, user.id).await?;
+    let txn = ctx.db.begin().await?;
+    group.transition_membership(&txn, user.id, next).await?;
+    txn.commit().await?;
+    format::json(group_response(ctx, &group).await?)
+}
+
+#[debug_handler]
+pub async fn accept(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    transition_membership(&ctx, &pid, &auth, ShareState::Accepted).await
+}
+
+#[debug_handler]
+pub async fn decline(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    transition_membership(&ctx, &pid, &auth, ShareState::Declined).await
+}
+
+/// Removes a member from the group. Owners can remove anyone but themselves,
+/// and members can remove themselves to leave the group.
+#[debug_handler]
+pub async fn remove_member(
+    auth: auth::JWT,
+    Path((pid, user_pid)): Path<(String, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let group = load_group(&ctx, &pid, user.id).await?;
+    let member = users::Model::find_by_pid_or_not_found(&ctx.db, &user_pid).await?;
+
+    if member.id == group.owner_user_id {
+        return bad_request("the group owner can not leave the group");
+    }
+    if member.id != user.id && group.owner_user_id != user.id {
+        return unauthorized("only the group owner can remove other members");
+    }
+
+    group_members::Entity::delete_many()
+        .filter(group_members::Column::GroupId.eq(group.id))
+        .filter(group_members::Column::UserId.eq(member.id))
+        .exec(&ctx.db)
+        .await?;
+    format::empty()
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("groups")
+        .add("/", get(list))
+        .add("/", post(add))
+        .add("/:pid", get(get_one))
+        .add("/:pid", post(update))
+        .add("/:pid", delete(remove))
+        .add("/:pid/accept", post(accept))
+        .add("/:pid/decline", post(decline))
+        .add("/:pid/members", post(add_member))
+        .add("/:pid/members/:user_pid", delete(remove_member))
+}
@@ -1,3 +1,8 @@
 pub mod auth;
+pub mod groups;
+pub mod notebooks;
 pub mod notes;
+pub mod public;
+pub mod tags;
 pub mod user;
+pub mod workspaces;
@@ -0,0 +1,476 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unused_async)]
+use std::collections::HashMap;
+
+use axum::{debug_handler, extract::Query, routing::patch};
+use loco_rs::{controller::bad_request, prelude::*};
+use sea_orm::{prelude::DateTimeWithTimeZone, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    controllers::{notes::ResharePolicyParams, workspaces::CurrentWorkspace},
+    models::{
+        _entities::{
+            notebook_shares,
+            sea_orm_active_enums::{SharePermission, ShareState, WorkspaceRole},
+            users,
+        },
+        notebooks,
+        shares::{
+            check_reshare, find_incoming, may_update_shares, transition_user_share,
+            upsert_user_share, ShareGrant,
+        },
+        user_blocks,
+    },
+    views::notebooks::{
+        IncomingNotebookShareResponse, NotebookResponse, NotebookShareResponse,
+    },
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Params {
+    pub name: String,
+    /// Creates the notebook inside this one
+    pub parent_pid: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RenameParams {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MoveParams {
+    /// `null` moves the notebook to the top level
+    pub parent_pid: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareParams {
+    pub shared_with_pid: String,
+    #[serde(default)]
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    /// Lists the notebooks directly inside this one instead of all of the
+    /// caller's notebooks
+    pub parent: Option<String>,
+}
+
+/// What happens to the contents of a deleted notebook
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteMode {
+    /// Notes and nested notebooks move up into the parent notebook
+    #[default]
+    Reparent,
+    /// Nested notebooks are deleted too and all notes go to the trash
+    Cascade,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteQuery {
+    #[serde(default)]
+    pub mode: DeleteMode,
+}
+
+/// Loads a notebook of the active workspace the user owns, or one shared
+/// with them directly or through a notebook it is nested in. Other notebooks
+/// are a 404.
+pub async fn load_notebook(
+    ctx: &AppContext,
+    pid: &str,
+    workspace: &CurrentWorkspace,
+) -> Result<notebooks::Model> {
+    let notebook = match notebooks::Model::find_by_pid(&ctx.db, pid).await {
+        Ok(notebook) if notebook.workspace_id == workspace.workspace.id => notebook,
+        Ok(_) | Err(ModelError::EntityNotFound | ModelError::Any(_)) => return not_found(),
+        Err(err) => return Err(err.into()),
+    };
+    if notebook.user_id != workspace.user.id
+        && notebook.shared_permission(&ctx.db, workspace.user.id).await?.is_none()
+    {
+        return not_found();
+    }
+    Ok(notebook)
+}
+
+/// Loads a notebook only its owner may change
+async fn load_owned_notebook(
+    ctx: &AppContext,
+    pid: &str,
+    workspace: &CurrentWorkspace,
+) -> Result<notebooks::Model> {
+    let notebook = load_notebook(ctx, pid, workspace).await?;
+    if notebook.user_id != workspace.user.id {
+        return unauthorized("only the notebook owner can do this");
+    }
+    Ok(notebook)
+}
+
+fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return bad_request("name must not be empty");
+    }
+    Ok(name.to_string())
+}
+
+async fn notebook_response(ctx: &AppContext, notebook: &notebooks::Model) -> Result<NotebookResponse> {
+    let parent = match notebook.parent_id {
+        Some(parent_id) => notebooks::Entity::find_by_id(parent_id).one(&ctx.db).await?,
+        None => None,
+    };
+    Ok(NotebookResponse::new(notebook, parent.as_ref().map(|parent| &parent.pid)))
+}
+
+/// All notebooks of the caller in the active workspace, by name. With
+/// `parent`, only the notebooks directly inside that notebook, which may
+/// also be one shared with the caller.
+#[debug_handler]
+pub async fn list(
+    workspace: CurrentWorkspace,
+    Query(query): Query<ListQuery>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let (notebooks, parent_pids) = match query.parent.as_deref() {
+        Some(pid) => {
+            let parent = load_notebook(&ctx, pid, &workspace).await?;
+            let children = notebooks::Entity::find()
+                .filter(notebooks::Column::ParentId.eq(parent.id))
+                .order_by_asc(notebooks::Column::Name)
+                .order_by_asc(notebooks::Column::Id)
+                .all(&ctx.db)
+                .await?;
+            (children, HashMap::from([(parent.id, parent.pid)]))
+        }
+        None => {
+            let notebooks = notebooks::Entity::find()
+                .filter(notebooks::Column::UserId.eq(workspace.user.id))
+                .filter(notebooks::Column::WorkspaceId.eq(workspace.workspace.id))
+                .order_by_asc(notebooks::Column::Name)
+                .order_by_asc(notebooks::Column::Id)
+                .all(&ctx.db)
+                .await?;
+            let pids = notebooks.iter().map(|notebook| (notebook.id, notebook.pid)).collect();
+            (notebooks, pids)
+        }
+    };
+
+    format::json(
+        notebooks
+            .iter()
+            .map(|notebook| {
+                let parent_pid = notebook.parent_id.and_then(|id| parent_pids.get(&id));
+                NotebookResponse::new(notebook, parent_pid)
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[debug_handler]
+pub async fn add(
+    workspace: CurrentWorkspace,
+    State(ctx): State<AppContext>,
+    Json(params): Json<Params>,
+) -> Result<Response> {
+    if workspace.role == WorkspaceRole::Guest {
+        return unauthorized("guests can not create notebooks");
+    }
+    let name = validate_name(&params.name)?;
+    let parent_id = match params.parent_pid.as_deref() {
+        Some(pid) => Some(load_owned_notebook(&ctx, pid, &workspace).await?.id),
+        None => None,
+    };
+
+    let notebook = notebooks::ActiveModel {
+        user_id: Set(workspace.user.id),
+        workspace_id: Set(workspace.workspace.id),
+        parent_id: Set(parent_id),
+        name: Set(name),
+        ..Default::default()
+    }
+    .insert(&ctx.db)
+    .await?;
+    format::json(notebook_response(&ctx, &notebook).await?)
+}
+
+#[debug_handler]
+pub async fn get_one(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let notebook = load_notebook(&ctx, &pid, &workspace).await?;
+    format::json(notebook_response(&ctx, &notebook).await?)
+}
+
+#[debug_handler]
+pub async fn rename(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<RenameParams>,
+) -> Result<Response> {
+    let name = validate_name(&params.name)?;
+    let notebook = load_owned_notebook(&ctx, &pid, &workspace).await?;
+    let mut item = notebook.into_active_model();
+    item.name = Set(name);
+    let notebook = item.update(&ctx.db).await?;
+    format::json(notebook_response(&ctx, &notebook).await?)
+}
+
+/// Moves a notebook with everything in it into another notebook of the
+/// caller, or to the top level
+#[debug_handler]
+pub async fn move_notebook(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<MoveParams>,
+) -> Result<Response> {
+    let notebook = load_owned_notebook(&ctx, &pid, &workspace).await?;
+    let parent_id = match params.parent_pid.as_deref() {
+        Some(parent_pid) => {
+            let parent = load_owned_notebook(&ctx, parent_pid, &workspace).await?;
+            if parent.ancestor_ids(&ctx.db).await?.contains(&notebook.id) {
+                return bad_request("can not move a notebook into itself or a notebook inside it");
+            }
+            Some(parent.id)
+        }
+        None => None,
+    };
+
+    let mut item = notebook.into_active_model();
+    item.parent_id = Set(parent_id);
+    let notebook = item.update(&ctx.db).await?;
+    format::json(notebook_response(&ctx, &notebook).await?)
+}
+
+/// Deletes a notebook. By default its notes and notebooks move up into its
+/// parent; with `mode=cascade` they are deleted, notes going to the trash.
+#[debug_handler]
+pub async fn remove(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    Query(query): Query<DeleteQuery>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let notebook = load_owned_notebook(&ctx, &pid, &workspace).await?;
+    match query.mode {
+        DeleteMode::Reparent => notebook.delete_reparenting(&ctx.db).await?,
+        DeleteMode::Cascade => notebook.delete_with_notes(&ctx.db).await?,
+    }
+    format::empty()
+}
+
+/// Shares the notebook with a user. Once they accept, they can access every
+/// note in it and in the notebooks inside it, including ones added later.
+/// Sharing again updates the permission and expiry. Recipients may reshare
+/// under the same policy as notes.
+#[debug_handler]
+pub async fn share(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ShareParams>,
+) -> Result<Response> {
+    if params.expires_at.is_some_and(|expires_at| expires_at <= chrono::Local::now()) {
+        return bad_request("expires_at must be in the future");
+    }
+    let user = &workspace.user;
+    let notebook = load_notebook(&ctx, &pid, &workspace).await?;
+    let own = if notebook.user_id == user.id {
+        None
+    } else {
+        Some(
+            notebook
+                .shared_permission(&ctx.db, user.id)
+                .await?
+                .ok_or_else(|| Error::NotFound)?,
+        )
+    };
+    check_reshare(own, notebook.allow_reshare, params.permission).or_else(unauthorized)?;
+    let shared_with =
+        users::Model::find_by_pid_or_not_found(&ctx.db, &params.shared_with_pid).await?;
+    if shared_with.id == user.id {
+        return bad_request("can not share a notebook with yourself");
+    }
+    if user_blocks::Model::exists(&ctx.db, shared_with.id, user.id).await? {
+        return unauthorized("user does not accept shares from you");
+    }
+
+    let grant = ShareGrant {
+        shared_with_user_id: shared_with.id,
+        permission: params.permission,
+        expires_at: params.expires_at,
+        shared_by_user_id: user.id,
+        update_existing: may_update_shares(own),
+    };
+    let txn = ctx.db.begin().await?;
+    let share = upsert_user_share::<notebook_shares::Entity, _>(
+        &txn,
+        &workspace.workspace,
+        notebook.id,
+        &grant,
+    )
+    .await?;
+    txn.commit().await?;
+
+    format::json(NotebookShareResponse::new(&share, &notebook, &shared_with))
+}
+
+/// Lets the owner decide whether recipients may reshare the notebook
+#[debug_handler]
+pub async fn update_reshare_policy(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ResharePolicyParams>,
+) -> Result<Response> {
+    let notebook = load_owned_notebook(&ctx, &pid, &workspace).await?;
+    let mut notebook = notebook.into_active_model();
+    notebook.allow_reshare = Set(params.allow_reshare);
+    let notebook = notebook.update(&ctx.db).await?;
+    format::json(notebook_response(&ctx, &notebook).await?)
+}
+
+#[debug_handler]
+pub async fn get_shares(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let notebook = load_owned_notebook(&ctx, &pid, &workspace).await?;
+    let shares = notebook_shares::Entity::find()
+        .filter(notebook_shares::Column::NotebookId.eq(notebook.id))
+        .find_also_related(users::Entity)
+        .order_by_asc(notebook_shares::Column::Id)
+        .all(&ctx.db)
+        .await?;
+
+    format::json(
+        shares
+            .iter()
+            .filter_map(|(share, shared_with)| {
+                shared_with
+                    .as_ref()
+                    .map(|shared_with| NotebookShareResponse::new(share, &notebook, shared_with))
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Removes a share of the notebook. Owners can remove any share, and
+/// recipients can remove their own to leave the notebook.
+#[debug_handler]
+pub async fn revoke_share(
+    workspace: CurrentWorkspace,
+    Path((pid, user_pid)): Path<(String, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let notebook = load_notebook(&ctx, &pid, &workspace).await?;
+    let shared_with = users::Model::find_by_pid_or_not_found(&ctx.db, &user_pid).await?;
+    if notebook.user_id != workspace.user.id && shared_with.id != workspace.user.id {
+        return unauthorized("only the notebook owner can do this");
+    }
+
+    let result = notebook_shares::Entity::delete_many()
+        .filter(notebook_shares::Column::NotebookId.eq(notebook.id))
+        .filter(notebook_shares::Column::SharedWithUserId.eq(shared_with.id))
+        .exec(&ctx.db)
+        .await?;
+    if result.rows_affected == 0 {
+        return not_found();
+    }
+
+    format::empty()
+}
+
+/// Moves the caller's share of a notebook to `next`. Pending shares don't
+/// grant access yet, so the notebook is looked up without an access check.
+async fn transition_share(
+    ctx: &AppContext,
+    pid: &str,
+    workspace: &CurrentWorkspace,
+    next: ShareState,
+) -> Result<Response> {
+    let notebook = match notebooks::Model::find_by_pid(&ctx.db, pid).await {
+        Ok(notebook) if notebook.workspace_id == workspace.workspace.id => notebook,
+        Ok(_) | Err(ModelError::EntityNotFound | ModelError::Any(_)) => return not_found(),
+        Err(err) => return Err(err.into()),
+    };
+    let share = transition_user_share::<notebook_shares::Entity, _>(
+        &ctx.db,
+        notebook.id,
+        workspace.user.id,
+        next,
+    )
+    .await?;
+    format::json(NotebookShareResponse::new(&share, &notebook, &workspace.user))
+}
+
+#[debug_handler]
+pub async fn accept_share(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    transition_share(&ctx, &pid, &workspace, ShareState::Accepted).await
+}
+
+#[debug_handler]
+pub async fn decline_share(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    transition_share(&ctx, &pid, &workspace, ShareState::Declined).await
+}
+
+/// Notebooks of the active workspace shared with the caller, whatever the
+/// state of the share. Expired shares are left out.
+#[debug_handler]
+pub async fn get_incoming_shares(
+    workspace: CurrentWorkspace,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let shares = find_incoming::<notebook_shares::Entity>(workspace.user.id)
+        .find_also_related(notebooks::Entity)
+        .filter(notebooks::Column::WorkspaceId.eq(workspace.workspace.id))
+        .order_by_asc(notebook_shares::Column::Id)
+        .all(&ctx.db)
+        .await?;
+
+    let mut response = Vec::with_capacity(shares.len());
+    for (share, notebook) in shares {
+        let Some(notebook) = notebook else { continue };
+        let owner = users::Entity::find_by_id(notebook.user_id)
+            .one(&ctx.db)
+            .await?
+            .ok_or_else(|| Error::NotFound)?;
+        response.push(IncomingNotebookShareResponse::new(&share, &notebook, &owner));
+    }
+    format::json(response)
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("notebooks")
+        .add("/", get(list))
+        .add("/", post(add))
+        .add("/shares/incoming", get(get_incoming_shares))
+        .add("/:pid", get(get_one))
+        .add("/:pid", patch(rename))
+        .add("/:pid", delete(remove))
+        .add("/:pid/move", post(move_notebook))
+        .add("/:pid/share", post(share))
+        .add("/:pid/shares", get(get_shares))
+        .add("/:pid/reshare-policy", patch(update_reshare_policy))
+        .add("/:pid/share/accept", post(accept_share))
+        .add("/:pid/share/decline", post(decline_share))
+        .add("/:pid/share/:user_pid", delete(revoke_share))
+}
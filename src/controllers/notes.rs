@@ -1,17 +1,135 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::unnecessary_struct_initialization)]
 #![allow(clippy::unused_async)]
-use axum::debug_handler;
-use loco_rs::prelude::*;
+use std::path::Path as StoragePath;
+
+use axum::{
+    body::Body,
+    debug_handler,
+    extract::{multipart::MultipartError, Multipart, Query},
+    http::{header, HeaderMap, StatusCode},
+    routing::patch,
+};
+use loco_rs::{
+    controller::{bad_request, ErrorDetail},
+    prelude::*,
+};
 use serde::{Deserialize, Serialize};
 use crate::models::_entities::notes::{ActiveModel, Column, Entity, Model};
 use crate::models::_entities::users;
-use crate::models::_entities::note_shares::{self, ActiveModel as NoteShareActiveModel};
+use crate::models::_entities::note_shares;
+use crate::models::_entities::{
+    groups, note_favourites, note_group_shares, note_invitations, note_links, note_references,
+    note_revisions, note_tags,
+};
+use crate::models::_entities::sea_orm_active_enums::{
+    NoteFormat, SharePermission, ShareState, WorkspaceRole,
+};
+use crate::models::_entities::workspaces;
+use crate::models::notes::{
+    shared_with, visible_to, NoteCursor, NotePage, NoteSort, SearchHit, SortOrder,
+};
+use crate::models::{attachments, note_items, notebooks, reminders, tags, user_blocks};
+use crate::models::shares::{
+    check_reshare, find_incoming, may_update_shares, transition_user_share, upsert_user_share,
+    ShareGrant,
+};
+use crate::views::attachments::AttachmentResponse;
+use crate::views::note_items::NoteItemResponse;
+use crate::views::reminders::ReminderResponse;
+use crate::views::tags::TagResponse;
+use crate::controllers::workspaces::CurrentWorkspace;
+use crate::mailers::shares::ShareMailer;
+use crate::views::notes::{
+    IncomingShareResponse, LinkedNoteResponse, NoteGraphResponse, NoteGroupShareResponse,
+    NoteHtmlResponse, NoteInvitationResponse, NoteLinkResponse,
+    NotePageResponse, NoteRevisionDiffResponse, NoteRevisionResponse, NoteSearchResultResponse,
+    NoteShareResponse,
+};
 use sea_orm::*;
+use sea_orm::prelude::DateTimeWithTimeZone;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ShareNoteParams {
-    pub shared_with_user_id: i32,
+    /// Deprecated, prefer `shared_with_pid` or `shared_with_email`
+    pub shared_with_user_id: Option<i32>,
+    pub shared_with_pid: Option<String>,
+    /// Users without an account get an invitation by email
+    pub shared_with_email: Option<String>,
+    /// Shares with every current and future member of the group
+    pub shared_with_group_pid: Option<String>,
+    #[serde(default)]
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+}
+
+impl ShareNoteParams {
+    fn validate(&self) -> Result<()> {
+        if self.expires_at.is_some_and(|expires_at| expires_at <= chrono::Local::now()) {
+            return bad_request("expires_at must be in the future");
+        }
+        Ok(())
+    }
+}
+
+/// Who a note is shared with, resolved from `ShareNoteParams`
+enum ShareTarget {
+    User(i32),
+    Invitee(String),
+    Group(groups::Model),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ShareOutcome {
+    Share(note_shares::Model),
+    Invitation(#[serde(serialize_with = "serialize_invitation")] note_invitations::Model),
+    GroupShare(NoteGroupShareResponse),
+}
+
+/// Invitations are shown without their token, which only goes out by email
+fn serialize_invitation<S>(
+    invitation: &note_invitations::Model,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    NoteInvitationResponse::new(invitation).serialize(serializer)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareMultipleParams {
+    pub note_ids: Vec<i32>,
+    #[serde(flatten)]
+    pub share: ShareNoteParams,
+}
+
+/// Outcome of sharing one note in `share_multiple_notes`
+#[derive(Debug, Serialize)]
+struct ShareMultipleResult {
+    note_id: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    share: Option<ShareOutcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ShareMultipleResponse {
+    results: Vec<ShareMultipleResult>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreateLinkParams {
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    /// Visitors have to provide this password to open the link
+    pub password: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateShareParams {
+    pub permission: SharePermission,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -22,192 +140,1888 @@ pub struct SharedNoteResponse {
     pub shared_by_user_id: i32,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransferNoteParams {
+    pub new_owner_pid: String,
+    /// Keep the previous owner on the note as an editor
+    #[serde(default)]
+    pub keep_access: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResharePolicyParams {
+    pub allow_reshare: bool,
+}
+
+/// Whose notes `GET /api/notes` lists
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteOwnership {
+    Mine,
+    Shared,
+}
+
+/// Paging, sorting and filtering of note listings
+#[derive(Debug, Default, Deserialize)]
+pub struct NoteListQuery {
+    pub limit: Option<u64>,
+    /// `next_cursor` of the previous page
+    pub after: Option<String>,
+    #[serde(default)]
+    pub sort: NoteSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Only used by `GET /api/notes`; both when not given
+    pub owner: Option<NoteOwnership>,
+    pub created_after: Option<DateTimeWithTimeZone>,
+    pub created_before: Option<DateTimeWithTimeZone>,
+    pub updated_after: Option<DateTimeWithTimeZone>,
+    pub updated_before: Option<DateTimeWithTimeZone>,
+    /// Only notes the caller tagged with this tag name
+    pub tag: Option<String>,
+    /// Only notes directly in the notebook with this pid
+    pub notebook: Option<String>,
+    /// Archived notes are left out unless this is `true`
+    #[serde(default)]
+    pub archived: bool,
+    /// `true` lists only the caller's favourites, `false` everything else
+    pub favourite: Option<bool>,
+}
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+impl NoteListQuery {
+    fn filter(&self, user_id: i32, select: Select<Entity>) -> Select<Entity> {
+        let select = if self.archived {
+            select
+        } else {
+            select.filter(Column::ArchivedAt.is_null())
+        };
+        select
+            .apply_if(self.created_after, |select, at| select.filter(Column::CreatedAt.gte(at)))
+            .apply_if(self.created_before, |select, at| select.filter(Column::CreatedAt.lt(at)))
+            .apply_if(self.updated_after, |select, at| select.filter(Column::UpdatedAt.gte(at)))
+            .apply_if(self.updated_before, |select, at| select.filter(Column::UpdatedAt.lt(at)))
+            .apply_if(self.tag.as_deref(), |select, name| {
+                select.filter(tagged_by(user_id, name.trim()))
+            })
+            .apply_if(self.favourite, |select, favourite| {
+                let favourites = note_favourites::Entity::find()
+                    .select_only()
+                    .column(note_favourites::Column::NoteId)
+                    .filter(note_favourites::Column::UserId.eq(user_id))
+                    .into_query();
+                select.filter(if favourite {
+                    Column::Id.in_subquery(favourites)
+                } else {
+                    Column::Id.not_in_subquery(favourites)
+                })
+            })
+    }
+
+    /// Loads the requested page of the notes `select` finds. Filtering by a
+    /// notebook the user can not open is a 404.
+    async fn page(
+        &self,
+        ctx: &AppContext,
+        workspace: &CurrentWorkspace,
+        select: Select<Entity>,
+    ) -> Result<NotePage> {
+        let user_id = workspace.user.id;
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return bad_request(format!("limit must be between 1 and {MAX_PAGE_SIZE}"));
+        }
+        let select = match self.notebook.as_deref() {
+            Some(pid) => {
+                let notebook = super::notebooks::load_notebook(ctx, pid, workspace).await?;
+                select.filter(Column::NotebookId.eq(notebook.id))
+            }
+            None => select,
+        };
+        let after = match self.after.as_deref() {
+            Some(after) => match NoteCursor::decode(after) {
+                Some(cursor) if cursor.is_for(self.sort) => Some(cursor),
+                _ => return bad_request("after is not a cursor of this listing"),
+            },
+            None => None,
+        };
+
+        Ok(Entity::paginate(&ctx.db, self.filter(user_id, select), self.sort, self.order, limit, after.as_ref())
+            .await?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+}
+
+/// Most results a search returns
+const SEARCH_LIMIT: u64 = 50;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Params {
     pub title: Option<String>,
     pub content: Option<String>,
+    /// The version the client edited, an alternative to `If-Match`
+    pub version: Option<i32>,
+    /// Replaces the caller's tags on the note when given
+    pub tags: Option<Vec<String>>,
+    /// The notebook a new note goes into; existing notes move with
+    /// `POST /api/notes/:id/move`
+    pub notebook_pid: Option<String>,
+    /// Keeps the current format when left out, new notes are `plain`
+    pub format: Option<NoteFormat>,
 }
 
 impl Params {
     fn update(&self, item: &mut ActiveModel) {
         item.title = Set(self.title.clone());
         item.content = Set(self.content.clone());
+        if let Some(format) = self.format {
+            item.format = Set(format);
+        }
+    }
+
+    fn tag_names(&self) -> Result<Option<Vec<String>>> {
+        self.tags.as_deref().map(tag_names).transpose()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MoveNoteParams {
+    /// `null` moves the note out of its notebook
+    pub notebook_pid: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoteItemParams {
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReorderNoteItemsParams {
+    /// Every item of the checklist, in the new order
+    pub pids: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReminderParams {
+    pub remind_at: DateTimeWithTimeZone,
+    /// `daily`, `weekly` or a cron expression, leave it out for a one-off
+    pub recurrence: Option<String>,
+}
+
+impl ReminderParams {
+    fn validate(&self) -> Result<Option<String>> {
+        if self.remind_at <= chrono::Local::now() {
+            return bad_request("remind_at must be in the future");
+        }
+        match self.recurrence.as_deref().map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(recurrence) if reminders::Recurrence::parse(recurrence).is_some() => {
+                Ok(Some(recurrence.to_string()))
+            }
+            Some(_) => bad_request("recurrence must be daily, weekly or a cron expression"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnoozeReminderParams {
+    /// Either a time or a number of minutes from now
+    pub until: Option<DateTimeWithTimeZone>,
+    pub minutes: Option<i64>,
+}
+
+/// Longest a reminder can be snoozed by `minutes`, a year
+const MAX_SNOOZE_MINUTES: i64 = 366 * 24 * 60;
+
+impl SnoozeReminderParams {
+    fn until(&self) -> Result<DateTimeWithTimeZone> {
+        let now = chrono::Local::now();
+        let until = match (self.until, self.minutes) {
+            (Some(until), None) => Some(until),
+            (None, Some(minutes)) if (1..=MAX_SNOOZE_MINUTES).contains(&minutes) => {
+                Some((now + chrono::Duration::minutes(minutes)).into())
+            }
+            _ => None,
+        };
+        match until {
+            Some(until) if until > now => Ok(until),
+            _ => bad_request(format!(
+                "give either a future `until` or `minutes` from 1 to {MAX_SNOOZE_MINUTES}"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoteTagsParams {
+    pub tags: Vec<String>,
+}
+
+/// Normalized tag names without duplicates, in the order given
+fn tag_names(names: &[String]) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::with_capacity(names.len());
+    for name in names {
+        let Some(name) = tags::normalize_name(name) else {
+            return bad_request(format!(
+                "tag names must be 1 to {} characters long",
+                tags::MAX_NAME_LENGTH
+            ));
+        };
+        if !normalized.contains(&name) {
+            normalized.push(name);
+        }
     }
+    Ok(normalized)
 }
 
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QuerySelect};
 
-async fn load_item(ctx: &AppContext, id: i32, user_id: i32) -> Result<Model> {
-    let item = Entity::find_by_id(id)
-        .filter(
-            crate::models::_entities::notes::Column::UserId.eq(user_id)
-                .or(
-                    crate::models::_entities::note_shares::Column::NoteId.eq(id)
-                        .and(crate::models::_entities::note_shares::Column::SharedWithUserId.eq(user_id))
-                )
-        )
-        .join(
-            JoinType::LeftJoin,
-            crate::models::_entities::notes::Relation::NoteShares.def()
-        )
+/// Loads a note of the active workspace the user owns, or one shared with
+/// them with at least the `required` permission. Owners always have full
+/// access. Notes in the trash are not found.
+async fn load_item(
+    ctx: &AppContext,
+    id: i32,
+    workspace: &CurrentWorkspace,
+    required: SharePermission,
+) -> Result<Model> {
+    let item = Entity::find_live()
+        .filter(Column::Id.eq(id))
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| Error::NotFound)?;
+    authorize_item(ctx, item, workspace.user.id, required).await
+}
+
+/// Like `load_item`, but only finds notes that are in the trash
+async fn load_trashed_item(
+    ctx: &AppContext,
+    id: i32,
+    workspace: &CurrentWorkspace,
+    required: SharePermission,
+) -> Result<Model> {
+    let item = Entity::find_trashed()
+        .filter(Column::Id.eq(id))
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
         .one(&ctx.db)
+        .await?
+        .ok_or_else(|| Error::NotFound)?;
+    authorize_item(ctx, item, workspace.user.id, required).await
+}
+
+async fn authorize_item(
+    ctx: &AppContext,
+    item: Model,
+    user_id: i32,
+    required: SharePermission,
+) -> Result<Model> {
+    if item.user_id == user_id {
+        return Ok(item);
+    }
+
+    let permission = shared_permission(ctx, &item, user_id)
+        .await?
+        .ok_or_else(|| Error::NotFound)?;
+
+    if permission < required {
+        return unauthorized("insufficient permission on shared note");
+    }
+
+    Ok(item)
+}
+
+/// The highest permission any active share of the note grants the user,
+/// either directly, through one of their groups or through a notebook the
+/// note is in.
+async fn shared_permission(
+    ctx: &AppContext,
+    note: &Model,
+    user_id: i32,
+) -> Result<Option<SharePermission>> {
+    let direct = note_shares::Entity::find_active()
+        .filter(note_shares::Column::NoteId.eq(note.id))
+        .filter(note_shares::Column::SharedWithUserId.eq(user_id))
+        .all(&ctx.db)
         .await?;
-    
-    item.ok_or_else(|| Error::NotFound)
+    let through_groups = note_group_shares::Entity::find_active_for_user(user_id)
+        .filter(note_group_shares::Column::NoteId.eq(note.id))
+        .all(&ctx.db)
+        .await?;
+    let through_notebook = match note.notebook_id {
+        Some(notebook_id) => match notebooks::Entity::find_by_id(notebook_id).one(&ctx.db).await? {
+            Some(notebook) => notebook.shared_permission(&ctx.db, user_id).await?,
+            None => None,
+        },
+        None => None,
+    };
+
+    Ok(direct
+        .into_iter()
+        .map(|share| share.permission)
+        .chain(through_groups.into_iter().map(|share| share.permission))
+        .chain(through_notebook)
+        .max())
 }
 
+async fn load_notebook(ctx: &AppContext, pid: &str) -> Result<notebooks::Model> {
+    match notebooks::Model::find_by_pid(&ctx.db, pid).await {
+        Ok(notebook) => Ok(notebook),
+        Err(ModelError::EntityNotFound | ModelError::Any(_)) => not_found(),
+        Err(err) => Err(err.into()),
+    }
+}
 
-#[debug_handler]
-pub async fn list(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
-    
-    let notes = Entity::find()
-        .filter(
-            Condition::any()
-                .add(crate::models::_entities::notes::Column::UserId.eq(user.id))
-                .add(
-                    crate::models::_entities::notes::Column::Id.in_subquery(
-                        note_shares::Entity::find()
-                            .select_only()
-                            .column(note_shares::Column::NoteId)
-                            .filter(note_shares::Column::SharedWithUserId.eq(user.id))
-                            .into_query()
-                    )
-                )
+/// Loads a notebook a note of the user may go into: one of their own in the
+/// same workspace as the note
+async fn load_target_notebook(
+    ctx: &AppContext,
+    pid: &str,
+    user_id: i32,
+    workspace_id: i32,
+) -> Result<notebooks::Model> {
+    let notebook = load_notebook(ctx, pid).await?;
+    if notebook.user_id != user_id || notebook.workspace_id != workspace_id {
+        return not_found();
+    }
+    Ok(notebook)
+}
+
+/// Condition matching notes the user tagged with the tag called `name`
+fn tagged_by(user_id: i32, name: &str) -> Condition {
+    Condition::all().add(
+        Column::Id.in_subquery(
+            note_tags::Entity::find()
+                .select_only()
+                .column(note_tags::Column::NoteId)
+                .inner_join(tags::Entity)
+                .filter(tags::Column::UserId.eq(user_id))
+                .filter(tags::Column::Name.eq(name))
+                .into_query(),
+        ),
+    )
+}
+
+/// Subquery selecting the ids of all notes in the active workspace that are
+/// not in the trash
+fn workspace_note_ids(workspace: &CurrentWorkspace) -> sea_query::SelectStatement {
+    Entity::find_live()
+        .select_only()
+        .column(Column::Id)
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .into_query()
+}
+
+/// Applies the resharing policy to a share of the note by `user_id`, and
+/// returns whether the share may change existing shares of the note
+async fn check_can_reshare(
+    ctx: &AppContext,
+    note: &Model,
+    user_id: i32,
+    params: &ShareNoteParams,
+) -> Result<bool> {
+    let own = if note.user_id == user_id {
+        None
+    } else {
+        Some(
+            shared_permission(ctx, note, user_id)
+                .await?
+                .ok_or_else(|| Error::NotFound)?,
         )
+    };
+    check_reshare(own, note.allow_reshare, params.permission).or_else(unauthorized)?;
+    Ok(may_update_shares(own))
+}
+
+/// Works out who a share is for. Unknown user ids and pids are a 404, and
+/// sharing with yourself is rejected.
+async fn resolve_share_target(
+    ctx: &AppContext,
+    shared_by: &users::Model,
+    params: &ShareNoteParams,
+) -> Result<ShareTarget> {
+    let target = match (
+        params.shared_with_user_id,
+        &params.shared_with_pid,
+        &params.shared_with_email,
+        &params.shared_with_group_pid,
+    ) {
+        (Some(user_id), None, None, None) => {
+            let user = users::Entity::find_by_id(user_id)
+                .one(&ctx.db)
+                .await?
+                .ok_or_else(|| Error::NotFound)?;
+            ShareTarget::User(user.id)
+        }
+        (None, Some(pid), None, None) => {
+            ShareTarget::User(users::Model::find_by_pid_or_not_found(&ctx.db, pid).await?.id)
+        }
+        (None, None, Some(email), None) => match users::Model::find_by_email(&ctx.db, email).await {
+            Ok(user) => ShareTarget::User(user.id),
+            Err(ModelError::EntityNotFound) => {
+                if validation::is_valid_email(email).is_err() {
+                    return bad_request("invalid email");
+                }
+                ShareTarget::Invitee(email.clone())
+            }
+            Err(err) => return Err(err.into()),
+        },
+        // only members can share with a group, other groups look like they do not exist
+        (None, None, None, Some(group_pid)) => match groups::Model::find_by_pid(&ctx.db, group_pid).await {
+            Ok(group) if group.has_member(&ctx.db, shared_by.id).await? => ShareTarget::Group(group),
+            Ok(_) | Err(ModelError::EntityNotFound) => return not_found(),
+            Err(err) => return Err(err.into()),
+        },
+        _ => {
+            return bad_request(
+                "exactly one of shared_with_pid, shared_with_email, shared_with_group_pid or shared_with_user_id is required",
+            )
+        }
+    };
+
+    if matches!(target, ShareTarget::User(user_id) if user_id == shared_by.id) {
+        return bad_request("can not share a note with yourself");
+    }
+
+    Ok(target)
+}
+
+/// Shares the note with an existing user, or records an invitation when the
+/// target has no account yet. Sharing again with the same target updates the
+/// permission and expiry instead of adding a duplicate, but only with
+/// `update_existing`; otherwise the existing share is kept as it is.
+/// Invitations are not mailed here, see `send_invitations`.
+async fn create_share<C>(
+    db: &C,
+    note: &Model,
+    shared_by: &users::Model,
+    target: &ShareTarget,
+    params: &ShareNoteParams,
+    update_existing: bool,
+) -> Result<ShareOutcome>
+where
+    C: ConnectionTrait,
+{
+    match target {
+        ShareTarget::User(user_id) => {
+            if *user_id == note.user_id {
+                return bad_request("the note owner already has access");
+            }
+            if user_blocks::Model::exists(db, *user_id, shared_by.id).await? {
+                return unauthorized("user does not accept shares from you");
+            }
+            let workspace = workspaces::Entity::find_by_id(note.workspace_id)
+                .one(db)
+                .await?
+                .ok_or_else(|| Error::NotFound)?;
+            let grant = ShareGrant {
+                shared_with_user_id: *user_id,
+                permission: params.permission,
+                expires_at: params.expires_at,
+                shared_by_user_id: shared_by.id,
+                update_existing,
+            };
+            let share =
+                upsert_user_share::<note_shares::Entity, _>(db, &workspace, note.id, &grant).await?;
+            Ok(ShareOutcome::Share(share))
+        }
+        ShareTarget::Invitee(email) => {
+            let existing = note_invitations::Entity::find()
+                .filter(note_invitations::Column::NoteId.eq(note.id))
+                .filter(note_invitations::Column::Email.eq(email.as_str()))
+                .one(db)
+                .await?;
+            let invitation = match existing {
+                Some(existing) if !update_existing => existing,
+                Some(existing) => {
+                    let mut invitation = existing.into_active_model();
+                    invitation.permission = Set(params.permission);
+                    invitation.expires_at = Set(params.expires_at);
+                    invitation.update(db).await?
+                }
+                None => {
+                    note_invitations::ActiveModel {
+                        note_id: Set(note.id),
+                        email: Set(email.clone()),
+                        permission: Set(params.permission),
+                        expires_at: Set(params.expires_at),
+                        invited_by_user_id: Set(shared_by.id),
+                        ..Default::default()
+                    }
+                    .insert(db)
+                    .await?
+                }
+            };
+            Ok(ShareOutcome::Invitation(invitation))
+        }
+        ShareTarget::Group(group) => {
+            // members from outside the workspace join it as guests
+            let workspace = workspaces::Entity::find_by_id(note.workspace_id)
+                .one(db)
+                .await?
+                .ok_or_else(|| Error::NotFound)?;
+            group.add_members_to_workspace(db, &workspace).await?;
+            let existing = note_group_shares::Entity::find()
+                .filter(note_group_shares::Column::NoteId.eq(note.id))
+                .filter(note_group_shares::Column::GroupId.eq(group.id))
+                .one(db)
+                .await?;
+            let mut share = match existing {
+                Some(existing) if !update_existing => {
+                    return Ok(ShareOutcome::GroupShare(NoteGroupShareResponse::new(&existing, group)));
+                }
+                Some(existing) => existing.into_active_model(),
+                None => note_group_shares::ActiveModel {
+                    note_id: Set(note.id),
+                    group_id: Set(group.id),
+                    ..Default::default()
+                },
+            };
+            share.permission = Set(params.permission);
+            share.expires_at = Set(params.expires_at);
+            share.shared_by_user_id = Set(Some(shared_by.id));
+            let share = share.save(db).await?.try_into_model()?;
+            Ok(ShareOutcome::GroupShare(NoteGroupShareResponse::new(&share, group)))
+        }
+    }
+}
+
+/// Mails the invitations made while sharing `notes`, one email for all of
+/// them. Call this once the shares are committed.
+async fn send_invitations(
+    ctx: &AppContext,
+    outcomes: &[&ShareOutcome],
+    notes: &[&Model],
+    invited_by: &users::Model,
+) -> Result<()> {
+    let invitations = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            ShareOutcome::Invitation(invitation) => Some(invitation),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let Some(first) = invitations.first() else {
+        return Ok(());
+    };
+    let invited_notes = notes
+        .iter()
+        .filter(|note| invitations.iter().any(|invitation| invitation.note_id == note.id))
+        .copied()
+        .collect::<Vec<_>>();
+    ShareMailer::send_invitation(ctx, first, &invited_notes, invited_by).await
+}
+
+/// What `share_multiple_notes` tells the client about a note it could not
+/// share. Only the reasons written for clients are passed on.
+fn share_error_message(err: &Error) -> String {
+    match err {
+        Error::NotFound => "note not found".to_string(),
+        Error::BadRequest(message) | Error::Unauthorized(message) => message.clone(),
+        err => {
+            tracing::error!(error = err.to_string(), "could not share note");
+            "could not share note".to_string()
+        }
+    }
+}
+
+#[debug_handler]
+pub async fn list(
+    workspace: CurrentWorkspace,
+    Query(query): Query<NoteListQuery>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = &workspace.user;
+    let ownership = match query.owner {
+        None => visible_to(user.id),
+        Some(NoteOwnership::Mine) => Condition::all().add(Column::UserId.eq(user.id)),
+        Some(NoteOwnership::Shared) => Condition::all()
+            .add(Column::UserId.ne(user.id))
+            .add(shared_with(user.id)),
+    };
+
+    let select = Entity::find_live()
+        .filter(crate::models::_entities::notes::Column::WorkspaceId.eq(workspace.workspace.id))
+        .filter(ownership);
+    let page = query.page(&ctx, &workspace, select).await?;
+
+    format::json(NotePageResponse::from(page))
+}
+
+/// Full-text search over the title and content of the notes the caller can
+/// see in the active workspace, best matches first
+#[debug_handler]
+pub async fn search(
+    workspace: CurrentWorkspace,
+    Query(query): Query<SearchQuery>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return bad_request("q must not be empty");
+    }
+
+    let hits = Entity::search(ctx.db.get_database_backend(), q)?
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .filter(visible_to(workspace.user.id))
+        .limit(SEARCH_LIMIT)
+        .into_model::<SearchHit>()
         .all(&ctx.db)
         .await?;
-    
-    format::json(notes)
+
+    format::json(hits.iter().map(NoteSearchResultResponse::new).collect::<Vec<_>>())
 }
 
 #[debug_handler]
-pub async fn add(auth: auth::JWT, State(ctx): State<AppContext>, Json(params): Json<Params>) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+pub async fn add(workspace: CurrentWorkspace, State(ctx): State<AppContext>, Json(params): Json<Params>) -> Result<Response> {
+    let user = &workspace.user;
+    if workspace.role == WorkspaceRole::Guest {
+        return unauthorized("guests can not create notes");
+    }
     let mut item = ActiveModel {
         user_id: Set(user.id),
-        
+        workspace_id: Set(workspace.workspace.id),
         ..Default::default()
     };
     params.update(&mut item);
+    if let Some(pid) = &params.notebook_pid {
+        let notebook = load_target_notebook(&ctx, pid, user.id, workspace.workspace.id).await?;
+        item.notebook_id = Set(Some(notebook.id));
+    }
+    let tag_names = params.tag_names()?;
     let item = item.insert(&ctx.db).await?;
+    if let Some(names) = tag_names {
+        tags::Model::set_for_note(&ctx.db, user.id, item.id, &names).await?;
+    }
     format::json(item)
 }
 
+/// The `ETag` of a note, its quoted version
+fn note_etag(note: &Model) -> String {
+    format!("\"{}\"", note.version)
+}
+
+/// The note version the client expects to overwrite, from `If-Match` or the
+/// `version` field. `None` when the client doesn't ask for a check.
+fn expected_version(headers: &HeaderMap, params: &Params) -> Result<Option<i32>> {
+    let Some(if_match) = headers.get(header::IF_MATCH) else {
+        return Ok(params.version);
+    };
+    let if_match = if_match.to_str().unwrap_or_default().trim();
+    if if_match == "*" {
+        return Ok(None);
+    }
+    match if_match.trim_start_matches("W/").trim_matches('"').parse() {
+        Ok(version) => Ok(Some(version)),
+        Err(_) => bad_request("If-Match must be a note ETag"),
+    }
+}
+
+/// Updates a note. When the client sends the version it edited and the note
+/// has changed since, nothing is written and the current note comes back
+/// with 409 Conflict.
 #[debug_handler]
 pub async fn update(
-    auth: auth::JWT,
+    workspace: CurrentWorkspace,
     Path(id): Path<i32>,
     State(ctx): State<AppContext>,
+    headers: HeaderMap,
     Json(params): Json<Params>,
 ) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
-    let item = Entity::find_by_id(id)
-        .filter(crate::models::_entities::notes::Column::UserId.eq(user.id))
-        .one(&ctx.db)
+    let item = load_item(&ctx, id, &workspace, SharePermission::Edit).await?;
+    let expected_version = expected_version(&headers, &params)?;
+    let tag_names = params.tag_names()?;
+
+    // the row stays locked between the version check and the write
+    let txn = ctx.db.begin().await?;
+    let current = Entity::find_by_id(item.id)
+        .lock_exclusive()
+        .one(&txn)
         .await?
         .ok_or_else(|| Error::NotFound)?;
-    let mut item = item.into_active_model();
+    if expected_version.is_some_and(|version| version != current.version) {
+        txn.rollback().await?;
+        return format::render()
+            .status(StatusCode::CONFLICT)
+            .etag(&note_etag(&current))?
+            .json(current);
+    }
+
+    let mut item = current.into_active_model();
     params.update(&mut item);
-    let item = item.update(&ctx.db).await?;
-    format::json(item)
+    let item = item.update(&txn).await?;
+    txn.commit().await?;
+    if let Some(names) = tag_names {
+        tags::Model::set_for_note(&ctx.db, workspace.user.id, item.id, &names).await?;
+    }
+
+    format::render().etag(&note_etag(&item))?.json(item)
 }
 
+/// Pins the note, putting it before all unpinned notes in listings
 #[debug_handler]
-pub async fn remove(auth: auth::JWT, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
-    let item = Entity::find_by_id(id)
-        .filter(crate::models::_entities::notes::Column::UserId.eq(user.id))
-        .one(&ctx.db)
-        .await?
-        .ok_or_else(|| Error::NotFound)?;
-    item.delete(&ctx.db).await?;
-    format::empty()
+pub async fn pin(workspace: CurrentWorkspace, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::Edit).await?;
+    format::json(item.set_pinned(&ctx.db, true).await?)
 }
 
 #[debug_handler]
-pub async fn get_notes_shared_by_me(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
-    
-    let shared_notes = Entity::find()
-        .filter(crate::models::_entities::notes::Column::UserId.eq(user.id))
-        .join(JoinType::InnerJoin, crate::models::_entities::notes::Relation::NoteShares.def())
-        .group_by(crate::models::_entities::notes::Column::Id)
-        .all(&ctx.db)
-        .await?;
-    
-    format::json(shared_notes)
+pub async fn unpin(workspace: CurrentWorkspace, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::Edit).await?;
+    format::json(item.set_pinned(&ctx.db, false).await?)
 }
 
+/// Archives the note, hiding it from listings unless they ask for archived
+/// notes. Everyone the note is shared with stops seeing it there too.
 #[debug_handler]
-pub async fn get_one(auth: auth::JWT, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
-    format::json(load_item(&ctx, id, user.id).await?)
+pub async fn archive(workspace: CurrentWorkspace, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::Edit).await?;
+    format::json(item.set_archived(&ctx.db, true).await?)
 }
 
 #[debug_handler]
-pub async fn share_note(
-    auth: auth::JWT,
-    Path(note_id): Path<i32>,
+pub async fn unarchive(workspace: CurrentWorkspace, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::Edit).await?;
+    format::json(item.set_archived(&ctx.db, false).await?)
+}
+
+/// Marks the note as a favourite of the caller. Favourites are personal and
+/// work on any note the caller can see.
+#[debug_handler]
+pub async fn favourite(workspace: CurrentWorkspace, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::View).await?;
+    item.set_favourite(&ctx.db, workspace.user.id, true).await?;
+    format::empty()
+}
+
+#[debug_handler]
+pub async fn unfavourite(workspace: CurrentWorkspace, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::View).await?;
+    item.set_favourite(&ctx.db, workspace.user.id, false).await?;
+    format::empty()
+}
+
+/// Moves one of the caller's notes into one of their notebooks, or out of
+/// its notebook. Everyone the target notebook is shared with gets access.
+#[debug_handler]
+pub async fn move_note(
+    workspace: CurrentWorkspace,
+    Path(id): Path<i32>,
     State(ctx): State<AppContext>,
-    Json(params): Json<ShareNoteParams>,
+    Json(params): Json<MoveNoteParams>,
 ) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
-    let note = load_item(&ctx, note_id, user.id).await?;
-    
-    let share = NoteShareActiveModel {
-        note_id: Set(note.id),
-        shared_with_user_id: Set(params.shared_with_user_id),
-        ..Default::default()
+    let user = &workspace.user;
+    let item = load_item(&ctx, id, &workspace, SharePermission::View).await?;
+    if item.user_id != user.id {
+        return unauthorized("only the owner can move a note");
+    }
+    let notebook_id = match &params.notebook_pid {
+        Some(pid) => Some(load_target_notebook(&ctx, pid, user.id, item.workspace_id).await?.id),
+        None => None,
     };
-    let share = share.insert(&ctx.db).await?;
-    
-    format::json(share)
-}
 
+    let mut item = item.into_active_model();
+    item.notebook_id = Set(notebook_id);
+    format::json(item.update(&ctx.db).await?)
+}
 
+/// The caller's tags on a note. Tags are personal, so recipients of a shared
+/// note see their own tags and not the owner's.
+#[debug_handler]
+pub async fn get_note_tags(
+    workspace: CurrentWorkspace,
+    Path(id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::View).await?;
+    let tags = tags::Model::find_for_note(&ctx.db, workspace.user.id, item.id).await?;
+    format::json(tags.iter().map(TagResponse::new).collect::<Vec<_>>())
+}
 
+/// Replaces the caller's tags on a note. Viewing a note is enough to tag it.
 #[debug_handler]
-pub async fn share_all_notes(
-    auth: auth::JWT,
+pub async fn set_note_tags(
+    workspace: CurrentWorkspace,
+    Path(id): Path<i32>,
     State(ctx): State<AppContext>,
-    Json(params): Json<ShareNoteParams>,
+    Json(params): Json<NoteTagsParams>,
 ) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
-    
-    // Get all notes of the current user
-    let user_notes = Entity::find()
-        .filter(Column::UserId.eq(user.id))
+    let names = tag_names(&params.tags)?;
+    let item = load_item(&ctx, id, &workspace, SharePermission::View).await?;
+    let tags = tags::Model::set_for_note(&ctx.db, workspace.user.id, item.id, &names).await?;
+    format::json(tags.iter().map(TagResponse::new).collect::<Vec<_>>())
+}
+
+/// Moves the note to the trash, see `purge_from_trash` for deleting it for good
+#[debug_handler]
+pub async fn remove(workspace: CurrentWorkspace, Path(id): Path<i32>, State(ctx): State<AppContext>) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::Manage).await?;
+    item.move_to_trash(&ctx.db).await?;
+    format::empty()
+}
+
+/// The caller's notes in the trash of the active workspace, most recently
+/// deleted first
+#[debug_handler]
+pub async fn list_trash(workspace: CurrentWorkspace, State(ctx): State<AppContext>) -> Result<Response> {
+    let notes = Entity::find_trashed()
+        .filter(Column::UserId.eq(workspace.user.id))
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .order_by_desc(Column::DeletedAt)
         .all(&ctx.db)
         .await?;
 
-    // Share each note with the specified user
-    for note in user_notes {
-        let share = note_shares::ActiveModel {
-            note_id: Set(note.id),
-            shared_with_user_id: Set(params.shared_with_user_id),
-            ..Default::default()
-        };
-        share.insert(&ctx.db).await?;
-    }
+    format::json(notes)
+}
 
-    format::json(serde_json::json!({
-        "message": "All notes have been shared successfully"
-    }))
+#[debug_handler]
+pub async fn restore_from_trash(
+    workspace: CurrentWorkspace,
+    Path(id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let item = load_trashed_item(&ctx, id, &workspace, SharePermission::Manage).await?;
+    format::json(item.restore_from_trash(&ctx.db).await?)
 }
 
+/// Deletes a note in the trash for good, together with its shares and
+/// attachments
 #[debug_handler]
-pub async fn get_shared_notes(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
-    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
-    
-    let shared_notes = Entity::find()
-        .join(JoinType::InnerJoin, crate::models::_entities::notes::Relation::NoteShares.def())
-        .filter(crate::models::_entities::note_shares::Column::SharedWithUserId.eq(user.id))
-        .group_by(crate::models::_entities::notes::Column::Id)
-        .all(&ctx.db)
-        .await?;
-    
-    format::json(shared_notes)
+pub async fn purge_from_trash(
+    workspace: CurrentWorkspace,
+    Path(id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let item = load_trashed_item(&ctx, id, &workspace, SharePermission::Manage).await?;
+    attachments::Model::delete_files_for_notes(&ctx.db, &ctx.storage, vec![item.id]).await?;
+    item.delete(&ctx.db).await?;
+    format::empty()
+}
+
+#[debug_handler]
+pub async fn get_notes_shared_by_me(
+    workspace: CurrentWorkspace,
+    Query(query): Query<NoteListQuery>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = &workspace.user;
+    
+    let select = Entity::find_live()
+        .filter(crate::models::_entities::notes::Column::UserId.eq(user.id))
+        .filter(crate::models::_entities::notes::Column::WorkspaceId.eq(workspace.workspace.id))
+        .filter(
+            Condition::any()
+                .add(
+                    crate::models::_entities::notes::Column::Id.in_subquery(
+                        note_shares::Entity::find()
+                            .select_only()
+                            .column(note_shares::Column::NoteId)
+                            .filter(note_shares::Column::State.ne(ShareState::Declined))
+                            .filter(note_shares::Entity::not_expired_condition())
+                            .into_query()
+                    )
+                )
+                .add(
+                    crate::models::_entities::notes::Column::Id.in_subquery(
+                        note_group_shares::Entity::find()
+                            .select_only()
+                            .column(note_group_shares::Column::NoteId)
+                            .filter(note_group_shares::Entity::not_expired_condition())
+                            .into_query()
+                    )
+                )
+        );
+    let page = query.page(&ctx, &workspace, select).await?;
+    
+    format::json(NotePageResponse::from(page))
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderAs {
+    Html,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetNoteQuery {
+    /// `html` adds the content rendered to sanitized HTML and a table of
+    /// contents to the note
+    pub render: Option<RenderAs>,
+}
+
+#[debug_handler]
+pub async fn get_one(
+    workspace: CurrentWorkspace,
+    Path(id): Path<i32>,
+    Query(query): Query<GetNoteQuery>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let item = load_item(&ctx, id, &workspace, SharePermission::View).await?;
+    let etag = note_etag(&item);
+    match query.render {
+        Some(RenderAs::Html) => format::render().etag(&etag)?.json(NoteHtmlResponse::new(item)),
+        None => format::render().etag(&etag)?.json(item),
+    }
+}
+
+#[debug_handler]
+pub async fn share_note(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ShareNoteParams>,
+) -> Result<Response> {
+    params.validate()?;
+    let user = &workspace.user;
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let update_existing = check_can_reshare(&ctx, &note, user.id, &params).await?;
+    let target = resolve_share_target(&ctx, user, &params).await?;
+    let share = create_share(&ctx.db, &note, user, &target, &params, update_existing).await?;
+    send_invitations(&ctx, &[&share], &[&note], user).await?;
+
+    format::json(share)
+}
+
+
+
+#[debug_handler]
+pub async fn share_all_notes(
+    workspace: CurrentWorkspace,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ShareNoteParams>,
+) -> Result<Response> {
+    params.validate()?;
+    let user = &workspace.user;
+    let target = resolve_share_target(&ctx, user, &params).await?;
+    
+    // Get all notes of the current user
+    let user_notes = Entity::find_live()
+        .filter(Column::UserId.eq(user.id))
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .order_by_asc(Column::Id)
+        .all(&ctx.db)
+        .await?;
+
+    // Share each note with the specified user
+    let mut shares = Vec::with_capacity(user_notes.len());
+    for note in &user_notes {
+        shares.push(create_share(&ctx.db, note, user, &target, &params, true).await?);
+    }
+    let notes = user_notes.iter().collect::<Vec<_>>();
+    send_invitations(&ctx, &shares.iter().collect::<Vec<_>>(), &notes, user).await?;
+
+    format::json(serde_json::json!({
+        "message": "All notes have been shared successfully"
+    }))
+}
+
+/// Shares a chosen set of the caller's notes in one transaction. Every note
+/// gets its own result, so one failing note does not undo the others.
+#[debug_handler]
+pub async fn share_multiple_notes(
+    workspace: CurrentWorkspace,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ShareMultipleParams>,
+) -> Result<Response> {
+    params.share.validate()?;
+    if params.note_ids.is_empty() {
+        return bad_request("note_ids must not be empty");
+    }
+    let user = &workspace.user;
+    let target = resolve_share_target(&ctx, user, &params.share).await?;
+
+    let mut note_ids = params.note_ids.clone();
+    note_ids.sort_unstable();
+    note_ids.dedup();
+
+    let txn = ctx.db.begin().await?;
+    let owned_notes = Entity::find_live()
+        .filter(Column::UserId.eq(user.id))
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .filter(Column::Id.is_in(note_ids.clone()))
+        .order_by_asc(Column::Id)
+        .all(&txn)
+        .await?;
+
+    let mut results = Vec::with_capacity(note_ids.len());
+    for note_id in note_ids {
+        let Some(note) = owned_notes.iter().find(|note| note.id == note_id) else {
+            results.push(ShareMultipleResult {
+                note_id,
+                share: None,
+                error: Some("note not found".to_string()),
+            });
+            continue;
+        };
+
+        // a savepoint per note keeps the transaction usable after a failure
+        let savepoint = txn.begin().await?;
+        match create_share(&savepoint, note, user, &target, &params.share, true).await {
+            Ok(share) => {
+                savepoint.commit().await?;
+                results.push(ShareMultipleResult { note_id, share: Some(share), error: None });
+            }
+            Err(err) => {
+                savepoint.rollback().await?;
+                results.push(ShareMultipleResult {
+                    note_id,
+                    share: None,
+                    error: Some(share_error_message(&err)),
+                });
+            }
+        }
+    }
+    txn.commit().await?;
+
+    // only mail invitations that made it into the database
+    let shares = results.iter().filter_map(|result| result.share.as_ref()).collect::<Vec<_>>();
+    send_invitations(&ctx, &shares, &owned_notes.iter().collect::<Vec<_>>(), user).await?;
+
+    format::json(ShareMultipleResponse { results })
+}
+
+#[debug_handler]
+pub async fn get_shared_notes(
+    workspace: CurrentWorkspace,
+    Query(query): Query<NoteListQuery>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = &workspace.user;
+    
+    let select = Entity::find_live()
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .filter(Column::UserId.ne(user.id))
+        .filter(shared_with(user.id));
+    let page = query.page(&ctx, &workspace, select).await?;
+
+    // who shared each note, preferring a direct share over a group share
+    let note_ids = page.items.iter().map(|note| note.id).collect::<Vec<_>>();
+    let direct = note_shares::Entity::find_active()
+        .filter(note_shares::Column::SharedWithUserId.eq(user.id))
+        .filter(note_shares::Column::NoteId.is_in(note_ids.clone()))
+        .all(&ctx.db)
+        .await?;
+    let through_groups = note_group_shares::Entity::find_active_for_user(user.id)
+        .filter(note_group_shares::Column::NoteId.is_in(note_ids))
+        .all(&ctx.db)
+        .await?;
+
+    let shared_notes = page
+        .items
+        .iter()
+        .map(|note| {
+            let shared_by = direct
+                .iter()
+                .find(|share| share.note_id == note.id)
+                .map(|share| share.shared_by_user_id)
+                .or_else(|| {
+                    through_groups
+                        .iter()
+                        .find(|share| share.note_id == note.id)
+                        .map(|share| share.shared_by_user_id)
+                })
+                .flatten();
+            SharedNoteResponse {
+                id: note.id,
+                title: note.title.clone(),
+                content: note.content.clone(),
+                // rows from before shares were tracked were all made by the owner
+                shared_by_user_id: shared_by.unwrap_or(note.user_id),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    format::json(NotePageResponse::new(shared_notes, &page))
+}
+
+/// Hands a note over to another user, keeping its shares.
+#[debug_handler]
+pub async fn transfer_note(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<TransferNoteParams>,
+) -> Result<Response> {
+    let user = &workspace.user;
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    if note.user_id != user.id {
+        return unauthorized("only the owner can transfer a note");
+    }
+
+    let new_owner = users::Model::find_by_pid_or_not_found(&ctx.db, &params.new_owner_pid).await?;
+    if new_owner.id == user.id {
+        return bad_request("can not transfer a note to yourself");
+    }
+    if user_blocks::Model::exists(&ctx.db, new_owner.id, user.id).await? {
+        return unauthorized("user does not accept notes from you");
+    }
+    if workspace
+        .workspace
+        .role_of(&ctx.db, new_owner.id)
+        .await?
+        .is_none_or(|role| role == WorkspaceRole::Guest)
+    {
+        return bad_request("the new owner must be a member of the workspace");
+    }
+
+    format::json(note.transfer_to(&ctx.db, new_owner.id, params.keep_access).await?)
+}
+
+/// Lets the owner decide whether recipients may reshare the note.
+#[debug_handler]
+pub async fn update_reshare_policy(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ResharePolicyParams>,
+) -> Result<Response> {
+    let user = &workspace.user;
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    if note.user_id != user.id {
+        return unauthorized("only the owner can change the resharing policy");
+    }
+
+    let mut note = note.into_active_model();
+    note.allow_reshare = Set(params.allow_reshare);
+    format::json(note.update(&ctx.db).await?)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+async fn load_revision(ctx: &AppContext, note_id: i32, revision: i32) -> Result<note_revisions::Model> {
+    let revision = note_revisions::Entity::find()
+        .filter(note_revisions::Column::NoteId.eq(note_id))
+        .filter(note_revisions::Column::Revision.eq(revision))
+        .one(&ctx.db)
+        .await?;
+    revision.ok_or_else(|| Error::NotFound)
+}
+
+#[debug_handler]
+pub async fn list_revisions(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+
+    let revisions = note_revisions::Entity::find_for_note(note.id).all(&ctx.db).await?;
+
+    format::json(revisions.iter().map(NoteRevisionResponse::new).collect::<Vec<_>>())
+}
+
+#[debug_handler]
+pub async fn get_revision(
+    workspace: CurrentWorkspace,
+    Path((note_id, revision)): Path<(i32, i32)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let revision = load_revision(&ctx, note.id, revision).await?;
+    format::json(NoteRevisionResponse::new(&revision))
+}
+
+#[debug_handler]
+pub async fn diff_revisions(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    Query(query): Query<RevisionDiffQuery>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let from = load_revision(&ctx, note.id, query.from).await?;
+    let to = load_revision(&ctx, note.id, query.to).await?;
+    format::json(NoteRevisionDiffResponse::new(&from, &to))
+}
+
+/// Brings back the title and content of an old revision. The history is
+/// never rewritten; the restored state is saved as the newest revision.
+#[debug_handler]
+pub async fn restore_revision(
+    workspace: CurrentWorkspace,
+    Path((note_id, revision)): Path<(i32, i32)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Edit).await?;
+    let revision = load_revision(&ctx, note.id, revision).await?;
+
+    let mut item = note.into_active_model();
+    item.title = Set(revision.title);
+    item.content = Set(revision.content);
+    item.format = Set(revision.format);
+    let item = item.update(&ctx.db).await?;
+    format::json(item)
+}
+
+#[debug_handler]
+pub async fn get_shares(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Manage).await?;
+
+    let shares = note_shares::Entity::find()
+        .filter(note_shares::Column::NoteId.eq(note.id))
+        .find_also_related(users::Entity)
+        .all(&ctx.db)
+        .await?;
+
+    format::json(
+        shares
+            .iter()
+            .filter_map(|(share, shared_with)| {
+                shared_with.as_ref().map(|shared_with| NoteShareResponse::new(share, shared_with))
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[debug_handler]
+pub async fn update_share(
+    workspace: CurrentWorkspace,
+    Path((note_id, user_pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<UpdateShareParams>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Manage).await?;
+    let shared_with = users::Model::find_by_pid_or_not_found(&ctx.db, &user_pid).await?;
+
+    let result = note_shares::Entity::update_many()
+        .col_expr(note_shares::Column::Permission, sea_query::Expr::value(params.permission))
+        .filter(note_shares::Column::NoteId.eq(note.id))
+        .filter(note_shares::Column::SharedWithUserId.eq(shared_with.id))
+        .exec(&ctx.db)
+        .await?;
+    if result.rows_affected == 0 {
+        return not_found();
+    }
+
+    let share = note_shares::Entity::find()
+        .filter(note_shares::Column::NoteId.eq(note.id))
+        .filter(note_shares::Column::SharedWithUserId.eq(shared_with.id))
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| Error::NotFound)?;
+
+    format::json(NoteShareResponse::new(&share, &shared_with))
+}
+
+#[debug_handler]
+pub async fn revoke_share(
+    workspace: CurrentWorkspace,
+    Path((note_id, user_pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Manage).await?;
+    let shared_with = users::Model::find_by_pid_or_not_found(&ctx.db, &user_pid).await?;
+
+    let result = note_shares::Entity::delete_many()
+        .filter(note_shares::Column::NoteId.eq(note.id))
+        .filter(note_shares::Column::SharedWithUserId.eq(shared_with.id))
+        .exec(&ctx.db)
+        .await?;
+    if result.rows_affected == 0 {
+        return not_found();
+    }
+
+    format::empty()
+}
+
+#[debug_handler]
+pub async fn get_group_shares(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Manage).await?;
+
+    let shares = note_group_shares::Entity::find()
+        .filter(note_group_shares::Column::NoteId.eq(note.id))
+        .find_also_related(groups::Entity)
+        .all(&ctx.db)
+        .await?;
+
+    format::json(
+        shares
+            .iter()
+            .filter_map(|(share, group)| {
+                group.as_ref().map(|group| NoteGroupShareResponse::new(share, group))
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[debug_handler]
+pub async fn revoke_group_share(
+    workspace: CurrentWorkspace,
+    Path((note_id, group_pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Manage).await?;
+    let group = match groups::Model::find_by_pid(&ctx.db, &group_pid).await {
+        Ok(group) => group,
+        Err(ModelError::EntityNotFound) => return not_found(),
+        Err(err) => return Err(err.into()),
+    };
+
+    let result = note_group_shares::Entity::delete_many()
+        .filter(note_group_shares::Column::NoteId.eq(note.id))
+        .filter(note_group_shares::Column::GroupId.eq(group.id))
+        .exec(&ctx.db)
+        .await?;
+    if result.rows_affected == 0 {
+        return not_found();
+    }
+
+    format::empty()
+}
+
+/// Moves the caller's own share of a note in the active workspace to the
+/// `next` state
+async fn transition_share(
+    ctx: &AppContext,
+    note_id: i32,
+    workspace: &CurrentWorkspace,
+    next: ShareState,
+) -> Result<note_shares::Model> {
+    Entity::find_live()
+        .filter(Column::Id.eq(note_id))
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| Error::NotFound)?;
+
+    let share =
+        transition_user_share::<note_shares::Entity, _>(&ctx.db, note_id, workspace.user.id, next)
+            .await?;
+    Ok(share)
+}
+
+#[debug_handler]
+pub async fn accept_share(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    format::json(transition_share(&ctx, note_id, &workspace, ShareState::Accepted).await?)
+}
+
+#[debug_handler]
+pub async fn decline_share(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    format::json(transition_share(&ctx, note_id, &workspace, ShareState::Declined).await?)
+}
+
+#[debug_handler]
+pub async fn get_incoming_shares(workspace: CurrentWorkspace, State(ctx): State<AppContext>) -> Result<Response> {
+    let user = &workspace.user;
+
+    let shares = find_incoming::<note_shares::Entity>(user.id)
+        .filter(note_shares::Column::State.eq(ShareState::Pending))
+        .filter(note_shares::Column::NoteId.in_subquery(workspace_note_ids(&workspace)))
+        .find_also_related(Entity)
+        .all(&ctx.db)
+        .await?;
+
+    let owner_ids = shares
+        .iter()
+        .filter_map(|(_, note)| note.as_ref().map(|note| note.user_id))
+        .collect::<Vec<_>>();
+    let owners = users::Entity::find()
+        .filter(users::Column::Id.is_in(owner_ids))
+        .all(&ctx.db)
+        .await?;
+
+    format::json(
+        shares
+            .iter()
+            .filter_map(|(share, note)| {
+                let note = note.as_ref()?;
+                let owner = owners.iter().find(|owner| owner.id == note.user_id)?;
+                Some(IncomingShareResponse::new(share, note, owner))
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Creates a public read-only link to the note, replacing the previous one
+#[debug_handler]
+pub async fn create_link(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<CreateLinkParams>,
+) -> Result<Response> {
+    if params.expires_at.is_some_and(|expires_at| expires_at <= chrono::Local::now()) {
+        return bad_request("expires_at must be in the future");
+    }
+    let user = &workspace.user;
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Manage).await?;
+
+    let password = params
+        .password
+        .as_deref()
+        .map(loco_rs::hash::hash_password)
+        .transpose()
+        .map_err(|e| Error::Hash(e.to_string()))?;
+
+    let txn = ctx.db.begin().await?;
+    note_links::Entity::delete_many()
+        .filter(note_links::Column::NoteId.eq(note.id))
+        .exec(&txn)
+        .await?;
+    let link = note_links::ActiveModel {
+        note_id: Set(note.id),
+        password: Set(password),
+        expires_at: Set(params.expires_at),
+        created_by_user_id: Set(user.id),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+
+    format::json(NoteLinkResponse::new(&link, &ctx.config.server.full_url()))
+}
+
+#[debug_handler]
+pub async fn revoke_link(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Manage).await?;
+
+    let result = note_links::Entity::delete_many()
+        .filter(note_links::Column::NoteId.eq(note.id))
+        .exec(&ctx.db)
+        .await?;
+    if result.rows_affected == 0 {
+        return not_found();
+    }
+
+    format::empty()
+}
+
+/// Live notes of the active workspace the caller can see
+fn visible_notes(workspace: &CurrentWorkspace) -> Select<Entity> {
+    Entity::find_live()
+        .filter(Column::WorkspaceId.eq(workspace.workspace.id))
+        .filter(visible_to(workspace.user.id))
+}
+
+/// The notes linking to this one with `[[...]]`. Only notes the caller can
+/// see are listed.
+#[debug_handler]
+pub async fn get_backlinks(
+    workspace: CurrentWorkspace,
+    Path(id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, id, &workspace, SharePermission::View).await?;
+    let sources = visible_notes(&workspace)
+        .filter(
+            Column::Id.in_subquery(
+                note_references::Entity::find()
+                    .select_only()
+                    .column(note_references::Column::SourceNoteId)
+                    .filter(note_references::Column::TargetNoteId.eq(note.id))
+                    .into_query(),
+            ),
+        )
+        .order_by_asc(Column::Id)
+        .all(&ctx.db)
+        .await?;
+
+    format::json(sources.iter().map(LinkedNoteResponse::new).collect::<Vec<_>>())
+}
+
+/// The notes the caller can see and the wiki links between them. Links from
+/// or to any other note are left out, so they are never revealed.
+#[debug_handler]
+pub async fn get_graph(workspace: CurrentWorkspace, State(ctx): State<AppContext>) -> Result<Response> {
+    let notes = visible_notes(&workspace)
+        .order_by_asc(Column::Id)
+        .all(&ctx.db)
+        .await?;
+    let visible_ids = || {
+        visible_notes(&workspace)
+            .select_only()
+            .column(Column::Id)
+            .into_query()
+    };
+    let references = note_references::Entity::find()
+        .filter(note_references::Column::SourceNoteId.in_subquery(visible_ids()))
+        .filter(note_references::Column::TargetNoteId.in_subquery(visible_ids()))
+        .order_by_asc(note_references::Column::SourceNoteId)
+        .order_by_asc(note_references::Column::TargetNoteId)
+        .all(&ctx.db)
+        .await?;
+
+    format::json(NoteGraphResponse::new(&notes, &references))
+}
+
+/// Loads a checklist item of the note, unknown pids are a 404
+async fn load_note_item(ctx: &AppContext, note_id: i32, pid: &str) -> Result<note_items::Model> {
+    match note_items::Model::find_by_pid(&ctx.db, note_id, pid).await {
+        Ok(item) => Ok(item),
+        Err(ModelError::EntityNotFound | ModelError::Any(_)) => not_found(),
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn note_items_response(ctx: &AppContext, note_id: i32) -> Result<Response> {
+    let items = note_items::Model::find_with_checkers(&ctx.db, note_id).await?;
+    format::json(
+        items
+            .iter()
+            .map(|(item, checked_by)| NoteItemResponse::new(item, checked_by.as_ref()))
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[debug_handler]
+pub async fn list_note_items(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    note_items_response(&ctx, note.id).await
+}
+
+/// Adds an item at the end of the note's checklist
+#[debug_handler]
+pub async fn add_note_item(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<NoteItemParams>,
+) -> Result<Response> {
+    let Some(text) = note_items::normalize_text(&params.text) else {
+        return bad_request(format!(
+            "item text must be 1 to {} characters long",
+            note_items::MAX_TEXT_LENGTH
+        ));
+    };
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Edit).await?;
+    let item = note_items::Model::append(&ctx.db, note.id, text).await?;
+    format::json(NoteItemResponse::new(&item, None))
+}
+
+/// Puts the checklist in the given order, which must name every item once
+#[debug_handler]
+pub async fn reorder_note_items(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ReorderNoteItemsParams>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Edit).await?;
+    let mut items = note_items::Entity::find_for_note(note.id).all(&ctx.db).await?;
+
+    let mut ordered = Vec::with_capacity(items.len());
+    for pid in &params.pids {
+        let Some(index) = items.iter().position(|item| item.pid.to_string() == *pid) else {
+            return bad_request("pids must list every item of the checklist once");
+        };
+        ordered.push(items.swap_remove(index));
+    }
+    if !items.is_empty() {
+        return bad_request("pids must list every item of the checklist once");
+    }
+
+    note_items::Model::reorder(&ctx.db, ordered).await?;
+    note_items_response(&ctx, note.id).await
+}
+
+async fn set_note_item_checked(
+    workspace: &CurrentWorkspace,
+    note_id: i32,
+    pid: &str,
+    ctx: &AppContext,
+    checked: bool,
+) -> Result<Response> {
+    let note = load_item(ctx, note_id, workspace, SharePermission::Edit).await?;
+    let item = load_note_item(ctx, note.id, pid).await?;
+    let item = item.set_checked(&ctx.db, workspace.user.id, checked).await?;
+    let checked_by = checked.then_some(&workspace.user);
+    format::json(NoteItemResponse::new(&item, checked_by))
+}
+
+/// Ticks the item as checked by the caller
+#[debug_handler]
+pub async fn check_note_item(
+    workspace: CurrentWorkspace,
+    Path((note_id, pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    set_note_item_checked(&workspace, note_id, &pid, &ctx, true).await
+}
+
+#[debug_handler]
+pub async fn uncheck_note_item(
+    workspace: CurrentWorkspace,
+    Path((note_id, pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    set_note_item_checked(&workspace, note_id, &pid, &ctx, false).await
+}
+
+#[debug_handler]
+pub async fn remove_note_item(
+    workspace: CurrentWorkspace,
+    Path((note_id, pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Edit).await?;
+    let item = load_note_item(&ctx, note.id, &pid).await?;
+    item.delete(&ctx.db).await?;
+    format::empty()
+}
+
+/// Loads a reminder the caller set on the note, others are a 404
+async fn load_reminder(
+    ctx: &AppContext,
+    note_id: i32,
+    user_id: i32,
+    pid: &str,
+) -> Result<reminders::Model> {
+    match reminders::Model::find_by_pid(&ctx.db, note_id, user_id, pid).await {
+        Ok(reminder) => Ok(reminder),
+        Err(ModelError::EntityNotFound | ModelError::Any(_)) => not_found(),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The caller's reminders on the note; reminders are personal
+#[debug_handler]
+pub async fn list_reminders(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let reminders = reminders::Entity::find_for_note(note.id, workspace.user.id)
+        .all(&ctx.db)
+        .await?;
+    format::json(reminders.iter().map(ReminderResponse::new).collect::<Vec<_>>())
+}
+
+/// Emails the caller about the note at `remind_at`, and again on every
+/// `recurrence`. Viewing the note is enough.
+#[debug_handler]
+pub async fn add_reminder(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<ReminderParams>,
+) -> Result<Response> {
+    let recurrence = params.validate()?;
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let reminder = reminders::ActiveModel {
+        note_id: Set(note.id),
+        user_id: Set(workspace.user.id),
+        due_at: Set(params.remind_at),
+        remind_at: Set(params.remind_at),
+        recurrence: Set(recurrence),
+        ..Default::default()
+    }
+    .insert(&ctx.db)
+    .await?;
+    format::json(ReminderResponse::new(&reminder))
+}
+
+/// Puts off the next email of the reminder, also after it was sent
+#[debug_handler]
+pub async fn snooze_reminder(
+    workspace: CurrentWorkspace,
+    Path((note_id, pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<SnoozeReminderParams>,
+) -> Result<Response> {
+    let until = params.until()?;
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let reminder = load_reminder(&ctx, note.id, workspace.user.id, &pid).await?;
+    let reminder = reminder.snooze(&ctx.db, until).await?;
+    format::json(ReminderResponse::new(&reminder))
+}
+
+#[debug_handler]
+pub async fn remove_reminder(
+    workspace: CurrentWorkspace,
+    Path((note_id, pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let reminder = load_reminder(&ctx, note.id, workspace.user.id, &pid).await?;
+    reminder.delete(&ctx.db).await?;
+    format::empty()
+}
+
+/// Loads an attachment of the note, unknown pids are a 404
+async fn load_attachment(ctx: &AppContext, note_id: i32, pid: &str) -> Result<attachments::Model> {
+    match attachments::Model::find_by_pid(&ctx.db, note_id, pid).await {
+        Ok(attachment) => Ok(attachment),
+        Err(ModelError::EntityNotFound | ModelError::Any(_)) => not_found(),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Uploads are read up to `limit_payload`, bigger ones fail with 413
+fn multipart_error(err: MultipartError) -> Error {
+    Error::CustomError(
+        err.status(),
+        ErrorDetail::new("invalid_upload".to_string(), err.body_text()),
+    )
+}
+
+/// Attaches the files of a `multipart/form-data` body to the note. Every
+/// field with a file name becomes one attachment; other fields are ignored.
+/// The upload is all or nothing: when any file fails, the rows are rolled
+/// back and the files stored so far are deleted again.
+#[debug_handler]
+pub async fn upload_attachments(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+    mut multipart: Multipart,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Edit).await?;
+
+    let txn = ctx.db.begin().await?;
+    let mut uploaded = Vec::new();
+    let saved = save_attachments(&ctx, &txn, &note, &workspace.user, &mut multipart, &mut uploaded)
+        .await;
+    let saved = match saved {
+        Ok(()) => txn.commit().await.map_err(Error::from),
+        // dropping the transaction rolls the rows back
+        Err(err) => Err(err),
+    };
+    if let Err(err) = saved {
+        for attachment in &uploaded {
+            attachment.delete_file(&ctx.storage).await;
+        }
+        return Err(err);
+    }
+    if uploaded.is_empty() {
+        return bad_request("no files in the upload");
+    }
+
+    format::json(uploaded.iter().map(AttachmentResponse::new).collect::<Vec<_>>())
+}
+
+/// Inserts a row for every file in the upload and stores the file after it,
+/// collecting the attachments whose files were stored in `uploaded`
+async fn save_attachments(
+    ctx: &AppContext,
+    txn: &DatabaseTransaction,
+    note: &Model,
+    user: &users::Model,
+    multipart: &mut Multipart,
+    uploaded: &mut Vec<attachments::Model>,
+) -> Result<()> {
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        let Some(file_name) = field.file_name().map(ToString::to_string) else {
+            continue;
+        };
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let content = field.bytes().await.map_err(multipart_error)?;
+
+        let attachment = attachments::ActiveModel {
+            note_id: Set(note.id),
+            uploaded_by_user_id: Set(Some(user.id)),
+            file_name: Set(file_name),
+            content_type: Set(content_type),
+            size: Set(i64::try_from(content.len()).unwrap_or(i64::MAX)),
+            storage_key: Set(attachments::new_storage_key(note.id)),
+            ..Default::default()
+        }
+        .insert(txn)
+        .await?;
+        ctx.storage
+            .upload(StoragePath::new(&attachment.storage_key), &content)
+            .await?;
+        uploaded.push(attachment);
+    }
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn list_attachments(
+    workspace: CurrentWorkspace,
+    Path(note_id): Path<i32>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let attachments = attachments::Entity::find_for_note(note.id).all(&ctx.db).await?;
+    format::json(attachments.iter().map(AttachmentResponse::new).collect::<Vec<_>>())
+}
+
+/// Streams the stored file, it is never read into memory as a whole
+#[debug_handler]
+pub async fn download_attachment(
+    workspace: CurrentWorkspace,
+    Path((note_id, pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::View).await?;
+    let attachment = load_attachment(&ctx, note.id, &pid).await?;
+
+    let file = ctx
+        .storage
+        .as_store_err(crate::storage::STORE)?
+        .get(StoragePath::new(&attachment.storage_key))
+        .await?;
+    // only images may be shown inline, anything else (html, svg, ...) could
+    // run in the app's origin
+    let disposition_type = if attachment.content_type.starts_with("image/")
+        && attachment.content_type != "image/svg+xml"
+    {
+        "inline"
+    } else {
+        "attachment"
+    };
+    let disposition = format!(
+        "{disposition_type}; filename=\"{}\"",
+        attachment.file_name.replace(['"', '\\', '\r', '\n'], "_")
+    );
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, attachment.content_type)
+        .header(header::CONTENT_LENGTH, file.meta.size)
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(Body::from_stream(file.into_stream()))?)
+}
+
+#[debug_handler]
+pub async fn remove_attachment(
+    workspace: CurrentWorkspace,
+    Path((note_id, pid)): Path<(i32, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let note = load_item(&ctx, note_id, &workspace, SharePermission::Edit).await?;
+    let attachment = load_attachment(&ctx, note.id, &pid).await?;
+    attachment.delete_with_file(&ctx.db, &ctx.storage).await?;
+    format::empty()
 }
 
 pub fn routes() -> Routes {
@@ -215,11 +2029,57 @@ pub fn routes() -> Routes {
         .prefix("notes")
         .add("/", get(list))
         .add("/", post(add))
+        .add("/search", get(search))
+        .add("/graph", get(get_graph))
         .add("/:id", get(get_one))
         .add("/:id", delete(remove))
+        .add("/:id/restore", post(restore_from_trash))
+        .add("/trash", get(list_trash))
+        .add("/trash/:id", delete(purge_from_trash))
         .add("/:id", post(update))
         .add("/:id/share", post(share_note))
+        .add("/:id/shares", get(get_shares))
+        .add("/:id/group-shares", get(get_group_shares))
+        .add("/:id/group-shares/:group_pid", delete(revoke_group_share))
+        .add("/:id/reshare-policy", patch(update_reshare_policy))
+        .add("/:id/transfer", post(transfer_note))
+        .add("/:id/tags", get(get_note_tags))
+        .add("/:id/move", post(move_note))
+        .add("/:id/pin", post(pin))
+        .add("/:id/pin", delete(unpin))
+        .add("/:id/archive", post(archive))
+        .add("/:id/archive", delete(unarchive))
+        .add("/:id/favourite", post(favourite))
+        .add("/:id/favourite", delete(unfavourite))
+        .add("/:id/tags", put(set_note_tags))
+        .add("/:id/backlinks", get(get_backlinks))
+        .add("/:id/items", get(list_note_items))
+        .add("/:id/items", post(add_note_item))
+        .add("/:id/items/order", put(reorder_note_items))
+        .add("/:id/items/:pid", delete(remove_note_item))
+        .add("/:id/items/:pid/check", post(check_note_item))
+        .add("/:id/items/:pid/check", delete(uncheck_note_item))
+        .add("/:id/reminders", get(list_reminders))
+        .add("/:id/reminders", post(add_reminder))
+        .add("/:id/reminders/:pid", delete(remove_reminder))
+        .add("/:id/reminders/:pid/snooze", post(snooze_reminder))
+        .add("/:id/attachments", get(list_attachments))
+        .add("/:id/attachments", post(upload_attachments))
+        .add("/:id/attachments/:pid", get(download_attachment))
+        .add("/:id/attachments/:pid", delete(remove_attachment))
+        .add("/:id/revisions", get(list_revisions))
+        .add("/:id/revisions/diff", get(diff_revisions))
+        .add("/:id/revisions/:revision", get(get_revision))
+        .add("/:id/revisions/:revision/restore", post(restore_revision))
+        .add("/:id/share/:user_pid", patch(update_share))
+        .add("/:id/share/:user_pid", delete(revoke_share))
+        .add("/:id/share/accept", post(accept_share))
+        .add("/:id/share/decline", post(decline_share))
+        .add("/shares/incoming", get(get_incoming_shares))
+        .add("/:id/link", post(create_link))
+        .add("/:id/link", delete(revoke_link))
         .add("/shared", get(get_shared_notes))
         .add("/shared-by-me", get(get_notes_shared_by_me))
         .add("/share-all", post(share_all_notes))
+        .add("/share-multiple", post(share_multiple_notes))
 }
\ No newline at end of file
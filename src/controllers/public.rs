@@ -0,0 +1,87 @@
+//! Routes that can be reached without logging in
+use axum::{
+    debug_handler,
+    http::{header, HeaderMap, StatusCode},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use loco_rs::{controller::ErrorDetail, prelude::*};
+
+use crate::{
+    models::{note_links, notes},
+    views::notes::PublicNoteResponse,
+};
+
+/// Carries the password of a protected link. It is not taken from the query
+/// string, which ends up in access logs and browser history.
+pub const LINK_PASSWORD_HEADER: &str = "x-link-password";
+
+/// The password of a protected link, from the `X-Link-Password` header or
+/// from Basic authorization, which browsers send once the user answers the
+/// password prompt. The Basic user name is ignored.
+fn link_password(headers: &HeaderMap) -> Option<String> {
+    if let Some(password) = headers.get(LINK_PASSWORD_HEADER) {
+        return password.to_str().ok().map(ToString::to_string);
+    }
+    let credentials = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let credentials = String::from_utf8(STANDARD.decode(credentials.trim()).ok()?).ok()?;
+    let (_, password) = credentials.split_once(':')?;
+    Some(password.to_string())
+}
+
+/// Answers a request without the right password, asking browsers to prompt
+/// for it
+fn password_required() -> Result<Response> {
+    format::render()
+        .status(StatusCode::UNAUTHORIZED)
+        .header(header::WWW_AUTHENTICATE, "Basic realm=\"note\", charset=\"UTF-8\"")
+        .json(ErrorDetail::new("unauthorized", "invalid link password"))
+}
+
+/// Whether the client prefers an HTML page, e.g. a browser opening the link
+fn accepts_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"))
+}
+
+/// Shows a note through its public link, as HTML when the client accepts
+/// `text/html` and as JSON otherwise
+#[debug_handler]
+async fn get_note(
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let link = match note_links::Model::find_active_by_token(&ctx.db, &token).await {
+        Ok(link) => link,
+        Err(ModelError::EntityNotFound) => return not_found(),
+        Err(err) => return Err(err.into()),
+    };
+    if !link.verify_password(link_password(&headers).as_deref()) {
+        return password_required();
+    }
+
+    let note = notes::Entity::find_live()
+        .filter(notes::Column::Id.eq(link.note_id))
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| Error::NotFound)?;
+    let note = PublicNoteResponse::new(&note);
+
+    if accepts_html(&headers) {
+        format::html(&note.to_html())
+    } else {
+        format::json(note)
+    }
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("public")
+        .add("/notes/:token", get(get_note))
+}
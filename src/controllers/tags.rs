@@ -0,0 +1,150 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unused_async)]
+use std::collections::HashMap;
+
+use axum::{debug_handler, routing::patch};
+use loco_rs::{controller::bad_request, prelude::*};
+use sea_orm::{QueryOrder, QuerySelect, QueryTrait};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    controllers::workspaces::CurrentWorkspace,
+    models::{
+        _entities::{note_tags, notes},
+        notes::visible_to,
+        tags,
+    },
+    views::tags::{TagResponse, TagSummaryResponse},
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Params {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MergeParams {
+    pub into_pid: String,
+}
+
+/// Loads a tag of the user. Tags of other people are a 404.
+async fn load_tag(ctx: &AppContext, pid: &str, user_id: i32) -> Result<tags::Model> {
+    match tags::Model::find_by_pid(&ctx.db, user_id, pid).await {
+        Ok(tag) => Ok(tag),
+        Err(ModelError::EntityNotFound) => not_found(),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The caller's tags by name, each with the number of notes in the active
+/// workspace they can still see that carry it
+#[debug_handler]
+pub async fn list(workspace: CurrentWorkspace, State(ctx): State<AppContext>) -> Result<Response> {
+    let user = &workspace.user;
+    let tags = tags::Entity::find()
+        .filter(tags::Column::UserId.eq(user.id))
+        .order_by_asc(tags::Column::Name)
+        .all(&ctx.db)
+        .await?;
+
+    let counts: HashMap<i32, i64> = note_tags::Entity::find()
+        .select_only()
+        .column(note_tags::Column::TagId)
+        .column_as(note_tags::Column::Id.count(), "note_count")
+        .filter(note_tags::Column::TagId.is_in(tags.iter().map(|tag| tag.id)))
+        .filter(
+            note_tags::Column::NoteId.in_subquery(
+                notes::Entity::find_live()
+                    .select_only()
+                    .column(notes::Column::Id)
+                    .filter(notes::Column::WorkspaceId.eq(workspace.workspace.id))
+                    .filter(visible_to(user.id))
+                    .into_query(),
+            ),
+        )
+        .group_by(note_tags::Column::TagId)
+        .into_tuple::<(i32, i64)>()
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .collect();
+
+    format::json(
+        tags.iter()
+            .map(|tag| TagSummaryResponse::new(tag, counts.get(&tag.id).copied().unwrap_or(0)))
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Renames a tag. Renaming onto the name of another tag is refused, merge
+/// the two instead.
+#[debug_handler]
+pub async fn update(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<Params>,
+) -> Result<Response> {
+    let Some(name) = tags::normalize_name(&params.name) else {
+        return bad_request(format!(
+            "tag names must be 1 to {} characters long",
+            tags::MAX_NAME_LENGTH
+        ));
+    };
+    let user = &workspace.user;
+    let tag = load_tag(&ctx, &pid, user.id).await?;
+
+    if let Some(existing) = tags::Model::find_by_name(&ctx.db, user.id, &name).await? {
+        if existing.id != tag.id {
+            return bad_request(format!(
+                "a tag named '{name}' already exists, merge into it instead"
+            ));
+        }
+    }
+
+    let mut item = tag.into_active_model();
+    item.name = Set(name);
+    let tag = item.update(&ctx.db).await?;
+    format::json(TagResponse::new(&tag))
+}
+
+/// Moves every note of the tag over to another tag of the caller and
+/// deletes it
+#[debug_handler]
+pub async fn merge(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<MergeParams>,
+) -> Result<Response> {
+    let user = &workspace.user;
+    let tag = load_tag(&ctx, &pid, user.id).await?;
+    let target = load_tag(&ctx, &params.into_pid, user.id).await?;
+    if tag.id == target.id {
+        return bad_request("can not merge a tag into itself");
+    }
+
+    tag.merge_into(&ctx.db, &target).await?;
+    format::json(TagResponse::new(&target))
+}
+
+/// Deletes the tag and takes it off all notes
+#[debug_handler]
+pub async fn remove(
+    workspace: CurrentWorkspace,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let tag = load_tag(&ctx, &pid, workspace.user.id).await?;
+    tag.delete(&ctx.db).await?;
+    format::empty()
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("tags")
+        .add("/", get(list))
+        .add("/:pid", patch(update))
+        .add("/:pid", delete(remove))
+        .add("/:pid/merge", post(merge))
+}
@@ -1,7 +1,17 @@
 use axum::debug_handler;
-use loco_rs::prelude::*;
+use loco_rs::{controller::bad_request, prelude::*};
+use sea_orm::{sea_query::Expr, QuerySelect, QueryTrait};
+use serde::{Deserialize, Serialize};
 
-use crate::{models::_entities::users, views::user::CurrentResponse};
+use crate::{
+    models::_entities::{note_shares, sea_orm_active_enums::ShareState, user_blocks, users},
+    views::user::{BlockedUserResponse, CurrentResponse},
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BlockParams {
+    pub user_pid: String,
+}
 
 #[debug_handler]
 async fn current(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
@@ -9,6 +19,85 @@ async fn current(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Respo
     format::json(CurrentResponse::new(&user))
 }
 
+#[debug_handler]
+async fn list_blocks(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+
+    let blocked = users::Entity::find()
+        .filter(
+            users::Column::Id.in_subquery(
+                user_blocks::Entity::find()
+                    .select_only()
+                    .column(user_blocks::Column::BlockedUserId)
+                    .filter(user_blocks::Column::UserId.eq(user.id))
+                    .into_query(),
+            ),
+        )
+        .all(&ctx.db)
+        .await?;
+
+    format::json(blocked.iter().map(BlockedUserResponse::new).collect::<Vec<_>>())
+}
+
+/// Blocks a user from sharing notes with the current user. Shares that user
+/// made, including reshares of other people's notes, are declined while they
+/// still wait for an answer.
+#[debug_handler]
+async fn block(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Json(params): Json<BlockParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let blocked = users::Model::find_by_pid_or_not_found(&ctx.db, &params.user_pid).await?;
+    if blocked.id == user.id {
+        return bad_request("you can not block yourself");
+    }
+
+    if !user_blocks::Model::exists(&ctx.db, user.id, blocked.id).await? {
+        user_blocks::ActiveModel {
+            user_id: Set(user.id),
+            blocked_user_id: Set(blocked.id),
+            ..Default::default()
+        }
+        .insert(&ctx.db)
+        .await?;
+    }
+
+    note_shares::Entity::update_many()
+        .col_expr(note_shares::Column::State, Expr::value(ShareState::Declined))
+        .filter(note_shares::Column::SharedWithUserId.eq(user.id))
+        .filter(note_shares::Column::State.eq(ShareState::Pending))
+        .filter(note_shares::Column::SharedByUserId.eq(blocked.id))
+        .exec(&ctx.db)
+        .await?;
+
+    format::json(BlockedUserResponse::new(&blocked))
+}
+
+#[debug_handler]
+async fn unblock(
+    auth: auth::JWT,
+    Path(user_pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let blocked = users::Model::find_by_pid_or_not_found(&ctx.db, &user_pid).await?;
+
+    user_blocks::Entity::delete_many()
+        .filter(user_blocks::Column::UserId.eq(user.id))
+        .filter(user_blocks::Column::BlockedUserId.eq(blocked.id))
+        .exec(&ctx.db)
+        .await?;
+
+    format::empty()
+}
+
 pub fn routes() -> Routes {
-    Routes::new().prefix("user").add("/current", get(current))
+    Routes::new()
+        .prefix("user")
+        .add("/current", get(current))
+        .add("/blocks", get(list_blocks))
+        .add("/blocks", post(block))
+        .add("/blocks/:user_pid", delete(unblock))
 }
@@ -0,0 +1,343 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unused_async)]
+use axum::{
+    debug_handler,
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+    routing::patch,
+};
+use loco_rs::{auth::jwt::UserClaims, controller::bad_request, prelude::*};
+use sea_orm::{QueryOrder, TransactionTrait};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::_entities::{
+        sea_orm_active_enums::WorkspaceRole, users, workspace_members, workspaces,
+    },
+    views::workspaces::{SwitchWorkspaceResponse, WorkspaceMemberResponse, WorkspaceResponse},
+};
+
+/// Header selecting the workspace of a single request by its pid
+pub const WORKSPACE_HEADER: &str = "x-workspace";
+
+/// The workspace a request works in. It comes from the `X-Workspace` header,
+/// else from the `workspace` claim of the JWT, else it is the user's default
+/// workspace. Users only ever get workspaces they are a member of.
+pub struct CurrentWorkspace {
+    pub user: users::Model,
+    pub workspace: workspaces::Model,
+    pub role: WorkspaceRole,
+}
+
+/// `UserClaims` keeps its extra claims private, so they are read back from
+/// their JSON form
+fn workspace_claim(claims: &UserClaims) -> Option<String> {
+    let claims = serde_json::to_value(claims).ok()?;
+    claims.get("claims")?.get("workspace")?.as_str().map(ToString::to_string)
+}
+
+/// Loads a workspace together with the user's role in it. Workspaces the
+/// user is not a member of look like they do not exist.
+async fn load_membership(
+    ctx: &AppContext,
+    pid: &str,
+    user_id: i32,
+) -> Result<(workspaces::Model, WorkspaceRole)> {
+    let workspace = match workspaces::Model::find_by_pid(&ctx.db, pid).await {
+        Ok(workspace) => workspace,
+        Err(ModelError::EntityNotFound | ModelError::Any(_)) => return not_found(),
+        Err(err) => return Err(err.into()),
+    };
+    match workspace.role_of(&ctx.db, user_id).await? {
+        Some(role) => Ok((workspace, role)),
+        None => not_found(),
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentWorkspace
+where
+    AppContext: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        let auth = auth::JWT::from_request_parts(parts, state).await?;
+        let ctx = AppContext::from_ref(state);
+        let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+
+        let requested = parts
+            .headers
+            .get(WORKSPACE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string)
+            .or_else(|| workspace_claim(&auth.claims));
+        let (workspace, role) = match requested {
+            Some(pid) => load_membership(&ctx, &pid, user.id).await?,
+            None => workspaces::Model::find_default_for_user(&ctx.db, user.id).await?,
+        };
+
+        Ok(Self { user, workspace, role })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Params {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AddMemberParams {
+    pub user_pid: String,
+    #[serde(default)]
+    pub role: WorkspaceRole,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateMemberParams {
+    pub role: WorkspaceRole,
+}
+
+/// Owners can not leave or be demoted while they are the only owner
+async fn is_last_owner(ctx: &AppContext, workspace_id: i32, user_id: i32) -> Result<bool> {
+    let owners = workspace_members::Entity::find()
+        .filter(workspace_members::Column::WorkspaceId.eq(workspace_id))
+        .filter(workspace_members::Column::Role.eq(WorkspaceRole::Owner))
+        .all(&ctx.db)
+        .await?;
+    Ok(owners.len() == 1 && owners[0].user_id == user_id)
+}
+
+#[debug_handler]
+pub async fn list(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+
+    let memberships = workspace_members::Entity::find()
+        .filter(workspace_members::Column::UserId.eq(user.id))
+        .order_by_asc(workspace_members::Column::Id)
+        .find_also_related(workspaces::Entity)
+        .all(&ctx.db)
+        .await?;
+
+    format::json(
+        memberships
+            .iter()
+            .filter_map(|(member, workspace)| {
+                workspace.as_ref().map(|workspace| WorkspaceResponse::new(workspace, member.role))
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[debug_handler]
+pub async fn add(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Json(params): Json<Params>,
+) -> Result<Response> {
+    if params.name.trim().is_empty() {
+        return bad_request("name must not be empty");
+    }
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+
+    let txn = ctx.db.begin().await?;
+    let workspace = workspaces::ActiveModel {
+        name: Set(params.name.clone()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    workspace.add_member(&txn, user.id, WorkspaceRole::Owner).await?;
+    txn.commit().await?;
+
+    format::json(WorkspaceResponse::new(&workspace, WorkspaceRole::Owner))
+}
+
+#[debug_handler(state = AppContext)]
+pub async fn current(workspace: CurrentWorkspace) -> Result<Response> {
+    format::json(WorkspaceResponse::new(&workspace.workspace, workspace.role))
+}
+
+/// Issues a token that works in the chosen workspace from now on
+#[debug_handler]
+pub async fn switch(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let (workspace, role) = load_membership(&ctx, &pid, user.id).await?;
+
+    let jwt_secret = ctx.config.get_jwt_config()?;
+    let token = user.generate_jwt_for_workspace(
+        &jwt_secret.secret,
+        &jwt_secret.expiration,
+        &workspace.pid.to_string(),
+    )?;
+
+    format::json(SwitchWorkspaceResponse {
+        token,
+        workspace: WorkspaceResponse::new(&workspace, role),
+    })
+}
+
+#[debug_handler]
+pub async fn update(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<Params>,
+) -> Result<Response> {
+    if params.name.trim().is_empty() {
+        return bad_request("name must not be empty");
+    }
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let (workspace, role) = load_membership(&ctx, &pid, user.id).await?;
+    if role < WorkspaceRole::Admin {
+        return unauthorized("only workspace admins can rename it");
+    }
+
+    let mut workspace = workspace.into_active_model();
+    workspace.name = Set(params.name.clone());
+    let workspace = workspace.update(&ctx.db).await?;
+    format::json(WorkspaceResponse::new(&workspace, role))
+}
+
+/// Deletes the workspace together with all of its notes
+#[debug_handler]
+pub async fn remove(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let (workspace, role) = load_membership(&ctx, &pid, user.id).await?;
+    if role != WorkspaceRole::Owner {
+        return unauthorized("only workspace owners can delete it");
+    }
+    workspace.delete(&ctx.db).await?;
+    format::empty()
+}
+
+#[debug_handler]
+pub async fn list_members(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let (workspace, _) = load_membership(&ctx, &pid, user.id).await?;
+
+    let members = workspace_members::Entity::find()
+        .filter(workspace_members::Column::WorkspaceId.eq(workspace.id))
+        .order_by_asc(workspace_members::Column::Id)
+        .find_also_related(users::Entity)
+        .all(&ctx.db)
+        .await?;
+
+    format::json(
+        members
+            .iter()
+            .filter_map(|(member, user)| {
+                user.as_ref().map(|user| WorkspaceMemberResponse::new(user, member.role))
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Adds a user to the workspace. Admins can not hand out a role above their
+/// own.
+#[debug_handler]
+pub async fn add_member(
+    auth: auth::JWT,
+    Path(pid): Path<String>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<AddMemberParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let (workspace, role) = load_membership(&ctx, &pid, user.id).await?;
+    if role < WorkspaceRole::Admin || params.role > role {
+        return unauthorized("insufficient workspace role");
+    }
+
+    let member = users::Model::find_by_pid_or_not_found(&ctx.db, &params.user_pid).await?;
+    let member_role = workspace.add_member(&ctx.db, member.id, params.role).await?;
+    format::json(WorkspaceMemberResponse::new(&member, member_role))
+}
+
+#[debug_handler]
+pub async fn update_member(
+    auth: auth::JWT,
+    Path((pid, user_pid)): Path<(String, String)>,
+    State(ctx): State<AppContext>,
+    Json(params): Json<UpdateMemberParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let (workspace, role) = load_membership(&ctx, &pid, user.id).await?;
+    let member = users::Model::find_by_pid_or_not_found(&ctx.db, &user_pid).await?;
+    let Some(member_role) = workspace.role_of(&ctx.db, member.id).await? else {
+        return not_found();
+    };
+
+    if role < WorkspaceRole::Admin || member_role > role || params.role > role {
+        return unauthorized("insufficient workspace role");
+    }
+    if params.role != WorkspaceRole::Owner && is_last_owner(&ctx, workspace.id, member.id).await? {
+        return bad_request("a workspace needs at least one owner");
+    }
+
+    workspace_members::Entity::update_many()
+        .col_expr(workspace_members::Column::Role, sea_orm::sea_query::Expr::value(params.role))
+        .filter(workspace_members::Column::WorkspaceId.eq(workspace.id))
+        .filter(workspace_members::Column::UserId.eq(member.id))
+        .exec(&ctx.db)
+        .await?;
+
+    format::json(WorkspaceMemberResponse::new(&member, params.role))
+}
+
+/// Removes a member from the workspace. Members can also remove themselves to
+/// leave it.
+#[debug_handler]
+pub async fn remove_member(
+    auth: auth::JWT,
+    Path((pid, user_pid)): Path<(String, String)>,
+    State(ctx): State<AppContext>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let (workspace, role) = load_membership(&ctx, &pid, user.id).await?;
+    let member = users::Model::find_by_pid_or_not_found(&ctx.db, &user_pid).await?;
+    let Some(member_role) = workspace.role_of(&ctx.db, member.id).await? else {
+        return not_found();
+    };
+
+    if member.id != user.id && (role < WorkspaceRole::Admin || member_role > role) {
+        return unauthorized("insufficient workspace role");
+    }
+    if is_last_owner(&ctx, workspace.id, member.id).await? {
+        return bad_request("a workspace needs at least one owner");
+    }
+
+    workspace_members::Entity::delete_many()
+        .filter(workspace_members::Column::WorkspaceId.eq(workspace.id))
+        .filter(workspace_members::Column::UserId.eq(member.id))
+        .exec(&ctx.db)
+        .await?;
+    format::empty()
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("workspaces")
+        .add("/", get(list))
+        .add("/", post(add))
+        .add("/current", get(current))
+        .add("/:pid", post(update))
+        .add("/:pid", delete(remove))
+        .add("/:pid/switch", post(switch))
+        .add("/:pid/members", get(list_members))
+        .add("/:pid/members", post(add_member))
+        .add("/:pid/members/:user_pid", patch(update_member))
+        .add("/:pid/members/:user_pid", delete(remove_member))
+}
@@ -1 +1,3 @@
 pub mod auth;
+pub mod shares;
+pub mod reminders;
@@ -0,0 +1,47 @@
+// reminders mailer
+#![allow(non_upper_case_globals)]
+
+use loco_rs::prelude::*;
+use serde_json::json;
+
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+use crate::models::{ notes, users };
+
+static due: Dir<'_> = include_dir!("src/mailers/reminders/due");
+
+#[allow(clippy::module_name_repetitions)]
+pub struct ReminderMailer {}
+impl Mailer for ReminderMailer {}
+impl ReminderMailer {
+    /// Reminds the user of a note when their reminder comes due at `due_at`
+    ///
+    /// # Errors
+    ///
+    /// When email sending is failed
+    pub async fn send_reminder(
+        ctx: &AppContext,
+        user: &users::Model,
+        note: &notes::Model,
+        due_at: DateTimeWithTimeZone,
+    ) -> Result<()> {
+        Self::mail_template(
+            ctx,
+            &due,
+            mailer::Args {
+                to: user.email.to_string(),
+                locals: json!({
+                  "name": user.name,
+                  "noteId": note.id,
+                  "noteTitle": note.title,
+                  "dueAt": due_at.to_rfc2822(),
+                  "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+;<html>
+
+<body>
+  Hey {{name | escape}},
+  You asked to be reminded of the note "{{noteTitle | escape}}" on {{dueAt}}.
+  <a href="{{domain}}/notes/{{noteId}}">
+    Open the note
+  </a>
+  <p>Best regards,<br>The Loco Team</p>
+</body>
+
+</html>
@@ -0,0 +1 @@
+Reminder: {{noteTitle}}
\ No newline at end of file
@@ -0,0 +1,4 @@
+Hey {{name}},
+You asked to be reminded of the note "{{noteTitle}}" on {{dueAt}}.
+
+  {{domain}}/notes/{{noteId}}
@@ -0,0 +1,118 @@
+// shares mailer
+#![allow(non_upper_case_globals)]
+
+use loco_rs::prelude::*;
+use serde_json::json;
+
+use crate::models::{ note_invitations, note_shares, notebook_shares, notebooks, notes, users };
+
+static expired: Dir<'_> = include_dir!("src/mailers/shares/expired");
+static notebook_expired: Dir<'_> = include_dir!("src/mailers/shares/notebook_expired");
+static invite: Dir<'_> = include_dir!("src/mailers/shares/invite");
+
+#[allow(clippy::module_name_repetitions)]
+pub struct ShareMailer {}
+impl Mailer for ShareMailer {}
+impl ShareMailer {
+    /// Lets the note owner know that one of their shares has expired
+    ///
+    /// # Errors
+    ///
+    /// When email sending is failed
+    pub async fn share_expired(
+        ctx: &AppContext,
+        owner: &users::Model,
+        note: &notes::Model,
+        shared_with: &users::Model,
+        share: &note_shares::Model,
+    ) -> Result<()> {
+        Self::mail_template(
+            ctx,
+            &expired,
+            mailer::Args {
+                to: owner.email.to_string(),
+                locals: json!({
+                  "name": owner.name,
+                  "noteTitle": note.title,
+                  "sharedWithName": shared_with.name,
+                  "sharedWithEmail": shared_with.email,
+                  "expiresAt": share.expires_at.map(|expires_at| expires_at.to_rfc2822()),
+                  "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lets the notebook owner know that one of their shares has expired
+    ///
+    /// # Errors
+    ///
+    /// When email sending is failed
+    pub async fn notebook_share_expired(
+        ctx: &AppContext,
+        owner: &users::Model,
+        notebook: &notebooks::Model,
+        shared_with: &users::Model,
+        share: &notebook_shares::Model,
+    ) -> Result<()> {
+        Self::mail_template(
+            ctx,
+            &notebook_expired,
+            mailer::Args {
+                to: owner.email.to_string(),
+                locals: json!({
+                  "name": owner.name,
+                  "notebookName": notebook.name,
+                  "sharedWithName": shared_with.name,
+                  "sharedWithEmail": shared_with.email,
+                  "expiresAt": share.expires_at.map(|expires_at| expires_at.to_rfc2822()),
+                  "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Invites someone without an account to the notes that were shared
+    /// with their email address, all in one email. `invitation` is any one
+    /// of the invitations; they all go to the same address.
+    ///
+    /// # Errors
+    ///
+    /// When email sending is failed
+    pub async fn send_invitation(
+        ctx: &AppContext,
+        invitation: &note_invitations::Model,
+        notes: &[&notes::Model],
+        invited_by: &users::Model,
+    ) -> Result<()> {
+        let note_titles = notes
+            .iter()
+            .map(|note| note.title.clone().unwrap_or_default())
+            .collect::<Vec<_>>();
+        Self::mail_template(
+            ctx,
+            &invite,
+            mailer::Args {
+                to: invitation.email.to_string(),
+                locals: json!({
+                  "invitedByName": invited_by.name,
+                  "noteTitles": note_titles,
+                  "inviteToken": invitation.token,
+                  "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,10 @@
+;<html>
+
+<body>
+  Hey {{name | escape}},
+  The share of your note "{{noteTitle | escape}}" with {{sharedWithName | escape}} ({{sharedWithEmail | escape}}) expired on {{expiresAt}}.
+  They no longer have access to it. You can share the note again at any time.
+  <p>Best regards,<br>The Loco Team</p>
+</body>
+
+</html>
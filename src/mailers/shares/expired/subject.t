@@ -0,0 +1 @@
+Your note "{{noteTitle}}" is no longer shared with {{sharedWithName}}
\ No newline at end of file
@@ -0,0 +1,3 @@
+Hey {{name}},
+The share of your note "{{noteTitle}}" with {{sharedWithName}} ({{sharedWithEmail}}) expired on {{expiresAt}}.
+They no longer have access to it.
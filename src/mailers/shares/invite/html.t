@@ -0,0 +1,16 @@
+;<html>
+
+<body>
+  Hi,
+  {% if noteTitles | length == 1 %}{{invitedByName | escape}} shared the note "{{noteTitles.0 | escape}}" with you.{% else %}{{invitedByName | escape}} shared these notes with you:
+  <ul>
+    {% for title in noteTitles %}<li>{{title | escape}}</li>{% endfor %}
+  </ul>{% endif %}
+  Create an account with this email address to open {% if noteTitles | length == 1 %}it{% else %}them{% endif %}:
+  <a href="{{domain}}/register#{{inviteToken}}">
+    Accept the invitation
+  </a>
+  <p>Best regards,<br>The Loco Team</p>
+</body>
+
+</html>
@@ -0,0 +1 @@
+{{invitedByName}} shared {% if noteTitles | length == 1 %}a note{% else %}{{noteTitles | length}} notes{% endif %} with you
\ No newline at end of file
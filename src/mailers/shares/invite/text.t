@@ -0,0 +1,7 @@
+{% if noteTitles | length == 1 %}{{invitedByName}} shared the note "{{noteTitles.0}}" with you.{% else %}{{invitedByName}} shared these notes with you:
+{% for title in noteTitles %}
+  - {{title}}{% endfor %}
+{% endif %}
+  Create an account with this email address to open {% if noteTitles | length == 1 %}it{% else %}them{% endif %}:
+
+  {{domain}}/register#{{inviteToken}}
@@ -0,0 +1,10 @@
+;<html>
+
+<body>
+  Hey {{name | escape}},
+  The share of your notebook "{{notebookName | escape}}" with {{sharedWithName | escape}} ({{sharedWithEmail | escape}}) expired on {{expiresAt}}.
+  They no longer have access to it or the notes in it. You can share the notebook again at any time.
+  <p>Best regards,<br>The Loco Team</p>
+</body>
+
+</html>
@@ -0,0 +1 @@
+Your notebook "{{notebookName}}" is no longer shared with {{sharedWithName}}
\ No newline at end of file
@@ -0,0 +1,3 @@
+Hey {{name}},
+The share of your notebook "{{notebookName}}" with {{sharedWithName}} ({{sharedWithEmail}}) expired on {{expiresAt}}.
+They no longer have access to it or the notes in it.
@@ -1,6 +1,25 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.0.0
 
 pub mod prelude;
+pub mod attachments;
+pub mod group_members;
+pub mod groups;
+pub mod note_group_shares;
+pub mod note_invitations;
+pub mod note_items;
+pub mod note_favourites;
+pub mod note_links;
+pub mod note_references;
+pub mod note_revisions;
 pub mod note_shares;
+pub mod note_tags;
+pub mod notebook_shares;
+pub mod notebooks;
 pub mod notes;
+pub mod reminders;
+pub mod sea_orm_active_enums;
+pub mod tags;
+pub mod user_blocks;
 pub mod users;
+pub mod workspace_members;
+pub mod workspaces;
@@ -1,6 +1,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{ Deserialize, Serialize };
 
+use super::sea_orm_active_enums::{ SharePermission, ShareState };
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "note_shares")]
 pub struct Model {
@@ -8,6 +10,10 @@ pub struct Model {
     pub id: i32,
     pub note_id: i32,
     pub shared_with_user_id: i32,
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub state: ShareState,
+    pub shared_by_user_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -24,6 +30,12 @@ pub enum Relation {
         to = "super::users::Column::Id"
     )]
     SharedWithUser,
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::SharedByUserId",
+        to = "super::users::Column::Id"
+    )]
+    SharedByUser,
 }
 
 impl Related<super::notes::Entity> for Entity {
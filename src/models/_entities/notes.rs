@@ -1,6 +1,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{ Deserialize, Serialize };
 
+use super::sea_orm_active_enums::NoteFormat;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "notes")]
 pub struct Model {
@@ -11,6 +13,14 @@ pub struct Model {
     pub title: Option<String>,
     pub content: Option<String>,
     pub user_id: i32, // Add this line
+    pub allow_reshare: bool,
+    pub workspace_id: i32,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub version: i32,
+    pub notebook_id: Option<i32>,
+    pub pinned: bool,
+    pub archived_at: Option<DateTimeWithTimeZone>,
+    pub format: NoteFormat,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -23,6 +33,22 @@ pub enum Relation {
     User,
     #[sea_orm(has_many = "super::note_shares::Entity")]
     NoteShares,
+    #[sea_orm(has_many = "super::note_revisions::Entity")]
+    NoteRevisions,
+    #[sea_orm(
+        belongs_to = "super::workspaces::Entity",
+        from = "Column::WorkspaceId",
+        to = "super::workspaces::Column::Id"
+    )]
+    Workspace,
+    #[sea_orm(
+        belongs_to = "super::notebooks::Entity",
+        from = "Column::NotebookId",
+        to = "super::notebooks::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Notebook,
 }
 
 impl Related<super::users::Entity> for Entity {
@@ -36,3 +62,21 @@ impl Related<super::note_shares::Entity> for Entity {
         Relation::NoteShares.def()
     }
 }
+
+impl Related<super::note_revisions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NoteRevisions.def()
+    }
+}
+
+impl Related<super::workspaces::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Workspace.def()
+    }
+}
+
+impl Related<super::notebooks::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notebook.def()
+    }
+}
@@ -2,4 +2,22 @@
 
 pub use super::notes::Entity as Notes;
 pub use super::users::Entity as Users;
-pub use super::note_shares::Entity as NoteShares;
\ No newline at end of file
+pub use super::note_shares::Entity as NoteShares;
+pub use super::note_invitations::Entity as NoteInvitations;
+pub use super::user_blocks::Entity as UserBlocks;
+pub use super::note_links::Entity as NoteLinks;
+pub use super::groups::Entity as Groups;
+pub use super::group_members::Entity as GroupMembers;
+pub use super::note_group_shares::Entity as NoteGroupShares;
+pub use super::workspaces::Entity as Workspaces;
+pub use super::workspace_members::Entity as WorkspaceMembers;
+pub use super::note_revisions::Entity as NoteRevisions;
+pub use super::tags::Entity as Tags;
+pub use super::note_tags::Entity as NoteTags;
+pub use super::notebooks::Entity as Notebooks;
+pub use super::notebook_shares::Entity as NotebookShares;
+pub use super::note_favourites::Entity as NoteFavourites;
+pub use super::attachments::Entity as Attachments;
+pub use super::note_references::Entity as NoteReferences;
+pub use super::note_items::Entity as NoteItems;
+pub use super::reminders::Entity as Reminders;
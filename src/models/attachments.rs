@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use loco_rs::{prelude::*, storage::Storage};
+use sea_orm::{QueryOrder, Select};
+use uuid::Uuid;
+
+pub use super::_entities::attachments::{self, ActiveModel, Column, Entity, Model};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::attachments::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.pid = ActiveValue::Set(Uuid::new_v4());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// A fresh key to store a file attached to `note_id` under
+#[must_use]
+pub fn new_storage_key(note_id: i32) -> String {
+    format!("attachments/{note_id}/{}", Uuid::new_v4())
+}
+
+impl super::_entities::attachments::Entity {
+    /// Selects the attachments of a note, oldest first
+    #[must_use]
+    pub fn find_for_note(note_id: i32) -> Select<Self> {
+        Self::find()
+            .filter(Column::NoteId.eq(note_id))
+            .order_by_asc(Column::CreatedAt)
+            .order_by_asc(Column::Id)
+    }
+}
+
+impl super::_entities::attachments::Model {
+    /// finds an attachment of the note by the provided pid
+    ///
+    /// # Errors
+    ///
+    /// When could not find the attachment or DB query error
+    pub async fn find_by_pid<C>(db: &C, note_id: i32, pid: &str) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let parse_uuid = Uuid::parse_str(pid).map_err(|e| ModelError::Any(e.into()))?;
+        let attachment = attachments::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(attachments::Column::Pid, parse_uuid)
+                    .eq(attachments::Column::NoteId, note_id)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        attachment.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Deletes the stored files of the attachments on `note_ids`. Deleting a
+    /// note takes its attachment rows with it but not their files, so call
+    /// this first. A file that can't be deleted is logged and skipped.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn delete_files_for_notes<C>(
+        db: &C,
+        storage: &Storage,
+        note_ids: Vec<i32>,
+    ) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        if note_ids.is_empty() {
+            return Ok(());
+        }
+        let attachments = attachments::Entity::find()
+            .filter(attachments::Column::NoteId.is_in(note_ids))
+            .all(db)
+            .await?;
+        for attachment in attachments {
+            attachment.delete_file(storage).await;
+        }
+        Ok(())
+    }
+
+    /// Deletes the attachment and then its stored file, see
+    /// `delete_files_for_notes` for when the file can't be deleted
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn delete_with_file<C>(self, db: &C, storage: &Storage) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        self.clone().delete(db).await?;
+        self.delete_file(storage).await;
+        Ok(())
+    }
+
+    /// Deletes only the stored file, logging and skipping it when that
+    /// fails, e.g. to clean up after an upload whose row was rolled back
+    pub async fn delete_file(&self, storage: &Storage) {
+        if let Err(err) = storage.delete(Path::new(&self.storage_key)).await {
+            tracing::warn!(
+                key = self.storage_key,
+                error = err.to_string(),
+                "could not delete attachment file"
+            );
+        }
+    }
+}
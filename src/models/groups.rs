@@ -0,0 +1,238 @@
+use loco_rs::prelude::*;
+use sea_orm::{QuerySelect, QueryTrait};
+use uuid::Uuid;
+
+pub use super::_entities::groups::{self, ActiveModel, Entity, Model};
+use super::{
+    _entities::{
+        group_members, note_group_shares, notes,
+        sea_orm_active_enums::{ShareState, WorkspaceRole},
+        workspaces,
+    },
+    shares::TransitionError,
+};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::groups::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.pid = ActiveValue::Set(Uuid::new_v4());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl super::_entities::groups::Model {
+    /// finds a group by the provided pid
+    ///
+    /// # Errors
+    ///
+    /// When could not find group or DB query error
+    pub async fn find_by_pid<C>(db: &C, pid: &str) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let parse_uuid = Uuid::parse_str(pid).map_err(|e| ModelError::Any(e.into()))?;
+        let group = groups::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(groups::Column::Pid, parse_uuid)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        group.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// The user's membership of the group in any state, including
+    /// invitations they have not answered yet
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn membership<C>(
+        &self,
+        db: &C,
+        user_id: i32,
+    ) -> ModelResult<Option<group_members::Model>>
+    where
+        C: ConnectionTrait,
+    {
+        let member = group_members::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(group_members::Column::GroupId, self.id)
+                    .eq(group_members::Column::UserId, user_id)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        Ok(member)
+    }
+
+    /// Whether the user is a member of the group who accepted joining it.
+    /// Owners are always members.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn has_member<C>(&self, db: &C, user_id: i32) -> ModelResult<bool>
+    where
+        C: ConnectionTrait,
+    {
+        let member = self.membership(db, user_id).await?;
+        Ok(member.is_some_and(|member| member.state == ShareState::Accepted))
+    }
+
+    /// Adds the user to the group as an accepted member, for its owner. They
+    /// join the workspaces of the notes shared with the group as guests, so
+    /// those notes open for them. Does nothing when they already are in the
+    /// group.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn add_member<C>(&self, db: &C, user_id: i32) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        if self.membership(db, user_id).await?.is_some() {
+            return Ok(());
+        }
+        group_members::ActiveModel {
+            group_id: ActiveValue::set(self.id),
+            user_id: ActiveValue::set(user_id),
+            state: ActiveValue::set(ShareState::Accepted),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        self.join_shared_workspaces(db, user_id).await
+    }
+
+    /// Invites the user to the group. Like a share, the invitation stays
+    /// pending until they accept it, and notes shared with the group only
+    /// reach them after that. Does nothing when they already were invited,
+    /// so a declined invitation can not be sent again.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn invite_member<C>(&self, db: &C, user_id: i32) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        if self.membership(db, user_id).await?.is_some() {
+            return Ok(());
+        }
+        group_members::ActiveModel {
+            group_id: ActiveValue::set(self.id),
+            user_id: ActiveValue::set(user_id),
+            state: ActiveValue::set(ShareState::Pending),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Moves the user's membership to `next`, following
+    /// [`ShareState::can_transition_to`]. Accepting joins the workspaces of
+    /// the notes shared with the group.
+    ///
+    /// # Errors
+    ///
+    /// When the user was not invited, the membership can not make the move,
+    /// or has DB query error
+    pub async fn transition_membership<C>(
+        &self,
+        db: &C,
+        user_id: i32,
+        next: ShareState,
+    ) -> Result<group_members::Model, TransitionError>
+    where
+        C: ConnectionTrait,
+    {
+        let member = self
+            .membership(db, user_id)
+            .await
+            .map_err(TransitionError::Model)?
+            .ok_or(TransitionError::NotFound)?;
+        if !member.state.can_transition_to(next) {
+            return Err(TransitionError::InvalidState);
+        }
+
+        let mut member = member.into_active_model();
+        member.state = ActiveValue::set(next);
+        let member = member.update(db).await?;
+        if next == ShareState::Accepted {
+            self.join_shared_workspaces(db, user_id)
+                .await
+                .map_err(TransitionError::Model)?;
+        }
+        Ok(member)
+    }
+
+    /// Adds the user to the workspaces of the notes shared with the group as
+    /// a guest
+    async fn join_shared_workspaces<C>(&self, db: &C, user_id: i32) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        let shared_workspaces = workspaces::Entity::find()
+            .filter(
+                workspaces::Column::Id.in_subquery(
+                    notes::Entity::find()
+                        .select_only()
+                        .column(notes::Column::WorkspaceId)
+                        .filter(
+                            notes::Column::Id.in_subquery(
+                                note_group_shares::Entity::find()
+                                    .select_only()
+                                    .column(note_group_shares::Column::NoteId)
+                                    .filter(note_group_shares::Column::GroupId.eq(self.id))
+                                    .into_query(),
+                            ),
+                        )
+                        .into_query(),
+                ),
+            )
+            .all(db)
+            .await?;
+        for workspace in shared_workspaces {
+            workspace.add_member(db, user_id, WorkspaceRole::Guest).await?;
+        }
+        Ok(())
+    }
+
+    /// Adds every accepted member of the group to `workspace` as a guest, for
+    /// sharing a note of the workspace with the group
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn add_members_to_workspace<C>(
+        &self,
+        db: &C,
+        workspace: &workspaces::Model,
+    ) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        let members = group_members::Entity::find()
+            .filter(group_members::Column::GroupId.eq(self.id))
+            .filter(group_members::Column::State.eq(ShareState::Accepted))
+            .all(db)
+            .await?;
+        for member in members {
+            workspace.add_member(db, member.user_id, WorkspaceRole::Guest).await?;
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,19 @@
 pub mod _entities;
+pub mod attachments;
+pub mod groups;
+pub mod note_group_shares;
+pub mod note_invitations;
+pub mod note_items;
+pub mod note_links;
+pub mod note_references;
+pub mod note_revisions;
+pub mod note_shares;
+pub mod notebook_shares;
+pub mod notebooks;
 pub mod notes;
+pub mod reminders;
+pub mod shares;
+pub mod tags;
+pub mod user_blocks;
 pub mod users;
+pub mod workspaces;
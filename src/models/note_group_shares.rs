@@ -0,0 +1,53 @@
+use chrono::offset::Local;
+use sea_orm::{entity::prelude::*, Condition, QuerySelect, QueryTrait};
+
+pub use super::_entities::note_group_shares::{self, ActiveModel, Column, Entity, Model};
+use super::_entities::{group_members, sea_orm_active_enums::ShareState, user_blocks};
+
+impl super::_entities::note_group_shares::Entity {
+    /// Condition matching group shares that have not expired yet
+    #[must_use]
+    pub fn not_expired_condition() -> Condition {
+        Condition::any()
+            .add(Column::ExpiresAt.is_null())
+            .add(Column::ExpiresAt.gt(Local::now()))
+    }
+
+    /// Selects group shares that currently grant access to the members of
+    /// any group the user belongs to. They need no accepting one by one, as
+    /// accepting the invitation to the group is the consent. Shares made by
+    /// someone the user has blocked are left out, as blocking stops a user's
+    /// shares from reaching you whichever way they are made.
+    #[must_use]
+    pub fn find_active_for_user(user_id: i32) -> Select<Self> {
+        Self::find()
+            .filter(Self::not_expired_condition())
+            .filter(
+                Condition::any().add(Column::SharedByUserId.is_null()).add(
+                    Column::SharedByUserId.not_in_subquery(
+                        user_blocks::Entity::find()
+                            .select_only()
+                            .column(user_blocks::Column::BlockedUserId)
+                            .filter(user_blocks::Column::UserId.eq(user_id))
+                            .into_query(),
+                    ),
+                ),
+            )
+            .filter(
+                Column::GroupId.in_subquery(
+                    group_members::Entity::find()
+                        .select_only()
+                        .column(group_members::Column::GroupId)
+                        .filter(group_members::Column::UserId.eq(user_id))
+                        .filter(group_members::Column::State.eq(ShareState::Accepted))
+                        .into_query(),
+                ),
+            )
+    }
+
+    /// Selects group shares whose `expires_at` is in the past
+    #[must_use]
+    pub fn find_expired() -> Select<Self> {
+        Self::find().filter(Column::ExpiresAt.lte(Local::now()))
+    }
+}
@@ -0,0 +1,108 @@
+use loco_rs::prelude::*;
+use sea_orm::sea_query::{Expr, Func};
+use uuid::Uuid;
+
+pub use super::_entities::note_invitations::{self, ActiveModel, Column, Entity, Model};
+use super::{
+    _entities::{note_shares, notes, users, workspaces},
+    shares::{upsert_user_share, ShareGrant},
+};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::note_invitations::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.token = ActiveValue::Set(Uuid::new_v4().to_string());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl super::_entities::note_invitations::Model {
+    /// Redeems the token from an invitation email. The token proves the user
+    /// received mail at the invited address, so every invitation sent there
+    /// is accepted, see `accept_for_user`.
+    ///
+    /// # Errors
+    ///
+    /// When there is no invitation with the token, or it was sent to another
+    /// address than the user's
+    pub async fn redeem(
+        db: &DatabaseConnection,
+        user: &users::Model,
+        token: &str,
+    ) -> ModelResult<Vec<note_shares::Model>> {
+        let invitation = note_invitations::Entity::find()
+            .filter(note_invitations::Column::Token.eq(token))
+            .one(db)
+            .await?
+            .ok_or_else(|| ModelError::EntityNotFound)?;
+        if !invitation.email.eq_ignore_ascii_case(&user.email) {
+            return Err(ModelError::EntityNotFound);
+        }
+        Self::accept_for_user(db, user).await
+    }
+
+    /// Turns every pending invitation sent to the user's email into a note
+    /// share, and uses the invitations up. Emails are compared ignoring case.
+    /// Only call this once the user proved they own the address. Invitations
+    /// whose share would already be expired are dropped.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn accept_for_user(
+        db: &DatabaseConnection,
+        user: &users::Model,
+    ) -> ModelResult<Vec<note_shares::Model>> {
+        let txn = db.begin().await?;
+
+        let invitations = note_invitations::Entity::find()
+            .filter(
+                Expr::expr(Func::lower(Expr::col(note_invitations::Column::Email)))
+                    .eq(user.email.to_lowercase()),
+            )
+            .all(&txn)
+            .await?;
+
+        let now = chrono::Local::now();
+        let mut shares = Vec::with_capacity(invitations.len());
+        for invitation in invitations {
+            let workspace = notes::Entity::find_by_id(invitation.note_id)
+                .find_also_related(workspaces::Entity)
+                .one(&txn)
+                .await?
+                .and_then(|(_, workspace)| workspace);
+            if let Some(workspace) = workspace {
+                if invitation.expires_at.is_none_or(|expires_at| expires_at > now) {
+                    let grant = ShareGrant {
+                        shared_with_user_id: user.id,
+                        permission: invitation.permission,
+                        expires_at: invitation.expires_at,
+                        shared_by_user_id: invitation.invited_by_user_id,
+                        update_existing: false,
+                    };
+                    let share = upsert_user_share::<note_shares::Entity, _>(
+                        &txn,
+                        &workspace,
+                        invitation.note_id,
+                        &grant,
+                    )
+                    .await?;
+                    shares.push(share);
+                }
+            }
+            invitation.delete(&txn).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(shares)
+    }
+}
@@ -0,0 +1,160 @@
+use loco_rs::prelude::*;
+use sea_orm::{QueryOrder, QuerySelect, Select};
+use uuid::Uuid;
+
+pub use super::_entities::note_items::{self, ActiveModel, Column, Entity, Model};
+use super::_entities::users;
+
+/// Longest checklist item, in characters
+pub const MAX_TEXT_LENGTH: usize = 1000;
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::note_items::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.pid = ActiveValue::Set(Uuid::new_v4());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// Trims the text of an item, `None` when nothing is left or it is too long
+#[must_use]
+pub fn normalize_text(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() || text.chars().count() > MAX_TEXT_LENGTH {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+impl super::_entities::note_items::Entity {
+    /// Selects the checklist of a note, in order
+    #[must_use]
+    pub fn find_for_note(note_id: i32) -> Select<Self> {
+        Self::find()
+            .filter(Column::NoteId.eq(note_id))
+            .order_by_asc(Column::Position)
+            .order_by_asc(Column::Id)
+    }
+}
+
+impl super::_entities::note_items::Model {
+    /// finds an item of the note by the provided pid
+    ///
+    /// # Errors
+    ///
+    /// When could not find the item or DB query error
+    pub async fn find_by_pid<C>(db: &C, note_id: i32, pid: &str) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let parse_uuid = Uuid::parse_str(pid).map_err(|e| ModelError::Any(e.into()))?;
+        let item = note_items::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(note_items::Column::Pid, parse_uuid)
+                    .eq(note_items::Column::NoteId, note_id)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        item.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// The checklist of a note, each item with who checked it
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn find_with_checkers<C>(
+        db: &C,
+        note_id: i32,
+    ) -> ModelResult<Vec<(Self, Option<users::Model>)>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Entity::find_for_note(note_id)
+            .find_also_related(users::Entity)
+            .all(db)
+            .await?)
+    }
+
+    /// Adds an item at the end of the note's checklist. `text` must already
+    /// be normalized.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn append<C>(db: &C, note_id: i32, text: String) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let last: Option<Option<i32>> = Entity::find()
+            .select_only()
+            .column_as(Column::Position.max(), "position")
+            .filter(Column::NoteId.eq(note_id))
+            .into_tuple()
+            .one(db)
+            .await?;
+        let position = last.flatten().map_or(0, |last| last + 1);
+
+        Ok(ActiveModel {
+            note_id: ActiveValue::set(note_id),
+            text: ActiveValue::set(text),
+            position: ActiveValue::set(position),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?)
+    }
+
+    /// Checks or unchecks the item, recording who checked it and when
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn set_checked<C>(self, db: &C, user_id: i32, checked: bool) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let mut item = self.into_active_model();
+        item.checked = ActiveValue::set(checked);
+        if checked {
+            item.checked_by_user_id = ActiveValue::set(Some(user_id));
+            item.checked_at = ActiveValue::set(Some(chrono::Local::now().into()));
+        } else {
+            item.checked_by_user_id = ActiveValue::set(None);
+            item.checked_at = ActiveValue::set(None);
+        }
+        Ok(item.update(db).await?)
+    }
+
+    /// Numbers the items from 0 in the order given. `items` must be the
+    /// whole checklist of one note.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn reorder<C>(db: &C, items: Vec<Self>) -> ModelResult<()>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let txn = db.begin().await?;
+        for (position, item) in (0..).zip(items) {
+            if item.position != position {
+                let mut item = item.into_active_model();
+                item.position = ActiveValue::set(position);
+                item.update(&txn).await?;
+            }
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use chrono::offset::Local;
+use loco_rs::{hash, prelude::*};
+use uuid::Uuid;
+
+pub use super::_entities::note_links::{self, ActiveModel, Entity, Model};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::note_links::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.token = ActiveValue::Set(format!(
+                "{}{}",
+                Uuid::new_v4().simple(),
+                Uuid::new_v4().simple()
+            ));
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl super::_entities::note_links::Model {
+    /// finds a link that has not expired by the provided token
+    ///
+    /// # Errors
+    ///
+    /// When could not find link by the given token or DB query error
+    pub async fn find_active_by_token(db: &DatabaseConnection, token: &str) -> ModelResult<Self> {
+        let link = note_links::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(note_links::Column::Token, token)
+                    .build(),
+            )
+            .filter(
+                sea_orm::Condition::any()
+                    .add(note_links::Column::ExpiresAt.is_null())
+                    .add(note_links::Column::ExpiresAt.gt(Local::now())),
+            )
+            .one(db)
+            .await?;
+        link.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Verifies the password of a protected link. Links without a password
+    /// accept any request.
+    #[must_use]
+    pub fn verify_password(&self, password: Option<&str>) -> bool {
+        match (&self.password, password) {
+            (None, _) => true,
+            (Some(hashed), Some(password)) => hash::verify_password(password, hashed),
+            (Some(_), None) => false,
+        }
+    }
+}
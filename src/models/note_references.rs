@@ -0,0 +1,110 @@
+use std::collections::BTreeSet;
+
+use sea_orm::{entity::prelude::*, sea_query::Expr, ActiveValue, Order, QueryOrder, QuerySelect};
+
+pub use super::_entities::note_references::{self, ActiveModel, Column, Entity, Model};
+use super::_entities::notes;
+
+/// A `[[...]]` link written in a note's content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WikiLink {
+    /// `[[#123]]`
+    Id(i32),
+    /// `[[Note title]]`
+    Title(String),
+}
+
+/// The wiki links in `content`, in order of appearance. Empty links and
+/// links spanning lines are not links.
+#[must_use]
+pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else {
+            break;
+        };
+        let inner = rest[..end].trim();
+        if inner.is_empty() || inner.contains('\n') || inner.contains("[[") {
+            continue;
+        }
+        rest = &rest[end + 2..];
+        match inner.strip_prefix('#').map(str::parse) {
+            Some(Ok(id)) => links.push(WikiLink::Id(id)),
+            _ => links.push(WikiLink::Title(inner.to_string())),
+        }
+    }
+    links
+}
+
+impl super::_entities::note_references::Model {
+    /// Makes the stored references of `note` match the wiki links in its
+    /// content. Links resolve to notes of the same workspace: `[[#id]]` by
+    /// id and `[[title]]` by exact title, preferring the author's own notes.
+    /// Links that resolve to nothing or to the note itself are dropped.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn sync<C>(db: &C, note: &notes::Model) -> Result<(), DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let mut targets = BTreeSet::new();
+        for link in parse_wiki_links(note.content.as_deref().unwrap_or_default()) {
+            if let Some(target) = resolve(db, note, &link).await? {
+                if target != note.id {
+                    targets.insert(target);
+                }
+            }
+        }
+
+        let existing: BTreeSet<i32> = Entity::find()
+            .select_only()
+            .column(Column::TargetNoteId)
+            .filter(Column::SourceNoteId.eq(note.id))
+            .into_tuple::<i32>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        let removed: Vec<i32> = existing.difference(&targets).copied().collect();
+        if !removed.is_empty() {
+            Entity::delete_many()
+                .filter(Column::SourceNoteId.eq(note.id))
+                .filter(Column::TargetNoteId.is_in(removed))
+                .exec(db)
+                .await?;
+        }
+        for target in targets.difference(&existing) {
+            ActiveModel {
+                source_note_id: ActiveValue::set(note.id),
+                target_note_id: ActiveValue::set(*target),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+async fn resolve<C>(db: &C, note: &notes::Model, link: &WikiLink) -> Result<Option<i32>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let select = notes::Entity::find_live()
+        .select_only()
+        .column(notes::Column::Id)
+        .filter(notes::Column::WorkspaceId.eq(note.workspace_id));
+    let select = match link {
+        WikiLink::Id(id) => select.filter(notes::Column::Id.eq(*id)),
+        WikiLink::Title(title) => select
+            .filter(notes::Column::Title.eq(title.as_str()))
+            .order_by(Expr::col(notes::Column::UserId).eq(note.user_id), Order::Desc)
+            .order_by_asc(notes::Column::Id),
+    };
+    select.into_tuple::<i32>().one(db).await
+}
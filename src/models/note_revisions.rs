@@ -0,0 +1,66 @@
+use sea_orm::{entity::prelude::*, ActiveValue, QueryOrder};
+
+pub use super::_entities::note_revisions::{self, ActiveModel, Column, Entity, Model};
+use super::_entities::notes;
+
+impl super::_entities::note_revisions::Entity {
+    /// Selects the revisions of a note, oldest first
+    #[must_use]
+    pub fn find_for_note(note_id: i32) -> Select<Self> {
+        Self::find()
+            .filter(Column::NoteId.eq(note_id))
+            .order_by_asc(Column::Revision)
+    }
+}
+
+impl super::_entities::note_revisions::Model {
+    /// Snapshots the current title, content and format of `note` as its next
+    /// revision. Nothing is written when they match the latest revision, so
+    /// saves that only touch other columns don't clutter the history.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn record<C>(db: &C, note: &notes::Model) -> Result<Option<Self>, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let latest = Entity::find()
+            .filter(Column::NoteId.eq(note.id))
+            .order_by_desc(Column::Revision)
+            .one(db)
+            .await?;
+
+        if let Some(latest) = &latest {
+            if latest.title == note.title
+                && latest.content == note.content
+                && latest.format == note.format
+            {
+                return Ok(None);
+            }
+        }
+
+        let revision = ActiveModel {
+            note_id: ActiveValue::set(note.id),
+            revision: ActiveValue::set(latest.map_or(1, |latest| latest.revision + 1)),
+            title: ActiveValue::set(note.title.clone()),
+            content: ActiveValue::set(note.content.clone()),
+            format: ActiveValue::set(note.format),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        Ok(Some(revision))
+    }
+
+    /// The revision as one document, the title on the first line
+    #[must_use]
+    pub fn document(&self) -> String {
+        format!(
+            "{}\n\n{}\n",
+            self.title.as_deref().unwrap_or_default(),
+            self.content.as_deref().unwrap_or_default()
+        )
+    }
+}
@@ -0,0 +1,49 @@
+use sea_orm::{entity::prelude::*, Condition};
+
+pub use super::_entities::note_shares::{self, ActiveModel, Column, Entity, Model};
+use super::_entities::sea_orm_active_enums::ShareState;
+
+impl super::_entities::note_shares::Entity {
+    /// Condition matching shares that have not expired yet. Shares without
+    /// an `expires_at` never expire.
+    #[must_use]
+    pub fn not_expired_condition() -> Condition {
+        super::shares::not_expired_condition::<Self>()
+    }
+
+    /// Condition matching shares that currently grant access: accepted by
+    /// the recipient and not expired.
+    #[must_use]
+    pub fn active_condition() -> Condition {
+        Condition::all()
+            .add(Column::State.eq(ShareState::Accepted))
+            .add(Self::not_expired_condition())
+    }
+
+    /// Selects only shares that currently grant access
+    #[must_use]
+    pub fn find_active() -> Select<Self> {
+        Self::find().filter(Self::active_condition())
+    }
+
+    /// Selects shares whose `expires_at` is in the past
+    #[must_use]
+    pub fn find_expired() -> Select<Self> {
+        super::shares::find_expired::<Self>()
+    }
+}
+
+impl ShareState {
+    /// Shares start out pending. Recipients accept or decline them, and can
+    /// later leave an accepted share or change their mind about a declined
+    /// one. Nothing goes back to pending.
+    #[must_use]
+    pub fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Accepted | Self::Declined)
+                | (Self::Accepted, Self::Declined)
+                | (Self::Declined, Self::Accepted)
+        )
+    }
+}
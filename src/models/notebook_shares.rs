@@ -0,0 +1,27 @@
+use sea_orm::{entity::prelude::*, Condition};
+
+pub use super::_entities::notebook_shares::{self, ActiveModel, Column, Entity, Model};
+use super::_entities::sea_orm_active_enums::ShareState;
+
+impl super::_entities::notebook_shares::Entity {
+    /// Condition matching notebook shares that currently grant access:
+    /// accepted by the recipient and not expired
+    #[must_use]
+    pub fn active_condition() -> Condition {
+        Condition::all()
+            .add(Column::State.eq(ShareState::Accepted))
+            .add(super::shares::not_expired_condition::<Self>())
+    }
+
+    /// Selects only notebook shares that currently grant access
+    #[must_use]
+    pub fn find_active() -> Select<Self> {
+        Self::find().filter(Self::active_condition())
+    }
+
+    /// Selects notebook shares whose `expires_at` is in the past
+    #[must_use]
+    pub fn find_expired() -> Select<Self> {
+        super::shares::find_expired::<Self>()
+    }
+}
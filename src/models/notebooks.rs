@@ -0,0 +1,231 @@
+use loco_rs::prelude::*;
+use sea_orm::{
+    sea_query::{
+        Alias, BinOper, CommonTableExpression, Expr, Query, SimpleExpr, SubQueryStatement,
+        UnionType, WithClause,
+    },
+    QuerySelect, QueryTrait,
+};
+use uuid::Uuid;
+
+pub use super::_entities::notebooks::{self, ActiveModel, Column, Entity, Model};
+use super::_entities::{notebook_shares, notes, sea_orm_active_enums::SharePermission};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::notebooks::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.pid = ActiveValue::Set(Uuid::new_v4());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl super::_entities::notebooks::Entity {
+    /// Expression matching when `column` holds the id of a notebook shared
+    /// with the user, or of one nested anywhere below such a notebook.
+    /// Notebooks added to a shared notebook later are covered as well.
+    #[must_use]
+    pub fn in_shared_subtree(column: SimpleExpr, user_id: i32) -> SimpleExpr {
+        let shared = Alias::new("shared_notebooks");
+        let id = Alias::new("id");
+
+        let mut tree = notebook_shares::Entity::find_active()
+            .select_only()
+            .column_as(notebook_shares::Column::NotebookId, "id")
+            .filter(notebook_shares::Column::SharedWithUserId.eq(user_id))
+            .into_query();
+        tree.union(
+            UnionType::Distinct,
+            Query::select()
+                .column((Self, Column::Id))
+                .from(Self)
+                .inner_join(
+                    shared.clone(),
+                    Expr::col((Self, Column::ParentId)).equals((shared.clone(), id.clone())),
+                )
+                .to_owned(),
+        );
+
+        let cte = CommonTableExpression::new()
+            .query(tree)
+            .column(id.clone())
+            .table_name(shared.clone())
+            .to_owned();
+        let ids = WithClause::new()
+            .recursive(true)
+            .cte(cte)
+            .to_owned()
+            .query(Query::select().column(id).from(shared).to_owned());
+
+        column.binary(
+            BinOper::In,
+            SimpleExpr::SubQuery(None, Box::new(SubQueryStatement::WithStatement(ids))),
+        )
+    }
+}
+
+impl super::_entities::notebooks::Model {
+    /// finds a notebook by the provided pid
+    ///
+    /// # Errors
+    ///
+    /// When could not find notebook or DB query error
+    pub async fn find_by_pid<C>(db: &C, pid: &str) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let parse_uuid = Uuid::parse_str(pid).map_err(|e| ModelError::Any(e.into()))?;
+        let notebook = notebooks::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(notebooks::Column::Pid, parse_uuid)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        notebook.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Ids of this notebook and every notebook it is nested in, innermost
+    /// first
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn ancestor_ids<C>(&self, db: &C) -> ModelResult<Vec<i32>>
+    where
+        C: ConnectionTrait,
+    {
+        let mut ids = vec![self.id];
+        let mut parent_id = self.parent_id;
+        while let Some(id) = parent_id {
+            if ids.contains(&id) {
+                break;
+            }
+            ids.push(id);
+            parent_id = notebooks::Entity::find_by_id(id)
+                .one(db)
+                .await?
+                .and_then(|parent| parent.parent_id);
+        }
+        Ok(ids)
+    }
+
+    /// Ids of this notebook and every notebook nested in it
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn subtree_ids<C>(&self, db: &C) -> ModelResult<Vec<i32>>
+    where
+        C: ConnectionTrait,
+    {
+        let mut ids = vec![self.id];
+        let mut level = vec![self.id];
+        while !level.is_empty() {
+            level = notebooks::Entity::find()
+                .select_only()
+                .column(notebooks::Column::Id)
+                .filter(notebooks::Column::ParentId.is_in(level))
+                .filter(notebooks::Column::Id.is_not_in(ids.clone()))
+                .into_tuple::<i32>()
+                .all(db)
+                .await?;
+            ids.extend(&level);
+        }
+        Ok(ids)
+    }
+
+    /// The highest permission any active share of this notebook, or of a
+    /// notebook it is nested in, grants the user
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn shared_permission<C>(
+        &self,
+        db: &C,
+        user_id: i32,
+    ) -> ModelResult<Option<SharePermission>>
+    where
+        C: ConnectionTrait,
+    {
+        let shares = notebook_shares::Entity::find_active()
+            .filter(notebook_shares::Column::NotebookId.is_in(self.ancestor_ids(db).await?))
+            .filter(notebook_shares::Column::SharedWithUserId.eq(user_id))
+            .all(db)
+            .await?;
+        Ok(shares.into_iter().map(|share| share.permission).max())
+    }
+
+    /// Deletes the notebook. Its notes and nested notebooks move up into
+    /// its parent, or to the top level.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn delete_reparenting<C>(self, db: &C) -> ModelResult<()>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let txn = db.begin().await?;
+
+        notebooks::Entity::update_many()
+            .col_expr(notebooks::Column::ParentId, Expr::value(self.parent_id))
+            .filter(notebooks::Column::ParentId.eq(self.id))
+            .exec(&txn)
+            .await?;
+        notes::Entity::update_many()
+            .col_expr(notes::Column::NotebookId, Expr::value(self.parent_id))
+            .filter(notes::Column::NotebookId.eq(self.id))
+            .exec(&txn)
+            .await?;
+        self.delete(&txn).await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes the notebook with every notebook nested in it, and moves all
+    /// their notes to the trash. Restored notes come back at the top level.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn delete_with_notes<C>(self, db: &C) -> ModelResult<()>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let ids = self.subtree_ids(db).await?;
+        let txn = db.begin().await?;
+
+        notes::Entity::update_many()
+            .col_expr(
+                notes::Column::DeletedAt,
+                Expr::value(chrono::Local::now().fixed_offset()),
+            )
+            .filter(notes::Column::NotebookId.is_in(ids.clone()))
+            .filter(notes::Column::DeletedAt.is_null())
+            .exec(&txn)
+            .await?;
+        notes::Entity::update_many()
+            .col_expr(notes::Column::NotebookId, Expr::value(Option::<i32>::None))
+            .filter(notes::Column::NotebookId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        // innermost first, so no notebook is deleted before its children
+        for id in ids.into_iter().rev() {
+            notebooks::Entity::delete_by_id(id).exec(&txn).await?;
+        }
+
+        txn.commit().await?;
+        Ok(())
+    }
+}
@@ -1,8 +1,517 @@
-use sea_orm::entity::prelude::*;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use loco_rs::prelude::*;
+use sea_orm::{
+    sea_query::{Alias, Expr, Func, JoinType, OnConflict, SimpleExpr},
+    prelude::DateTimeWithTimeZone,
+    Condition, DbBackend, FromQueryResult, Order, PaginatorTrait, QueryOrder, QuerySelect,
+    QueryTrait, Select,
+};
+use serde::{Deserialize, Serialize};
 
-use super::_entities::notes::ActiveModel;
+pub use super::_entities::notes::{self, ActiveModel, Column, Entity, Model};
+use super::{note_group_shares, note_references, notebooks};
+use super::_entities::{
+    note_favourites, note_revisions, note_shares,
+    sea_orm_active_enums::{SharePermission, ShareState},
+};
 
+#[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
     // extend activemodel below (keep comment for generators)
+
+    /// Every update bumps `version`, which clients send back in `If-Match`
+    /// to detect that someone else changed the note in the meantime. Edits
+    /// to the title, content or format also move `updated_at`.
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let mut this = self;
+        if !insert {
+            if let ActiveValue::Unchanged(version) = this.version {
+                this.version = ActiveValue::set(version + 1);
+            }
+            let edited = this.title.is_set() || this.content.is_set() || this.format.is_set();
+            if edited && !this.updated_at.is_set() {
+                this.updated_at = ActiveValue::set(chrono::Local::now().fixed_offset());
+            }
+        }
+        Ok(this)
+    }
+
+    /// Every saved change to the title, content or format becomes a new revision,
+    /// and the wiki links in the content are stored as references
+    async fn after_save<C>(model: Model, db: &C, _insert: bool) -> Result<Model, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        note_revisions::Model::record(db, &model).await?;
+        note_references::Model::sync(db, &model).await?;
+        Ok(model)
+    }
+}
+
+/// Marks the start of a matched word in search snippets
+pub const SNIPPET_MATCH_START: &str = "\u{2}";
+/// Marks the end of a matched word in search snippets
+pub const SNIPPET_MATCH_END: &str = "\u{3}";
+
+/// A note found by `Entity::search`
+#[derive(Debug, FromQueryResult)]
+pub struct SearchHit {
+    pub id: i32,
+    pub title: Option<String>,
+    /// Matched words are wrapped in `SNIPPET_MATCH_START` and `SNIPPET_MATCH_END`
+    pub snippet: Option<String>,
+    /// Higher is better; only comparable within one search
+    pub search_rank: f64,
+}
+
+/// Quotes every word so FTS5 treats user input as plain terms, never as
+/// query syntax
+fn fts5_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Condition matching notes the user owns or that are shared with them,
+/// directly or through one of their groups
+#[must_use]
+pub fn visible_to(user_id: i32) -> Condition {
+    Condition::any()
+        .add(Column::UserId.eq(user_id))
+        .add(shared_with(user_id))
+}
+
+/// Condition matching notes with an active share for the user, directly,
+/// through one of their groups or through a shared notebook they are in
+#[must_use]
+pub fn shared_with(user_id: i32) -> Condition {
+    Condition::any()
+        .add(
+            Column::Id.in_subquery(
+                note_shares::Entity::find_active()
+                    .select_only()
+                    .column(note_shares::Column::NoteId)
+                    .filter(note_shares::Column::SharedWithUserId.eq(user_id))
+                    .into_query(),
+            ),
+        )
+        .add(
+            Column::Id.in_subquery(
+                note_group_shares::Entity::find_active_for_user(user_id)
+                    .select_only()
+                    .column(note_group_shares::Column::NoteId)
+                    .into_query(),
+            ),
+        )
+        .add(notebooks::Entity::in_shared_subtree(
+            Expr::col((Entity, Column::NotebookId)).into(),
+            user_id,
+        ))
+}
+
+impl super::_entities::notes::Entity {
+    /// Selects notes that are not in the trash
+    #[must_use]
+    pub fn find_live() -> Select<Self> {
+        Self::find().filter(Column::DeletedAt.is_null())
+    }
+
+    /// Selects notes that are in the trash
+    #[must_use]
+    pub fn find_trashed() -> Select<Self> {
+        Self::find().filter(Column::DeletedAt.is_not_null())
+    }
+
+    /// Selects notes outside the trash whose title or content match the
+    /// full-text `query`, best matches first, as `SearchHit`s. Postgres
+    /// searches the `search_vector` column, SQLite the `notes_fts` table.
+    ///
+    /// # Errors
+    ///
+    /// When the database has no full-text search set up
+    pub fn search(backend: DbBackend, query: &str) -> Result<Select<Self>, DbErr> {
+        let snippet_marks = [SNIPPET_MATCH_START.to_string(), SNIPPET_MATCH_END.to_string()];
+        let mut select = Self::find_live()
+            .select_only()
+            .column(Column::Id)
+            .column(Column::Title);
+
+        select = match backend {
+            DbBackend::Postgres => {
+                let [start, end] = snippet_marks;
+                select
+                    .expr_as(
+                        Expr::cust_with_values(
+                            "ts_rank(notes.search_vector, websearch_to_tsquery('english', $1))::float8",
+                            [query],
+                        ),
+                        "search_rank",
+                    )
+                    .expr_as(
+                        Expr::cust_with_values(
+                            "ts_headline('english', concat_ws(' ', notes.title, notes.content), \
+                             websearch_to_tsquery('english', $1), $2)",
+                            [
+                                query.to_string(),
+                                format!("StartSel={start}, StopSel={end}, MaxWords=20, MinWords=5"),
+                            ],
+                        ),
+                        "snippet",
+                    )
+                    .filter(Expr::cust_with_values(
+                        "notes.search_vector @@ websearch_to_tsquery('english', $1)",
+                        [query],
+                    ))
+            }
+            DbBackend::Sqlite => {
+                QueryTrait::query(&mut select).join(
+                    JoinType::InnerJoin,
+                    Alias::new("notes_fts"),
+                    Expr::cust("notes_fts.rowid = notes.id"),
+                );
+                select
+                    .expr_as(Expr::cust("-bm25(notes_fts, 10.0, 1.0)"), "search_rank")
+                    .expr_as(
+                        Expr::cust_with_values("snippet(notes_fts, -1, ?, ?, '…', 16)", snippet_marks),
+                        "snippet",
+                    )
+                    .filter(Expr::cust_with_values("notes_fts MATCH ?", [fts5_query(query)]))
+            }
+            DbBackend::MySql => {
+                return Err(DbErr::Custom(
+                    "full-text search is only set up for Postgres and SQLite".to_string(),
+                ));
+            }
+        };
+
+        Ok(select
+            .order_by_desc(Expr::cust("search_rank"))
+            .order_by_asc(Column::Id))
+    }
+}
+
+impl super::_entities::notes::Model {
+    /// Pins or unpins the note. Like archiving, this is not an edit, so the
+    /// version and `updated_at` stay as they are.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn set_pinned<C>(mut self, db: &C, pinned: bool) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        notes::Entity::update_many()
+            .col_expr(Column::Pinned, Expr::value(pinned))
+            .filter(Column::Id.eq(self.id))
+            .exec(db)
+            .await?;
+        self.pinned = pinned;
+        Ok(self)
+    }
+
+    /// Archives the note, or takes it out of the archive again
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn set_archived<C>(mut self, db: &C, archived: bool) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let archived_at = archived.then(|| chrono::Local::now().fixed_offset());
+        notes::Entity::update_many()
+            .col_expr(Column::ArchivedAt, Expr::value(archived_at))
+            .filter(Column::Id.eq(self.id))
+            .exec(db)
+            .await?;
+        self.archived_at = archived_at;
+        Ok(self)
+    }
+
+    /// Marks the note as a favourite of the user, or unmarks it
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn set_favourite<C>(&self, db: &C, user_id: i32, favourite: bool) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        if favourite {
+            note_favourites::Entity::insert(note_favourites::ActiveModel {
+                note_id: ActiveValue::set(self.id),
+                user_id: ActiveValue::set(user_id),
+                ..Default::default()
+            })
+            .on_conflict(
+                OnConflict::columns([note_favourites::Column::UserId, note_favourites::Column::NoteId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec(db)
+            .await?;
+        } else {
+            note_favourites::Entity::delete_many()
+                .filter(note_favourites::Column::NoteId.eq(self.id))
+                .filter(note_favourites::Column::UserId.eq(user_id))
+                .exec(db)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Moves the note to the trash. Its shares are kept so restoring the note
+    /// gives everyone their access back.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn move_to_trash(self, db: &DatabaseConnection) -> ModelResult<Self> {
+        let mut note: ActiveModel = self.into();
+        note.deleted_at = ActiveValue::set(Some(chrono::Local::now().into()));
+        Ok(note.update(db).await?)
+    }
+
+    /// Takes the note back out of the trash
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn restore_from_trash(self, db: &DatabaseConnection) -> ModelResult<Self> {
+        let mut note: ActiveModel = self.into();
+        note.deleted_at = ActiveValue::set(None);
+        Ok(note.update(db).await?)
+    }
+
+    /// Hands the note over to `new_owner_id`. Existing shares are kept; a share
+    /// the new owner had on the note is dropped since they now own it. With
+    /// `keep_previous_owner` the previous owner stays on as an editor.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn transfer_to<C>(
+        self,
+        db: &C,
+        new_owner_id: i32,
+        keep_previous_owner: bool,
+    ) -> ModelResult<Self>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let txn = db.begin().await?;
+        let previous_owner_id = self.user_id;
+
+        note_shares::Entity::delete_many()
+            .filter(note_shares::Column::NoteId.eq(self.id))
+            .filter(note_shares::Column::SharedWithUserId.eq(new_owner_id))
+            .exec(&txn)
+            .await?;
+
+        let mut note: ActiveModel = self.into();
+        note.user_id = ActiveValue::set(new_owner_id);
+        // the notebook stays with the previous owner
+        note.notebook_id = ActiveValue::set(None);
+        let note = note.update(&txn).await?;
+
+        if keep_previous_owner {
+            note_shares::ActiveModel {
+                note_id: ActiveValue::set(note.id),
+                shared_with_user_id: ActiveValue::set(previous_owner_id),
+                permission: ActiveValue::set(SharePermission::Edit),
+                state: ActiveValue::set(ShareState::Accepted),
+                shared_by_user_id: ActiveValue::set(Some(new_owner_id)),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+        Ok(note)
+    }
+}
+
+/// Column note listings can be sorted by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    Title,
+}
+
+impl NoteSort {
+    /// Notes without a title sort as if it were empty
+    fn expr(self) -> SimpleExpr {
+        match self {
+            Self::CreatedAt => Expr::col((Entity, Column::CreatedAt)).into(),
+            Self::UpdatedAt => Expr::col((Entity, Column::UpdatedAt)).into(),
+            Self::Title => Func::coalesce([Expr::col((Entity, Column::Title)).into(), Expr::val("").into()])
+                .into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// The sort value of the last note on a page
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "sort", content = "value", rename_all = "snake_case")]
+enum CursorKey {
+    CreatedAt(DateTimeWithTimeZone),
+    UpdatedAt(DateTimeWithTimeZone),
+    Title(String),
+}
+
+impl CursorKey {
+    fn value(&self) -> sea_orm::Value {
+        match self {
+            Self::CreatedAt(value) | Self::UpdatedAt(value) => (*value).into(),
+            Self::Title(value) => value.clone().into(),
+        }
+    }
+}
+
+/// Where the next page of a note listing starts. Clients only see it
+/// encoded, as an opaque string.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NoteCursor {
+    /// Pinned notes come before all others
+    #[serde(default)]
+    pinned: bool,
+    key: CursorKey,
+    id: i32,
 }
 
+impl NoteCursor {
+    fn after(note: &Model, sort: NoteSort) -> Self {
+        let key = match sort {
+            NoteSort::CreatedAt => CursorKey::CreatedAt(note.created_at),
+            NoteSort::UpdatedAt => CursorKey::UpdatedAt(note.updated_at),
+            NoteSort::Title => CursorKey::Title(note.title.clone().unwrap_or_default()),
+        };
+        Self {
+            pinned: note.pinned,
+            key,
+            id: note.id,
+        }
+    }
+
+    #[must_use]
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// `None` when the cursor is malformed
+    #[must_use]
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let json = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+
+    /// Whether the cursor was handed out for a listing sorted by `sort`
+    #[must_use]
+    pub fn is_for(&self, sort: NoteSort) -> bool {
+        matches!(
+            (&self.key, sort),
+            (CursorKey::CreatedAt(_), NoteSort::CreatedAt)
+                | (CursorKey::UpdatedAt(_), NoteSort::UpdatedAt)
+                | (CursorKey::Title(_), NoteSort::Title)
+        )
+    }
+}
+
+/// One page of a note listing
+#[derive(Debug)]
+pub struct NotePage {
+    pub items: Vec<Model>,
+    /// Pass as `after` to get the next page, `None` on the last page
+    pub next_cursor: Option<String>,
+    /// How many notes the listing has over all pages
+    pub total: u64,
+}
+
+impl super::_entities::notes::Entity {
+    /// Loads the page of `select` that starts after `after`. Pinned notes
+    /// come first whatever the order, and ties on the sort column are broken
+    /// by id, so every note shows up exactly once even when notes are added
+    /// between requests.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn paginate<C>(
+        db: &C,
+        select: Select<Self>,
+        sort: NoteSort,
+        order: SortOrder,
+        limit: u64,
+        after: Option<&NoteCursor>,
+    ) -> ModelResult<NotePage>
+    where
+        C: ConnectionTrait,
+    {
+        let total = select.clone().count(db).await?;
+
+        let mut select = select;
+        if let Some(after) = after {
+            let value = after.key.value();
+            let past_key = Expr::expr(sort.expr());
+            let past_key = match order {
+                SortOrder::Asc => past_key.gt(value.clone()),
+                SortOrder::Desc => past_key.lt(value.clone()),
+            };
+            let past_id = match order {
+                SortOrder::Asc => Column::Id.gt(after.id),
+                SortOrder::Desc => Column::Id.lt(after.id),
+            };
+            let past_sort = Condition::any()
+                .add(past_key)
+                .add(Condition::all().add(Expr::expr(sort.expr()).eq(value)).add(past_id));
+            select = select.filter(if after.pinned {
+                Condition::any()
+                    .add(Condition::all().add(Column::Pinned.eq(true)).add(past_sort))
+                    .add(Column::Pinned.eq(false))
+            } else {
+                Condition::all().add(Column::Pinned.eq(false)).add(past_sort)
+            });
+        }
+
+        let order = match order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        };
+        // one extra row tells whether there is a next page
+        let mut items = select
+            .order_by_desc(Column::Pinned)
+            .order_by(sort.expr(), order.clone())
+            .order_by(Column::Id, order)
+            .limit(limit + 1)
+            .all(db)
+            .await?;
+
+        let next_cursor = if items.len() as u64 > limit {
+            items.truncate(usize::try_from(limit).unwrap_or(usize::MAX));
+            items.last().map(|last| NoteCursor::after(last, sort).encode())
+        } else {
+            None
+        };
+
+        Ok(NotePage { items, next_cursor, total })
+    }
+}
@@ -0,0 +1,246 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use loco_rs::prelude::*;
+use sea_orm::{
+    prelude::DateTimeWithTimeZone,
+    sea_query::{Expr, LockBehavior, LockType},
+    Condition, QueryOrder, QuerySelect, Select, TransactionTrait,
+};
+use uuid::Uuid;
+
+pub use super::_entities::reminders::{self, ActiveModel, Column, Entity, Model};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::reminders::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.pid = ActiveValue::Set(Uuid::new_v4());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// How a reminder repeats, stored in `recurrence` as `daily`, `weekly` or a
+/// cron expression
+#[derive(Debug, Clone)]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    /// Five fields (minute to weekday) like crontab, or six and seven with
+    /// seconds and years; evaluated in UTC. Five fields number weekdays like
+    /// crontab, 0 or 7 for Sunday and 1 for Monday. Six and seven fields use
+    /// the numbers of the `cron` crate, 1 for Sunday to 7 for Saturday.
+    Cron(Box<cron::Schedule>),
+}
+
+impl Recurrence {
+    /// `None` when `value` is neither `daily`, `weekly` nor a valid cron
+    /// expression
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "daily" => Some(Self::Daily),
+            "weekly" => Some(Self::Weekly),
+            expression => {
+                let fields = expression.split_whitespace().collect::<Vec<_>>();
+                let expression = if let [minute, hour, day, month, weekday] = fields[..] {
+                    let weekday = crontab_weekdays(weekday)?;
+                    format!("0 {minute} {hour} {day} {month} {weekday}")
+                } else {
+                    expression.to_string()
+                };
+                cron::Schedule::from_str(&expression)
+                    .ok()
+                    .map(|schedule| Self::Cron(Box::new(schedule)))
+            }
+        }
+    }
+
+    /// The first occurrence after `now`, counting from `due_at`. `None` when
+    /// the schedule has no more occurrences.
+    #[must_use]
+    pub fn next_after(
+        &self,
+        due_at: DateTime<FixedOffset>,
+        now: DateTime<FixedOffset>,
+    ) -> Option<DateTime<FixedOffset>> {
+        let period = match self {
+            Self::Daily => Duration::days(1),
+            Self::Weekly => Duration::weeks(1),
+            Self::Cron(schedule) => {
+                let after = due_at.max(now).with_timezone(&Utc);
+                return schedule.after(&after).next().map(|next| next.fixed_offset());
+            }
+        };
+        if due_at > now {
+            return Some(due_at);
+        }
+        // skips occurrences missed while nothing was sent, in one step
+        let missed = (now - due_at).num_seconds() / period.num_seconds();
+        Some(due_at + period * i32::try_from(missed + 1).ok()?)
+    }
+}
+
+/// Renumbers the weekday field of a crontab expression for the `cron`
+/// crate, which counts from 1 for Sunday where crontab counts from 0 (and
+/// takes 7 for Sunday too). Numeric items are spelled out as lists, so
+/// ranges ending on Sunday keep working. Names are left alone.
+fn crontab_weekdays(field: &str) -> Option<String> {
+    let items = field
+        .split(',')
+        .map(|item| {
+            if !item.contains(|c: char| c.is_ascii_digit()) {
+                return Some(item.to_string());
+            }
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step.parse::<usize>().ok()?)),
+                None => (item, None),
+            };
+            let (first, last) = match range.split_once('-') {
+                _ if range == "*" => (0, 6),
+                Some((first, last)) => (first.parse::<u32>().ok()?, last.parse::<u32>().ok()?),
+                // `1/2` counts on from 1 to the end of the week
+                None if step.is_some() => (range.parse::<u32>().ok()?, 6),
+                None => (range.parse::<u32>().ok()?, range.parse::<u32>().ok()?),
+            };
+            if first > last || last > 7 || step == Some(0) {
+                return None;
+            }
+            let days = (first..=last)
+                .step_by(step.unwrap_or(1))
+                .map(|day| day % 7 + 1)
+                .collect::<BTreeSet<_>>();
+            Some(days.iter().map(ToString::to_string).collect::<Vec<_>>().join(","))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(items.join(","))
+}
+
+impl super::_entities::reminders::Entity {
+    /// Selects reminders whose email is due at `now`: `remind_at` has passed
+    /// and nothing was sent for it yet
+    #[must_use]
+    pub fn find_due(now: DateTimeWithTimeZone) -> Select<Self> {
+        Self::find()
+            .filter(Column::RemindAt.lte(now))
+            .filter(
+                Condition::any()
+                    .add(Column::SentAt.is_null())
+                    .add(Expr::col(Column::SentAt).lt(Expr::col(Column::RemindAt))),
+            )
+            .order_by_asc(Column::RemindAt)
+    }
+
+    /// Claims the reminders due at `now` by marking them sent, in one
+    /// transaction with the rows locked, so runs that overlap never claim the
+    /// same reminder twice. Rows locked by another run are skipped. Returns
+    /// the reminders as they were when claimed, with the occurrence that came
+    /// due.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn claim_due<C>(db: &C, now: DateTimeWithTimeZone) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let txn = db.begin().await?;
+        let due = Self::find_due(now)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .all(&txn)
+            .await?;
+        for reminder in &due {
+            reminder.clone().mark_sent(&txn, now).await?;
+        }
+        txn.commit().await?;
+        Ok(due)
+    }
+
+    /// Selects the reminders a user set on a note, soonest first
+    #[must_use]
+    pub fn find_for_note(note_id: i32, user_id: i32) -> Select<Self> {
+        Self::find()
+            .filter(Column::NoteId.eq(note_id))
+            .filter(Column::UserId.eq(user_id))
+            .order_by_asc(Column::RemindAt)
+            .order_by_asc(Column::Id)
+    }
+}
+
+impl super::_entities::reminders::Model {
+    /// finds a reminder the user set on the note by the provided pid
+    ///
+    /// # Errors
+    ///
+    /// When could not find the reminder or DB query error
+    pub async fn find_by_pid<C>(db: &C, note_id: i32, user_id: i32, pid: &str) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let parse_uuid = Uuid::parse_str(pid).map_err(|e| ModelError::Any(e.into()))?;
+        let reminder = reminders::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(reminders::Column::Pid, parse_uuid)
+                    .eq(reminders::Column::NoteId, note_id)
+                    .eq(reminders::Column::UserId, user_id)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        reminder.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Whether the reminder will send another email
+    #[must_use]
+    pub fn is_pending(&self) -> bool {
+        self.sent_at.is_none_or(|sent_at| sent_at < self.remind_at)
+    }
+
+    /// Records that the email went out at `now`. Recurring reminders move on
+    /// to their next occurrence, which also ends any snooze.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn mark_sent<C>(self, db: &C, now: DateTimeWithTimeZone) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let next = self
+            .recurrence
+            .as_deref()
+            .and_then(Recurrence::parse)
+            .and_then(|recurrence| recurrence.next_after(self.due_at, now));
+
+        let mut reminder = self.into_active_model();
+        reminder.sent_at = ActiveValue::set(Some(now));
+        if let Some(next) = next {
+            reminder.due_at = ActiveValue::set(next);
+            reminder.remind_at = ActiveValue::set(next);
+        }
+        Ok(reminder.update(db).await?)
+    }
+
+    /// Sends the email at `until` instead. The occurrence stays the same, so
+    /// a recurring reminder keeps its schedule.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn snooze<C>(self, db: &C, until: DateTimeWithTimeZone) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let mut reminder = self.into_active_model();
+        reminder.remind_at = ActiveValue::set(until);
+        Ok(reminder.update(db).await?)
+    }
+}
@@ -0,0 +1,280 @@
+use chrono::offset::Local;
+use loco_rs::prelude::*;
+use sea_orm::{prelude::DateTimeWithTimeZone, sea_query::OnConflict, Condition, Select};
+
+use super::_entities::{
+    note_shares, notebook_shares,
+    sea_orm_active_enums::{SharePermission, ShareState, WorkspaceRole},
+    workspaces,
+};
+
+/// What a share with a single user grants
+#[derive(Debug, Clone)]
+pub struct ShareGrant {
+    pub shared_with_user_id: i32,
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub shared_by_user_id: i32,
+    /// Whether sharing changes an existing share with the user. Only owners
+    /// and managers may, see [`may_update_shares`]; for anyone else the
+    /// existing share stays as it is.
+    pub update_existing: bool,
+}
+
+/// A table of shares with single users, one row per shared item and user
+pub trait UserShareEntity: EntityTrait {
+    /// The column pointing at the shared item
+    fn item_column() -> Self::Column;
+    fn shared_with_column() -> Self::Column;
+    fn state_column() -> Self::Column;
+    fn expires_at_column() -> Self::Column;
+    /// The columns sharing again overwrites
+    fn grant_columns() -> [Self::Column; 3];
+    fn new_share(item_id: i32, grant: &ShareGrant) -> Self::ActiveModel;
+    fn state(share: &Self::Model) -> ShareState;
+}
+
+impl UserShareEntity for note_shares::Entity {
+    fn item_column() -> Self::Column {
+        note_shares::Column::NoteId
+    }
+
+    fn shared_with_column() -> Self::Column {
+        note_shares::Column::SharedWithUserId
+    }
+
+    fn state_column() -> Self::Column {
+        note_shares::Column::State
+    }
+
+    fn expires_at_column() -> Self::Column {
+        note_shares::Column::ExpiresAt
+    }
+
+    fn grant_columns() -> [Self::Column; 3] {
+        [
+            note_shares::Column::Permission,
+            note_shares::Column::ExpiresAt,
+            note_shares::Column::SharedByUserId,
+        ]
+    }
+
+    fn new_share(item_id: i32, grant: &ShareGrant) -> Self::ActiveModel {
+        note_shares::ActiveModel {
+            note_id: ActiveValue::set(item_id),
+            shared_with_user_id: ActiveValue::set(grant.shared_with_user_id),
+            permission: ActiveValue::set(grant.permission),
+            expires_at: ActiveValue::set(grant.expires_at),
+            shared_by_user_id: ActiveValue::set(Some(grant.shared_by_user_id)),
+            ..Default::default()
+        }
+    }
+
+    fn state(share: &Self::Model) -> ShareState {
+        share.state
+    }
+}
+
+impl UserShareEntity for notebook_shares::Entity {
+    fn item_column() -> Self::Column {
+        notebook_shares::Column::NotebookId
+    }
+
+    fn shared_with_column() -> Self::Column {
+        notebook_shares::Column::SharedWithUserId
+    }
+
+    fn state_column() -> Self::Column {
+        notebook_shares::Column::State
+    }
+
+    fn expires_at_column() -> Self::Column {
+        notebook_shares::Column::ExpiresAt
+    }
+
+    fn grant_columns() -> [Self::Column; 3] {
+        [
+            notebook_shares::Column::Permission,
+            notebook_shares::Column::ExpiresAt,
+            notebook_shares::Column::SharedByUserId,
+        ]
+    }
+
+    fn new_share(item_id: i32, grant: &ShareGrant) -> Self::ActiveModel {
+        notebook_shares::ActiveModel {
+            notebook_id: ActiveValue::set(item_id),
+            shared_with_user_id: ActiveValue::set(grant.shared_with_user_id),
+            permission: ActiveValue::set(grant.permission),
+            expires_at: ActiveValue::set(grant.expires_at),
+            shared_by_user_id: ActiveValue::set(Some(grant.shared_by_user_id)),
+            ..Default::default()
+        }
+    }
+
+    fn state(share: &Self::Model) -> ShareState {
+        share.state
+    }
+}
+
+/// Shares an item of `workspace` with a user, or updates the permission,
+/// expiry and sharer when it already is shared with them and the grant may
+/// update existing shares. Otherwise the existing share is returned as is.
+///
+/// As a side effect a recipient from outside the workspace joins it as a
+/// guest, since items are only reachable from inside their workspace. The
+/// membership stays when the share is revoked or expires; members who are
+/// already in the workspace keep their role.
+///
+/// # Errors
+///
+/// When has DB query error
+pub async fn upsert_user_share<E, C>(
+    db: &C,
+    workspace: &workspaces::Model,
+    item_id: i32,
+    grant: &ShareGrant,
+) -> ModelResult<E::Model>
+where
+    E: UserShareEntity,
+    E::Model: IntoActiveModel<E::ActiveModel>,
+    C: ConnectionTrait,
+{
+    if !grant.update_existing {
+        let existing = E::find()
+            .filter(E::item_column().eq(item_id))
+            .filter(E::shared_with_column().eq(grant.shared_with_user_id))
+            .one(db)
+            .await?;
+        if let Some(existing) = existing {
+            return Ok(existing);
+        }
+    }
+    workspace
+        .add_member(db, grant.shared_with_user_id, WorkspaceRole::Guest)
+        .await?;
+    // the state is left alone so re-sharing can not revive a declined share
+    let share = E::insert(E::new_share(item_id, grant))
+        .on_conflict(
+            OnConflict::columns([E::item_column(), E::shared_with_column()])
+                .update_columns(E::grant_columns())
+                .to_owned(),
+        )
+        .exec_with_returning(db)
+        .await?;
+    Ok(share)
+}
+
+/// Condition matching shares that have not expired yet. Shares without an
+/// `expires_at` never expire.
+#[must_use]
+pub fn not_expired_condition<E: UserShareEntity>() -> Condition {
+    Condition::any()
+        .add(E::expires_at_column().is_null())
+        .add(E::expires_at_column().gt(Local::now()))
+}
+
+/// Selects shares whose `expires_at` is in the past
+#[must_use]
+pub fn find_expired<E: UserShareEntity>() -> Select<E> {
+    E::find().filter(E::expires_at_column().lte(Local::now()))
+}
+
+/// Selects the unexpired shares of any state made with the user
+#[must_use]
+pub fn find_incoming<E: UserShareEntity>(user_id: i32) -> Select<E> {
+    E::find()
+        .filter(E::shared_with_column().eq(user_id))
+        .filter(not_expired_condition::<E>())
+}
+
+/// Why a recipient's answer to a share was refused
+#[derive(Debug)]
+pub enum TransitionError {
+    /// There is no unexpired share of the item with the user
+    NotFound,
+    /// The share can not move from its current state to the requested one
+    InvalidState,
+    Model(ModelError),
+}
+
+impl From<DbErr> for TransitionError {
+    fn from(err: DbErr) -> Self {
+        Self::Model(err.into())
+    }
+}
+
+impl From<TransitionError> for Error {
+    fn from(err: TransitionError) -> Self {
+        match err {
+            TransitionError::NotFound => Self::NotFound,
+            TransitionError::InvalidState => {
+                Self::BadRequest("share can not be moved to this state".to_string())
+            }
+            TransitionError::Model(err) => err.into(),
+        }
+    }
+}
+
+/// Moves the user's share of an item to `next`, following
+/// [`ShareState::can_transition_to`]. Expired shares are treated as gone.
+///
+/// # Errors
+///
+/// When the share does not exist, can not make the move, or has DB query
+/// error
+pub async fn transition_user_share<E, C>(
+    db: &C,
+    item_id: i32,
+    user_id: i32,
+    next: ShareState,
+) -> Result<E::Model, TransitionError>
+where
+    E: UserShareEntity,
+    E::Model: IntoActiveModel<E::ActiveModel>,
+    E::ActiveModel: ActiveModelTrait<Entity = E> + ActiveModelBehavior + Send,
+    C: ConnectionTrait,
+{
+    let share = find_incoming::<E>(user_id)
+        .filter(E::item_column().eq(item_id))
+        .one(db)
+        .await?
+        .ok_or(TransitionError::NotFound)?;
+    if !E::state(&share).can_transition_to(next) {
+        return Err(TransitionError::InvalidState);
+    }
+
+    let mut share = share.into_active_model();
+    share.set(E::state_column(), next.into());
+    Ok(share.update(db).await?)
+}
+
+/// Whether a sharer with `own` permission on an item, `None` for its owner,
+/// may change the shares others made of it
+#[must_use]
+pub fn may_update_shares(own: Option<SharePermission>) -> bool {
+    matches!(own, None | Some(SharePermission::Manage))
+}
+
+/// The resharing policy shared by notes and notebooks. Owners and managers
+/// may always share an item. Other recipients only may when the owner allows
+/// resharing, never with more than their own permission, and without
+/// changing existing shares. `own` is the sharer's permission on the item,
+/// `None` for its owner.
+///
+/// # Errors
+///
+/// The reason the share is refused
+pub fn check_reshare(
+    own: Option<SharePermission>,
+    allow_reshare: bool,
+    requested: SharePermission,
+) -> Result<(), &'static str> {
+    match own {
+        None | Some(SharePermission::Manage) => Ok(()),
+        Some(_) if !allow_reshare => Err("the owner does not allow resharing this item"),
+        Some(permission) if requested > permission => {
+            Err("can not grant more than your own permission")
+        }
+        Some(_) => Ok(()),
+    }
+}
@@ -0,0 +1,195 @@
+use loco_rs::prelude::*;
+use sea_orm::{sea_query::Expr, QueryOrder, QuerySelect, QueryTrait};
+use uuid::Uuid;
+
+pub use super::_entities::tags::{self, ActiveModel, Column, Entity, Model};
+use super::_entities::note_tags;
+
+/// Longest tag name, in characters
+pub const MAX_NAME_LENGTH: usize = 64;
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::tags::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.pid = ActiveValue::Set(Uuid::new_v4());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// Trims a tag name, `None` when nothing is left or it is too long
+#[must_use]
+pub fn normalize_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+impl super::_entities::tags::Model {
+    /// finds a tag of the user by the provided pid
+    ///
+    /// # Errors
+    ///
+    /// When could not find tag or DB query error
+    pub async fn find_by_pid<C>(db: &C, user_id: i32, pid: &str) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let parse_uuid = Uuid::parse_str(pid).map_err(|e| ModelError::Any(e.into()))?;
+        let tag = tags::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(tags::Column::Pid, parse_uuid)
+                    .eq(tags::Column::UserId, user_id)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        tag.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// finds a tag of the user by its name
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn find_by_name<C>(db: &C, user_id: i32, name: &str) -> ModelResult<Option<Self>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(tags::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(tags::Column::UserId, user_id)
+                    .eq(tags::Column::Name, name)
+                    .build(),
+            )
+            .one(db)
+            .await?)
+    }
+
+    /// The user's tags on a note, by name
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn find_for_note<C>(db: &C, user_id: i32, note_id: i32) -> ModelResult<Vec<Self>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(tags::Entity::find()
+            .filter(tags::Column::UserId.eq(user_id))
+            .filter(
+                tags::Column::Id.in_subquery(
+                    note_tags::Entity::find()
+                        .select_only()
+                        .column(note_tags::Column::TagId)
+                        .filter(note_tags::Column::NoteId.eq(note_id))
+                        .into_query(),
+                ),
+            )
+            .order_by_asc(tags::Column::Name)
+            .all(db)
+            .await?)
+    }
+
+    /// Replaces the user's tags on a note with `names`, creating tags the
+    /// user doesn't have yet. Tags other users put on the note are left
+    /// alone. `names` must already be normalized.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn set_for_note<C>(
+        db: &C,
+        user_id: i32,
+        note_id: i32,
+        names: &[String],
+    ) -> ModelResult<Vec<Self>>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let txn = db.begin().await?;
+
+        note_tags::Entity::delete_many()
+            .filter(note_tags::Column::NoteId.eq(note_id))
+            .filter(
+                note_tags::Column::TagId.in_subquery(
+                    tags::Entity::find()
+                        .select_only()
+                        .column(tags::Column::Id)
+                        .filter(tags::Column::UserId.eq(user_id))
+                        .into_query(),
+                ),
+            )
+            .exec(&txn)
+            .await?;
+
+        for name in names {
+            let tag = match Self::find_by_name(&txn, user_id, name).await? {
+                Some(tag) => tag,
+                None => {
+                    tags::ActiveModel {
+                        user_id: ActiveValue::set(user_id),
+                        name: ActiveValue::set(name.clone()),
+                        ..Default::default()
+                    }
+                    .insert(&txn)
+                    .await?
+                }
+            };
+            note_tags::ActiveModel {
+                note_id: ActiveValue::set(note_id),
+                tag_id: ActiveValue::set(tag.id),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        let tags = Self::find_for_note(&txn, user_id, note_id).await?;
+        txn.commit().await?;
+        Ok(tags)
+    }
+
+    /// Moves every note of this tag over to `target` and deletes this tag
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn merge_into<C>(self, db: &C, target: &Self) -> ModelResult<()>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let txn = db.begin().await?;
+
+        // notes that already carry the target tag keep just that one
+        note_tags::Entity::update_many()
+            .col_expr(note_tags::Column::TagId, Expr::value(target.id))
+            .filter(note_tags::Column::TagId.eq(self.id))
+            .filter(
+                note_tags::Column::NoteId.not_in_subquery(
+                    note_tags::Entity::find()
+                        .select_only()
+                        .column(note_tags::Column::NoteId)
+                        .filter(note_tags::Column::TagId.eq(target.id))
+                        .into_query(),
+                ),
+            )
+            .exec(&txn)
+            .await?;
+        self.delete(&txn).await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use loco_rs::prelude::*;
+
+pub use super::_entities::user_blocks::{self, ActiveModel, Entity, Model};
+
+impl super::_entities::user_blocks::Model {
+    /// Whether `user_id` has blocked `blocked_user_id` from sharing notes
+    /// with them
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn exists<C>(db: &C, user_id: i32, blocked_user_id: i32) -> ModelResult<bool>
+    where
+        C: ConnectionTrait,
+    {
+        let block = user_blocks::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(user_blocks::Column::UserId, user_id)
+                    .eq(user_blocks::Column::BlockedUserId, blocked_user_id)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        Ok(block.is_some())
+    }
+}
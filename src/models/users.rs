@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub use super::_entities::users::{self, ActiveModel, Entity, Model};
+use super::workspaces;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct LoginParams {
@@ -146,6 +147,19 @@ impl super::_entities::users::Model {
         user.ok_or_else(|| ModelError::EntityNotFound)
     }
 
+    /// finds a user by the provided pid, for requests naming another user
+    ///
+    /// # Errors
+    ///
+    /// `Error::NotFound` when there is no such user, or DB query error
+    pub async fn find_by_pid_or_not_found(db: &DatabaseConnection, pid: &str) -> Result<Self> {
+        match Self::find_by_pid(db, pid).await {
+            Ok(user) => Ok(user),
+            Err(ModelError::EntityNotFound) => Err(Error::NotFound),
+            Err(err) => Err(err.into()),
+        }
+    }
+
     /// finds a user by the provided api key
     ///
     /// # Errors
@@ -174,7 +188,8 @@ impl super::_entities::users::Model {
     }
 
     /// Asynchronously creates a user with a password and saves it to the
-    /// database.
+    /// database, together with their personal workspace so a user never
+    /// exists without one.
     ///
     /// # Errors
     ///
@@ -208,6 +223,7 @@ impl super::_entities::users::Model {
         }
         .insert(&txn)
         .await?;
+        workspaces::Model::create_personal(&txn, &user).await?;
 
         txn.commit().await?;
 
@@ -222,6 +238,25 @@ impl super::_entities::users::Model {
     pub fn generate_jwt(&self, secret: &str, expiration: &u64) -> ModelResult<String> {
         Ok(jwt::JWT::new(secret).generate_token(expiration, self.pid.to_string(), None)?)
     }
+
+    /// Creates a JWT that selects the given workspace for the requests made
+    /// with it
+    ///
+    /// # Errors
+    ///
+    /// when could not convert user claims to jwt token
+    pub fn generate_jwt_for_workspace(
+        &self,
+        secret: &str,
+        expiration: &u64,
+        workspace_pid: &str,
+    ) -> ModelResult<String> {
+        Ok(jwt::JWT::new(secret).generate_token(
+            expiration,
+            self.pid.to_string(),
+            Some(serde_json::json!({ "workspace": workspace_pid })),
+        )?)
+    }
 }
 
 impl super::_entities::users::ActiveModel {
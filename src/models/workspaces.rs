@@ -0,0 +1,137 @@
+use loco_rs::prelude::*;
+use sea_orm::QueryOrder;
+use uuid::Uuid;
+
+pub use super::_entities::workspaces::{self, ActiveModel, Entity, Model};
+use super::_entities::{sea_orm_active_enums::WorkspaceRole, users, workspace_members};
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for super::_entities::workspaces::ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            let mut this = self;
+            this.pid = ActiveValue::Set(Uuid::new_v4());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl super::_entities::workspaces::Model {
+    /// finds a workspace by the provided pid
+    ///
+    /// # Errors
+    ///
+    /// When could not find workspace or DB query error
+    pub async fn find_by_pid<C>(db: &C, pid: &str) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let parse_uuid = Uuid::parse_str(pid).map_err(|e| ModelError::Any(e.into()))?;
+        let workspace = workspaces::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(workspaces::Column::Pid, parse_uuid)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        workspace.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// The workspace a user works in when they did not pick one: the first
+    /// one they joined, which is their personal workspace
+    ///
+    /// # Errors
+    ///
+    /// When could not find a workspace or DB query error
+    pub async fn find_default_for_user<C>(db: &C, user_id: i32) -> ModelResult<(Self, WorkspaceRole)>
+    where
+        C: ConnectionTrait,
+    {
+        let membership = workspace_members::Entity::find()
+            .filter(workspace_members::Column::UserId.eq(user_id))
+            .order_by_asc(workspace_members::Column::Id)
+            .find_also_related(workspaces::Entity)
+            .one(db)
+            .await?;
+        match membership {
+            Some((member, Some(workspace))) => Ok((workspace, member.role)),
+            _ => Err(ModelError::EntityNotFound),
+        }
+    }
+
+    /// Creates the personal workspace every user starts out with
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn create_personal<C>(db: &C, user: &users::Model) -> ModelResult<Self>
+    where
+        C: ConnectionTrait,
+    {
+        let workspace = workspaces::ActiveModel {
+            name: ActiveValue::set(format!("{}'s workspace", user.name)),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        workspace.add_member(db, user.id, WorkspaceRole::Owner).await?;
+        Ok(workspace)
+    }
+
+    /// The role of the user in the workspace, `None` when they are not a
+    /// member
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn role_of<C>(&self, db: &C, user_id: i32) -> ModelResult<Option<WorkspaceRole>>
+    where
+        C: ConnectionTrait,
+    {
+        let member = workspace_members::Entity::find()
+            .filter(
+                model::query::condition()
+                    .eq(workspace_members::Column::WorkspaceId, self.id)
+                    .eq(workspace_members::Column::UserId, user_id)
+                    .build(),
+            )
+            .one(db)
+            .await?;
+        Ok(member.map(|member| member.role))
+    }
+
+    /// Adds the user to the workspace with `role`. Existing members keep
+    /// their role, which is returned.
+    ///
+    /// # Errors
+    ///
+    /// When has DB query error
+    pub async fn add_member<C>(
+        &self,
+        db: &C,
+        user_id: i32,
+        role: WorkspaceRole,
+    ) -> ModelResult<WorkspaceRole>
+    where
+        C: ConnectionTrait,
+    {
+        if let Some(existing) = self.role_of(db, user_id).await? {
+            return Ok(existing);
+        }
+        workspace_members::ActiveModel {
+            workspace_id: ActiveValue::set(self.id),
+            user_id: ActiveValue::set(user_id),
+            role: ActiveValue::set(role),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        Ok(role)
+    }
+}
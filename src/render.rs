@@ -0,0 +1,163 @@
+//! Turns note content into HTML that is safe to show in a browser, so every
+//! client renders notes the same way.
+//!
+//! Markdown is rendered with tables and strikethrough, then sanitized: raw
+//! HTML is reduced to a safe subset, links may only use `http`, `https` and
+//! `mailto` and get `rel="noopener noreferrer nofollow"`, and code blocks keep
+//! a `language-*` class for syntax highlighters. Headings get ids, all
+//! starting with `note-`, that the table of contents links to.
+use std::{ borrow::Cow, collections::HashSet };
+
+use pulldown_cmark::{ html, CowStr, Event, Options, Parser, Tag, TagEnd };
+use serde::{ Deserialize, Serialize };
+
+use crate::models::_entities::sea_orm_active_enums::NoteFormat;
+
+/// Prefixes the id of every element in the output, so notes can not clash
+/// with ids of the page they are shown in
+const ID_PREFIX: &str = "note-";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TocEntry {
+    /// 1 to 6, as in `<h1>` to `<h6>`
+    pub level: u8,
+    pub text: String,
+    /// The heading's `id`, link to it with `#<id>`
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Rendered {
+    pub html: String,
+    /// The headings in document order, empty for plain notes
+    pub toc: Vec<TocEntry>,
+}
+
+/// Renders `content` written in `format` to sanitized HTML
+#[must_use]
+pub fn render(format: NoteFormat, content: &str) -> Rendered {
+    let (unsafe_html, toc) = match format {
+        NoteFormat::Plain => (plain_html(content), Vec::new()),
+        NoteFormat::Markdown => markdown_html(content),
+    };
+    Rendered { html: sanitize(&unsafe_html), toc }
+}
+
+/// Every blank line separated block becomes a paragraph, line breaks are kept
+fn plain_html(content: &str) -> String {
+    let mut events = Vec::new();
+    for paragraph in content.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        events.push(Event::Start(Tag::Paragraph));
+        for (index, line) in paragraph.lines().enumerate() {
+            if index > 0 {
+                events.push(Event::HardBreak);
+            }
+            events.push(Event::Text(CowStr::from(line)));
+        }
+        events.push(Event::End(TagEnd::Paragraph));
+    }
+
+    let mut output = String::new();
+    html::push_html(&mut output, events.into_iter());
+    output
+}
+
+fn markdown_html(content: &str) -> (String, Vec<TocEntry>) {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut events: Vec<Event> = Parser::new_ext(content, options).collect();
+
+    let mut toc = Vec::new();
+    let mut used_ids = HashSet::new();
+    let mut index = 0;
+    while index < events.len() {
+        let Event::Start(Tag::Heading { level, .. }) = &events[index] else {
+            index += 1;
+            continue;
+        };
+        let level = *level as u8;
+        let text = heading_text(&events[index + 1..]);
+        let id = unique_id(&mut used_ids, &slug(&text));
+        if let Event::Start(Tag::Heading { id: heading_id, .. }) = &mut events[index] {
+            *heading_id = Some(CowStr::from(id.clone()));
+        }
+        toc.push(TocEntry { level, text, id: format!("{ID_PREFIX}{id}") });
+        index += 1;
+    }
+
+    let mut output = String::new();
+    html::push_html(&mut output, events.into_iter());
+    (output, toc)
+}
+
+/// The text of the heading whose content starts at `events`
+fn heading_text(events: &[Event]) -> String {
+    let mut text = String::new();
+    for event in events {
+        match event {
+            Event::End(TagEnd::Heading(_)) => break,
+            Event::Text(value) | Event::Code(value) => text.push_str(value),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            _ => {}
+        }
+    }
+    text.trim().to_string()
+}
+
+/// Lowercase letters and digits, words joined by `-`
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() { "section".to_string() } else { slug.to_string() }
+}
+
+/// Repeated headings get `-1`, `-2`, ... appended
+fn unique_id(used: &mut HashSet<String>, slug: &str) -> String {
+    let mut id = slug.to_string();
+    let mut count = 0;
+    while used.contains(&id) {
+        count += 1;
+        id = format!("{slug}-{count}");
+    }
+    used.insert(id.clone());
+    id
+}
+
+fn sanitize(unsafe_html: &str) -> String {
+    ammonia::Builder::default()
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("h1", &["id"])
+        .add_tag_attributes("h2", &["id"])
+        .add_tag_attributes("h3", &["id"])
+        .add_tag_attributes("h4", &["id"])
+        .add_tag_attributes("h5", &["id"])
+        .add_tag_attributes("h6", &["id"])
+        .id_prefix(Some(ID_PREFIX))
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("code", "class") => code_class(value).map(Cow::Owned),
+            _ => Some(Cow::Borrowed(value)),
+        })
+        .clean(unsafe_html)
+        .to_string()
+}
+
+/// Keeps only a `language-*` class of a code block
+fn code_class(value: &str) -> Option<String> {
+    value
+        .split_whitespace()
+        .find(|class| {
+            class.strip_prefix("language-").is_some_and(|language| {
+                !language.is_empty() &&
+                    language.chars().all(|c| c.is_ascii_alphanumeric() || "+#-_.".contains(c))
+            })
+        })
+        .map(ToString::to_string)
+}
@@ -0,0 +1,115 @@
+//! Where note attachments are stored. The backend is picked by
+//! `settings.storage` in the config:
+//!
+//! ```yaml
+//! settings:
+//!   storage:
+//!     driver: s3
+//!     bucket: attachments
+//!     region: us-east-1
+//!     # for S3 compatible services such as MinIO
+//!     endpoint: http://localhost:9000
+//!     allow_http: true
+//!     access_key_id: ...
+//!     secret_access_key: ...
+//! ```
+use std::path::PathBuf;
+
+use loco_rs::{
+    config::Config,
+    storage::{
+        drivers::{self, object_store_adapter::ObjectStoreAdapter, StoreDriver},
+        Storage,
+    },
+    Error, Result,
+};
+use object_store::aws::AmazonS3Builder;
+use serde::Deserialize;
+
+/// The key `Storage::single` registers its driver under
+pub const STORE: &str = "store";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "driver", rename_all = "snake_case")]
+pub enum StorageSettings {
+    /// Files in a directory on this machine
+    Local { path: PathBuf },
+    /// An S3 bucket, or a bucket of any S3 compatible service
+    S3(S3Settings),
+    /// Kept in memory and lost on restart, for tests
+    Memory,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct S3Settings {
+    pub bucket: String,
+    pub region: String,
+    /// Only needed for services other than AWS
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub allow_http: bool,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self::Local {
+            path: PathBuf::from("storage"),
+        }
+    }
+}
+
+impl StorageSettings {
+    /// Reads `settings.storage`, local files in `storage/` when it is missing
+    ///
+    /// # Errors
+    ///
+    /// When the settings are malformed
+    pub fn from_config(config: &Config) -> Result<Self> {
+        match config.settings.as_ref().and_then(|settings| settings.get("storage")) {
+            Some(storage) => Ok(serde_json::from_value(storage.clone())?),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// When the backend can not be set up
+    pub fn driver(&self) -> Result<Box<dyn StoreDriver>> {
+        match self {
+            Self::Local { path } => {
+                std::fs::create_dir_all(path)?;
+                drivers::local::new_with_prefix(path)
+            }
+            Self::S3(settings) => settings.driver(),
+            Self::Memory => Ok(drivers::mem::new()),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// When the backend can not be set up
+    pub fn storage(&self) -> Result<Storage> {
+        Ok(Storage::single(self.driver()?))
+    }
+}
+
+impl S3Settings {
+    /// # Errors
+    ///
+    /// When the settings are incomplete
+    pub fn driver(&self) -> Result<Box<dyn StoreDriver>> {
+        let mut builder = AmazonS3Builder::new()
+            .with_bucket_name(&self.bucket)
+            .with_region(&self.region)
+            .with_access_key_id(&self.access_key_id)
+            .with_secret_access_key(&self.secret_access_key)
+            .with_allow_http(self.allow_http);
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        let s3 = builder.build().map_err(|err| Error::Any(err.into()))?;
+        Ok(Box::new(ObjectStoreAdapter::new(Box::new(s3))))
+    }
+}
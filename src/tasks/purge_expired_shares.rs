@@ -0,0 +1,81 @@
+//! This task removes note and notebook shares whose `expires_at` has passed
+//! and lets the owners know by email.
+//!
+//! Expired shares are already ignored when notes and notebooks are listed or
+//! loaded, so the task only keeps the share tables tidy. Expired group shares
+//! are deleted as well, without an email. Schedule it with cron (or any
+//! other scheduler), e.g. once an hour.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo loco task purge_expired_shares
+//! ```
+
+use loco_rs::prelude::*;
+
+use crate::{
+    mailers::shares::ShareMailer,
+    models::{ note_group_shares, note_shares, notebook_shares, notebooks, notes, users },
+};
+
+#[allow(clippy::module_name_repetitions)]
+pub struct PurgeExpiredShares;
+#[async_trait]
+impl Task for PurgeExpiredShares {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "purge_expired_shares".to_string(),
+            detail: "Delete expired note and notebook shares and notify the owners".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, _vars: &task::Vars) -> Result<()> {
+        let expired = note_shares::Entity::find_expired()
+            .find_also_related(notes::Entity)
+            .all(&app_context.db)
+            .await?;
+
+        for (share, note) in expired {
+            share.clone().delete(&app_context.db).await?;
+
+            let Some(note) = note else {
+                continue;
+            };
+            let owner = users::Entity::find_by_id(note.user_id).one(&app_context.db).await?;
+            let shared_with = users::Entity::find_by_id(share.shared_with_user_id)
+                .one(&app_context.db)
+                .await?;
+            if let (Some(owner), Some(shared_with)) = (owner, shared_with) {
+                ShareMailer::share_expired(app_context, &owner, &note, &shared_with, &share).await?;
+            }
+        }
+
+        let expired = notebook_shares::Entity::find_expired()
+            .find_also_related(notebooks::Entity)
+            .all(&app_context.db)
+            .await?;
+
+        for (share, notebook) in expired {
+            share.clone().delete(&app_context.db).await?;
+
+            let Some(notebook) = notebook else {
+                continue;
+            };
+            let owner = users::Entity::find_by_id(notebook.user_id).one(&app_context.db).await?;
+            let shared_with = users::Entity::find_by_id(share.shared_with_user_id)
+                .one(&app_context.db)
+                .await?;
+            if let (Some(owner), Some(shared_with)) = (owner, shared_with) {
+                ShareMailer::notebook_share_expired(app_context, &owner, &notebook, &shared_with, &share)
+                    .await?;
+            }
+        }
+
+        for share in note_group_shares::Entity::find_expired().all(&app_context.db).await? {
+            share.delete(&app_context.db).await?;
+        }
+
+        Ok(())
+    }
+}
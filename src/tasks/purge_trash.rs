@@ -0,0 +1,72 @@
+//! This task deletes notes that have been in the trash for longer than the
+//! retention period, together with their shares, revisions and attachments.
+//!
+//! The retention defaults to 30 days and can be changed with
+//! `retention_days`. Schedule it with cron (or any other scheduler), e.g. once
+//! a day.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo loco task purge_trash retention_days:30
+//! ```
+
+use loco_rs::prelude::*;
+
+use sea_orm::QuerySelect;
+
+use crate::models::{attachments, notes};
+
+/// How long notes stay in the trash when `retention_days` is not given
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+#[allow(clippy::module_name_repetitions)]
+pub struct PurgeTrash;
+#[async_trait]
+impl Task for PurgeTrash {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "purge_trash".to_string(),
+            detail: "Delete notes that have been in the trash longer than the retention period"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let retention_days = match vars.cli_arg("retention_days") {
+            Ok(value) => value
+                .parse::<i64>()
+                .ok()
+                .filter(|days| *days >= 0)
+                .ok_or_else(|| Error::string("`retention_days` must be a non-negative number"))?,
+            Err(_) => DEFAULT_RETENTION_DAYS,
+        };
+        let cutoff = chrono::Local::now() - chrono::Duration::days(retention_days);
+
+        let expired: Vec<i32> = notes::Entity::find()
+            .select_only()
+            .column(notes::Column::Id)
+            .filter(notes::Column::DeletedAt.lt(cutoff))
+            .into_tuple()
+            .all(&app_context.db)
+            .await?;
+        attachments::Model::delete_files_for_notes(
+            &app_context.db,
+            &app_context.storage,
+            expired.clone(),
+        )
+        .await?;
+
+        let purged = notes::Entity::delete_many()
+            .filter(notes::Column::Id.is_in(expired))
+            .exec(&app_context.db)
+            .await?;
+
+        tracing::info!(
+            count = purged.rows_affected,
+            retention_days,
+            "purged notes from the trash"
+        );
+        Ok(())
+    }
+}
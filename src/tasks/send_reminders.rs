@@ -0,0 +1,52 @@
+//! This task claims every reminder that has come due, marking it sent and
+//! moving recurring reminders on to their next occurrence, and enqueues a
+//! `ReminderWorker` job that sends the email. Claiming is atomic, so runs
+//! that overlap send each reminder once.
+//!
+//! Schedule it with cron (or any other scheduler), e.g. once a minute.
+//! Reminders go out at most one run late.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo loco task send_reminders
+//! ```
+
+use loco_rs::prelude::*;
+
+use crate::{
+    models::reminders,
+    workers::reminders::{ReminderWorker, ReminderWorkerArgs},
+};
+
+#[allow(clippy::module_name_repetitions)]
+pub struct SendReminders;
+#[async_trait]
+impl Task for SendReminders {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "send_reminders".to_string(),
+            detail: "Send the emails of reminders that have come due".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, _vars: &task::Vars) -> Result<()> {
+        let due =
+            reminders::Entity::claim_due(&app_context.db, chrono::Local::now().into()).await?;
+
+        for reminder in &due {
+            ReminderWorker::perform_later(
+                app_context,
+                ReminderWorkerArgs {
+                    reminder_id: reminder.id,
+                    due_at: reminder.due_at,
+                },
+            )
+            .await
+            .map_err(Error::wrap)?;
+        }
+
+        tracing::info!(count = due.len(), "claimed and enqueued due reminders");
+        Ok(())
+    }
+}
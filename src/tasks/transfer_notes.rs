@@ -0,0 +1,60 @@
+//! This task hands every note of one user over to another, e.g. when someone
+//! leaves the team and their account is about to be deleted.
+//!
+//! Shares of the transferred notes are kept, and the new owner joins the
+//! workspaces of the notes if they are not a member yet. Pass
+//! `keep_access:true` to keep the previous owner on every note as an editor.
+//!
+//! # Example
+//!
+//! ```sh
+//! cargo loco task transfer_notes from:<user pid> to:<user pid> keep_access:true
+//! ```
+
+use loco_rs::prelude::*;
+
+use crate::models::{
+    _entities::{ notes, sea_orm_active_enums::WorkspaceRole },
+    users,
+    workspaces,
+};
+
+#[allow(clippy::module_name_repetitions)]
+pub struct TransferNotes;
+#[async_trait]
+impl Task for TransferNotes {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "transfer_notes".to_string(),
+            detail: "Transfer all notes of one user to another".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let from = users::Model::find_by_pid(&app_context.db, vars.cli_arg("from")?).await?;
+        let to = users::Model::find_by_pid(&app_context.db, vars.cli_arg("to")?).await?;
+        if from.id == to.id {
+            return Err(Error::string("`from` and `to` must be different users"));
+        }
+        let keep_access = vars.cli_arg("keep_access").is_ok_and(|value| value == "true");
+
+        let owned = notes::Entity::find()
+            .filter(notes::Column::UserId.eq(from.id))
+            .all(&app_context.db)
+            .await?;
+
+        let txn = app_context.db.begin().await?;
+        let count = owned.len();
+        for note in owned {
+            // the new owner has to be able to work in the note's workspace
+            if let Some(workspace) = workspaces::Entity::find_by_id(note.workspace_id).one(&txn).await? {
+                workspace.add_member(&txn, to.id, WorkspaceRole::Member).await?;
+            }
+            note.transfer_to(&txn, to.id, keep_access).await?;
+        }
+        txn.commit().await?;
+
+        tracing::info!(count, from = from.pid.to_string(), to = to.pid.to_string(), "transferred notes");
+        Ok(())
+    }
+}
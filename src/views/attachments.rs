@@ -0,0 +1,28 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::attachments;
+
+/// A file attached to a note, download it from
+/// `/api/notes/:id/attachments/:pid`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AttachmentResponse {
+    pub pid: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl AttachmentResponse {
+    #[must_use]
+    pub fn new(attachment: &attachments::Model) -> Self {
+        Self {
+            pid: attachment.pid.to_string(),
+            file_name: attachment.file_name.clone(),
+            content_type: attachment.content_type.clone(),
+            size: attachment.size,
+            created_at: attachment.created_at,
+        }
+    }
+}
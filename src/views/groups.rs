@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::{group_members, groups, sea_orm_active_enums::ShareState, users};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GroupMemberResponse {
+    pub pid: String,
+    pub name: String,
+    /// `pending` until the user accepts the invitation to the group
+    pub state: ShareState,
+}
+
+impl GroupMemberResponse {
+    #[must_use]
+    pub fn new(member: &group_members::Model, user: &users::Model) -> Self {
+        Self {
+            pid: user.pid.to_string(),
+            name: user.name.clone(),
+            state: member.state,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GroupResponse {
+    pub pid: String,
+    pub name: String,
+    pub owner_pid: String,
+    pub members: Vec<GroupMemberResponse>,
+}
+
+impl GroupResponse {
+    #[must_use]
+    pub fn new(
+        group: &groups::Model,
+        owner: &users::Model,
+        members: &[(group_members::Model, users::Model)],
+    ) -> Self {
+        Self {
+            pid: group.pid.to_string(),
+            name: group.name.clone(),
+            owner_pid: owner.pid.to_string(),
+            members: members
+                .iter()
+                .map(|(member, user)| GroupMemberResponse::new(member, user))
+                .collect(),
+        }
+    }
+}
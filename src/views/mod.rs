@@ -1,2 +1,10 @@
+pub mod attachments;
 pub mod auth;
+pub mod groups;
+pub mod note_items;
+pub mod notebooks;
+pub mod notes;
+pub mod reminders;
+pub mod tags;
 pub mod user;
+pub mod workspaces;
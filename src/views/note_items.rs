@@ -0,0 +1,40 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::{note_items, users};
+
+/// Who checked an item
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CheckedByResponse {
+    pub pid: String,
+    pub name: String,
+}
+
+/// One entry of a note's checklist
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteItemResponse {
+    pub pid: String,
+    pub text: String,
+    pub checked: bool,
+    pub position: i32,
+    /// `null` when unchecked or the user was deleted
+    pub checked_by: Option<CheckedByResponse>,
+    pub checked_at: Option<DateTimeWithTimeZone>,
+}
+
+impl NoteItemResponse {
+    #[must_use]
+    pub fn new(item: &note_items::Model, checked_by: Option<&users::Model>) -> Self {
+        Self {
+            pid: item.pid.to_string(),
+            text: item.text.clone(),
+            checked: item.checked,
+            position: item.position,
+            checked_by: checked_by.map(|user| CheckedByResponse {
+                pid: user.pid.to_string(),
+                name: user.name.clone(),
+            }),
+            checked_at: item.checked_at,
+        }
+    }
+}
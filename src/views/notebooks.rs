@@ -0,0 +1,91 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::_entities::{
+    notebook_shares, notebooks,
+    sea_orm_active_enums::{SharePermission, ShareState},
+    users,
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NotebookResponse {
+    pub pid: String,
+    pub name: String,
+    /// `None` for top level notebooks
+    pub parent_pid: Option<String>,
+    pub allow_reshare: bool,
+}
+
+impl NotebookResponse {
+    #[must_use]
+    pub fn new(notebook: &notebooks::Model, parent_pid: Option<&Uuid>) -> Self {
+        Self {
+            pid: notebook.pid.to_string(),
+            name: notebook.name.clone(),
+            parent_pid: parent_pid.map(ToString::to_string),
+            allow_reshare: notebook.allow_reshare,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NotebookShareResponse {
+    pub notebook_pid: String,
+    pub user_pid: String,
+    pub name: String,
+    pub email: String,
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub state: ShareState,
+}
+
+impl NotebookShareResponse {
+    #[must_use]
+    pub fn new(
+        share: &notebook_shares::Model,
+        notebook: &notebooks::Model,
+        user: &users::Model,
+    ) -> Self {
+        Self {
+            notebook_pid: notebook.pid.to_string(),
+            user_pid: user.pid.to_string(),
+            name: user.name.clone(),
+            email: user.email.clone(),
+            permission: share.permission,
+            expires_at: share.expires_at,
+            state: share.state,
+        }
+    }
+}
+
+/// A notebook share as its recipient sees it
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IncomingNotebookShareResponse {
+    pub notebook_pid: String,
+    pub name: String,
+    pub owner_pid: String,
+    pub owner_name: String,
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub state: ShareState,
+}
+
+impl IncomingNotebookShareResponse {
+    #[must_use]
+    pub fn new(
+        share: &notebook_shares::Model,
+        notebook: &notebooks::Model,
+        owner: &users::Model,
+    ) -> Self {
+        Self {
+            notebook_pid: notebook.pid.to_string(),
+            name: notebook.name.clone(),
+            owner_pid: owner.pid.to_string(),
+            owner_name: owner.name.clone(),
+            permission: share.permission,
+            expires_at: share.expires_at,
+            state: share.state,
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+
+use crate::render::{self, TocEntry};
+use crate::models::notes::{NotePage, SearchHit, SNIPPET_MATCH_END, SNIPPET_MATCH_START};
+use crate::models::_entities::{
+    groups, note_group_shares, note_invitations, note_links, note_references, note_revisions,
+    note_shares, notes,
+    sea_orm_active_enums::{NoteFormat, SharePermission, ShareState},
+    users,
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteShareResponse {
+    pub note_id: i32,
+    pub user_pid: String,
+    pub name: String,
+    pub email: String,
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub state: ShareState,
+}
+
+impl NoteShareResponse {
+    #[must_use]
+    pub fn new(share: &note_shares::Model, user: &users::Model) -> Self {
+        Self {
+            note_id: share.note_id,
+            user_pid: user.pid.to_string(),
+            name: user.name.clone(),
+            email: user.email.clone(),
+            permission: share.permission,
+            expires_at: share.expires_at,
+            state: share.state,
+        }
+    }
+}
+
+/// A share waiting for the recipient to accept or decline it
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IncomingShareResponse {
+    pub note_id: i32,
+    pub title: Option<String>,
+    pub owner_pid: String,
+    pub owner_name: String,
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub state: ShareState,
+}
+
+impl IncomingShareResponse {
+    #[must_use]
+    pub fn new(share: &note_shares::Model, note: &notes::Model, owner: &users::Model) -> Self {
+        Self {
+            note_id: note.id,
+            title: note.title.clone(),
+            owner_pid: owner.pid.to_string(),
+            owner_name: owner.name.clone(),
+            permission: share.permission,
+            expires_at: share.expires_at,
+            state: share.state,
+        }
+    }
+}
+
+/// A share with every member of a group
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteGroupShareResponse {
+    pub note_id: i32,
+    pub group_pid: String,
+    pub group_name: String,
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+}
+
+impl NoteGroupShareResponse {
+    #[must_use]
+    pub fn new(share: &note_group_shares::Model, group: &groups::Model) -> Self {
+        Self {
+            note_id: share.note_id,
+            group_pid: group.pid.to_string(),
+            group_name: group.name.clone(),
+            permission: share.permission,
+            expires_at: share.expires_at,
+        }
+    }
+}
+
+/// A share with someone who has no account yet. The invitation token is
+/// only sent to the invitee and never returned to the sharer.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteInvitationResponse {
+    pub note_id: i32,
+    pub email: String,
+    pub permission: SharePermission,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+}
+
+impl NoteInvitationResponse {
+    #[must_use]
+    pub fn new(invitation: &note_invitations::Model) -> Self {
+        Self {
+            note_id: invitation.note_id,
+            email: invitation.email.clone(),
+            permission: invitation.permission,
+            expires_at: invitation.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteLinkResponse {
+    pub note_id: i32,
+    pub token: String,
+    pub url: String,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub password_protected: bool,
+}
+
+impl NoteLinkResponse {
+    #[must_use]
+    pub fn new(link: &note_links::Model, base_url: &str) -> Self {
+        Self {
+            note_id: link.note_id,
+            token: link.token.clone(),
+            url: format!("{base_url}/api/public/notes/{}", link.token),
+            expires_at: link.expires_at,
+            password_protected: link.password.is_some(),
+        }
+    }
+}
+
+/// What anonymous visitors of a public link get to see. Owner and ids are
+/// left out on purpose.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PublicNoteResponse {
+    pub title: Option<String>,
+    pub content: Option<String>,
+    /// How `content` is written, for clients that render it themselves
+    pub format: NoteFormat,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+impl PublicNoteResponse {
+    #[must_use]
+    pub fn new(note: &notes::Model) -> Self {
+        Self {
+            title: note.title.clone(),
+            content: note.content.clone(),
+            format: note.format,
+            updated_at: note.updated_at,
+        }
+    }
+
+    /// Renders the note as a standalone read-only HTML page, the content
+    /// the same way as `NoteHtmlResponse`
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let title = escape_html(self.title.as_deref().unwrap_or_default());
+        let rendered = render::render(self.format, self.content.as_deref().unwrap_or_default());
+        let content = rendered.html.trim_end();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n  <meta charset=\"utf-8\">\n  <title>{title}</title>\n</head>\n\n<body>\n  <h1>{title}</h1>\n  {content}\n</body>\n\n</html>\n"
+        )
+    }
+}
+
+/// A saved version of a note's title and content
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteRevisionResponse {
+    pub note_id: i32,
+    pub revision: i32,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub format: NoteFormat,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl NoteRevisionResponse {
+    #[must_use]
+    pub fn new(revision: &note_revisions::Model) -> Self {
+        Self {
+            note_id: revision.note_id,
+            revision: revision.revision,
+            title: revision.title.clone(),
+            content: revision.content.clone(),
+            format: revision.format,
+            created_at: revision.created_at,
+        }
+    }
+}
+
+/// Unified diff between two revisions of a note
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteRevisionDiffResponse {
+    pub note_id: i32,
+    pub from: i32,
+    pub to: i32,
+    pub diff: String,
+}
+
+impl NoteRevisionDiffResponse {
+    #[must_use]
+    pub fn new(from: &note_revisions::Model, to: &note_revisions::Model) -> Self {
+        let (old, new) = (from.document(), to.document());
+        let diff = similar::TextDiff::from_lines(&old, &new)
+            .unified_diff()
+            .header(&format!("revision {}", from.revision), &format!("revision {}", to.revision))
+            .to_string();
+
+        Self {
+            note_id: to.note_id,
+            from: from.revision,
+            to: to.revision,
+            diff,
+        }
+    }
+}
+
+/// One page of a note listing
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NotePageResponse<T> {
+    pub items: Vec<T>,
+    /// Pass as `after` to get the next page, `null` on the last page
+    pub next_cursor: Option<String>,
+    /// How many notes the listing has over all pages
+    pub total: u64,
+}
+
+impl<T> NotePageResponse<T> {
+    #[must_use]
+    pub fn new(items: Vec<T>, page: &NotePage) -> Self {
+        Self {
+            items,
+            next_cursor: page.next_cursor.clone(),
+            total: page.total,
+        }
+    }
+}
+
+impl From<NotePage> for NotePageResponse<notes::Model> {
+    fn from(page: NotePage) -> Self {
+        Self {
+            items: page.items,
+            next_cursor: page.next_cursor,
+            total: page.total,
+        }
+    }
+}
+
+/// A note at one end of a wiki link
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LinkedNoteResponse {
+    pub id: i32,
+    pub title: Option<String>,
+}
+
+impl LinkedNoteResponse {
+    #[must_use]
+    pub fn new(note: &notes::Model) -> Self {
+        Self {
+            id: note.id,
+            title: note.title.clone(),
+        }
+    }
+}
+
+/// `source` links to `target` with `[[...]]`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteGraphEdgeResponse {
+    pub source: i32,
+    pub target: i32,
+}
+
+/// The wiki links between the notes the caller can see
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteGraphResponse {
+    pub nodes: Vec<LinkedNoteResponse>,
+    pub edges: Vec<NoteGraphEdgeResponse>,
+}
+
+impl NoteGraphResponse {
+    #[must_use]
+    pub fn new(notes: &[notes::Model], references: &[note_references::Model]) -> Self {
+        Self {
+            nodes: notes.iter().map(LinkedNoteResponse::new).collect(),
+            edges: references
+                .iter()
+                .map(|reference| NoteGraphEdgeResponse {
+                    source: reference.source_note_id,
+                    target: reference.target_note_id,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A note with its content rendered to sanitized HTML, see `crate::render`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteHtmlResponse {
+    #[serde(flatten)]
+    pub note: notes::Model,
+    pub html: String,
+    pub toc: Vec<TocEntry>,
+}
+
+impl NoteHtmlResponse {
+    #[must_use]
+    pub fn new(note: notes::Model) -> Self {
+        let rendered = render::render(note.format, note.content.as_deref().unwrap_or_default());
+        Self {
+            note,
+            html: rendered.html,
+            toc: rendered.toc,
+        }
+    }
+}
+
+/// A note matching a search, with the matched words of the snippet wrapped
+/// in `<mark>`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NoteSearchResultResponse {
+    pub id: i32,
+    pub title: Option<String>,
+    /// HTML; everything but the `<mark>` tags is escaped
+    pub snippet: String,
+    pub rank: f64,
+}
+
+impl NoteSearchResultResponse {
+    #[must_use]
+    pub fn new(hit: &SearchHit) -> Self {
+        let snippet = escape_html(hit.snippet.as_deref().unwrap_or_default())
+            .replace(SNIPPET_MATCH_START, "<mark>")
+            .replace(SNIPPET_MATCH_END, "</mark>");
+        Self {
+            id: hit.id,
+            title: hit.title.clone(),
+            snippet,
+            rank: hit.search_rank,
+        }
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
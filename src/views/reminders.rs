@@ -0,0 +1,30 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::reminders;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReminderResponse {
+    pub pid: String,
+    pub note_id: i32,
+    /// The occurrence the next email is about
+    pub due_at: DateTimeWithTimeZone,
+    /// When the next email goes out, later than `due_at` when snoozed
+    pub remind_at: DateTimeWithTimeZone,
+    pub recurrence: Option<String>,
+    pub sent_at: Option<DateTimeWithTimeZone>,
+}
+
+impl ReminderResponse {
+    #[must_use]
+    pub fn new(reminder: &reminders::Model) -> Self {
+        Self {
+            pid: reminder.pid.to_string(),
+            note_id: reminder.note_id,
+            due_at: reminder.due_at,
+            remind_at: reminder.remind_at,
+            recurrence: reminder.recurrence.clone(),
+            sent_at: reminder.sent_at,
+        }
+    }
+}
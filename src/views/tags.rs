@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::tags;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TagResponse {
+    pub pid: String,
+    pub name: String,
+}
+
+impl TagResponse {
+    #[must_use]
+    pub fn new(tag: &tags::Model) -> Self {
+        Self {
+            pid: tag.pid.to_string(),
+            name: tag.name.clone(),
+        }
+    }
+}
+
+/// A tag in the caller's tag list, with how many notes of the active
+/// workspace carry it
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TagSummaryResponse {
+    pub pid: String,
+    pub name: String,
+    pub note_count: i64,
+}
+
+impl TagSummaryResponse {
+    #[must_use]
+    pub fn new(tag: &tags::Model, note_count: i64) -> Self {
+        Self {
+            pid: tag.pid.to_string(),
+            name: tag.name.clone(),
+            note_count,
+        }
+    }
+}
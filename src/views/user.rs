@@ -19,3 +19,19 @@ impl CurrentResponse {
         }
     }
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BlockedUserResponse {
+    pub pid: String,
+    pub name: String,
+}
+
+impl BlockedUserResponse {
+    #[must_use]
+    pub fn new(user: &users::Model) -> Self {
+        Self {
+            pid: user.pid.to_string(),
+            name: user.name.clone(),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::{sea_orm_active_enums::WorkspaceRole, users, workspaces};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WorkspaceResponse {
+    pub pid: String,
+    pub name: String,
+    pub role: WorkspaceRole,
+}
+
+impl WorkspaceResponse {
+    #[must_use]
+    pub fn new(workspace: &workspaces::Model, role: WorkspaceRole) -> Self {
+        Self {
+            pid: workspace.pid.to_string(),
+            name: workspace.name.clone(),
+            role,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WorkspaceMemberResponse {
+    pub pid: String,
+    pub name: String,
+    pub role: WorkspaceRole,
+}
+
+impl WorkspaceMemberResponse {
+    #[must_use]
+    pub fn new(user: &users::Model, role: WorkspaceRole) -> Self {
+        Self {
+            pid: user.pid.to_string(),
+            name: user.name.clone(),
+            role,
+        }
+    }
+}
+
+/// A token that selects the workspace the user switched to
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SwitchWorkspaceResponse {
+    pub token: String,
+    pub workspace: WorkspaceResponse,
+}
@@ -0,0 +1,65 @@
+use loco_rs::prelude::*;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    mailers::reminders::ReminderMailer,
+    models::{
+        notes::{self, visible_to},
+        reminders, users,
+    },
+};
+
+/// Sends the email of one due reminder, enqueued by the `send_reminders`
+/// task once it has claimed the reminder
+pub struct ReminderWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReminderWorkerArgs {
+    pub reminder_id: i32,
+    /// The occurrence that came due; recurring reminders have already moved
+    /// on to the next one
+    pub due_at: DateTimeWithTimeZone,
+}
+
+impl worker::AppWorker<ReminderWorkerArgs> for ReminderWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+}
+
+#[async_trait]
+impl worker::Worker<ReminderWorkerArgs> for ReminderWorker {
+    async fn perform(&self, args: ReminderWorkerArgs) -> worker::Result<()> {
+        let db = &self.ctx.db;
+        // the reminder may have been deleted since it was claimed
+        let Some(reminder) = reminders::Entity::find_by_id(args.reminder_id)
+            .one(db)
+            .await
+            .map_err(Box::from)?
+        else {
+            return Ok(());
+        };
+
+        // a note that was trashed or is no longer shared with the user
+        // doesn't send anything
+        let note = notes::Entity::find_live()
+            .filter(notes::Column::Id.eq(reminder.note_id))
+            .filter(visible_to(reminder.user_id))
+            .one(db)
+            .await
+            .map_err(Box::from)?;
+        let user = users::Entity::find_by_id(reminder.user_id)
+            .one(db)
+            .await
+            .map_err(Box::from)?;
+        if let (Some(note), Some(user)) = (note, user) {
+            ReminderMailer::send_reminder(&self.ctx, &user, &note, args.due_at)
+                .await
+                .map_err(Box::from)?;
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
 mod models;
 mod requests;
+mod storage;
 mod tasks;
@@ -1 +1,4 @@
+mod notes;
+mod reminders;
+mod shares;
 mod users;
@@ -0,0 +1,134 @@
+use edvinas_notes_app::models::{
+    notes::{self, SearchHit, SNIPPET_MATCH_END, SNIPPET_MATCH_START},
+    users::{self, RegisterParams},
+    workspaces,
+};
+use migration::{
+    async_trait, Alias, ColumnDef, DbErr, MigrationName, MigrationTrait, Migrator, MigratorTrait,
+    SchemaManager, Table,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ConnectionTrait, Database, DatabaseConnection, EntityTrait,
+    IntoActiveModel,
+};
+
+/// `m20240825_000001_add_user_id_to_notes` without the foreign key, which
+/// SQLite can not add to an existing table
+struct AddUserIdToNotes;
+
+impl MigrationName for AddUserIdToNotes {
+    fn name(&self) -> &str {
+        "m20240825_000001_add_user_id_to_notes"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for AddUserIdToNotes {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alias::new("notes"))
+                    .add_column(
+                        ColumnDef::new(Alias::new("user_id")).integer().not_null().default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// The app's migrations, with [`AddUserIdToNotes`] in place of the one SQLite
+/// can not run
+struct SqliteMigrator;
+
+impl MigratorTrait for SqliteMigrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        Migrator::migrations()
+            .into_iter()
+            .map(|migration| {
+                if migration.name() == AddUserIdToNotes.name() {
+                    Box::new(AddUserIdToNotes)
+                } else {
+                    migration
+                }
+            })
+            .collect()
+    }
+}
+
+/// The app runs on Postgres in tests, so the SQLite search path gets its own
+/// in-memory database
+async fn sqlite_db() -> DatabaseConnection {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    SqliteMigrator::up(&db, None).await.unwrap();
+    db
+}
+
+async fn add_note(db: &DatabaseConnection, title: &str, content: &str) -> notes::Model {
+    let user = users::Model::find_by_email(db, "search@example.com").await.unwrap();
+    let (workspace, _) = workspaces::Model::find_default_for_user(db, user.id).await.unwrap();
+    notes::ActiveModel {
+        title: ActiveValue::set(Some(title.to_string())),
+        content: ActiveValue::set(Some(content.to_string())),
+        user_id: ActiveValue::set(user.id),
+        workspace_id: ActiveValue::set(workspace.id),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+async fn search(db: &DatabaseConnection, query: &str) -> Vec<SearchHit> {
+    notes::Entity::search(db.get_database_backend(), query)
+        .unwrap()
+        .into_model::<SearchHit>()
+        .all(db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn can_search_notes_on_sqlite() {
+    let db = sqlite_db().await;
+    users::Model::create_with_password(
+        &db,
+        &RegisterParams {
+            email: "search@example.com".to_string(),
+            password: "1234".to_string(),
+            name: "search".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let in_content = add_note(&db, "Groceries", "buy apples and pears").await;
+    let in_title = add_note(&db, "Apples", "a list of varieties").await;
+    add_note(&db, "Unrelated", "nothing to see").await;
+
+    // title matches rank higher
+    let hits = search(&db, "apples").await;
+    assert_eq!(
+        hits.iter().map(|hit| hit.id).collect::<Vec<_>>(),
+        vec![in_title.id, in_content.id]
+    );
+    let snippet = hits[1].snippet.as_deref().unwrap();
+    assert!(snippet.contains(&format!("{SNIPPET_MATCH_START}apples{SNIPPET_MATCH_END}")));
+
+    // the index follows edits, and query syntax is taken literally
+    let mut note = in_content.into_active_model();
+    note.content = ActiveValue::set(Some("buy pears".to_string()));
+    note.update(&db).await.unwrap();
+    assert_eq!(
+        search(&db, "apples").await.iter().map(|hit| hit.id).collect::<Vec<_>>(),
+        vec![in_title.id]
+    );
+    assert!(search(&db, "apples OR \"pears").await.is_empty());
+
+    // trashed notes are left out
+    let mut note = notes::Entity::find_by_id(in_title.id).one(&db).await.unwrap().unwrap().into_active_model();
+    note.deleted_at = ActiveValue::set(Some(chrono::Local::now().into()));
+    note.update(&db).await.unwrap();
+    assert!(search(&db, "apples").await.is_empty());
+}
@@ -0,0 +1,57 @@
+use chrono::{ DateTime, FixedOffset };
+use edvinas_notes_app::models::reminders::Recurrence;
+
+fn at(value: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(value).unwrap()
+}
+
+#[test]
+fn can_parse_recurrence() {
+    assert!(matches!(Recurrence::parse("daily"), Some(Recurrence::Daily)));
+    assert!(matches!(Recurrence::parse(" weekly "), Some(Recurrence::Weekly)));
+    assert!(matches!(Recurrence::parse("30 9 * * Mon-Fri"), Some(Recurrence::Cron(_))));
+    assert!(matches!(Recurrence::parse("0 30 9 * * *"), Some(Recurrence::Cron(_))));
+    assert!(Recurrence::parse("hourly").is_none());
+    assert!(Recurrence::parse("61 * * * *").is_none());
+}
+
+#[test]
+fn can_find_next_occurrence() {
+    let due_at = at("2024-09-02T09:30:00+02:00");
+    let now = at("2024-09-10T12:00:00+02:00");
+
+    let daily = Recurrence::parse("daily").unwrap();
+    assert_eq!(daily.next_after(due_at, now), Some(at("2024-09-11T09:30:00+02:00")));
+    // not due yet, nothing to skip
+    assert_eq!(daily.next_after(now + chrono::Duration::hours(1), now), Some(now + chrono::Duration::hours(1)));
+
+    let weekly = Recurrence::parse("weekly").unwrap();
+    assert_eq!(weekly.next_after(due_at, now), Some(at("2024-09-16T09:30:00+02:00")));
+
+    // cron expressions are in UTC
+    let weekdays = Recurrence::parse("30 7 * * Mon-Fri").unwrap();
+    assert_eq!(
+        weekdays.next_after(due_at, at("2024-09-13T12:00:00+02:00")),
+        Some(at("2024-09-16T07:30:00+00:00"))
+    );
+}
+
+#[test]
+fn cron_weekdays_are_numbered_like_crontab() {
+    // a Friday
+    let now = at("2024-09-13T12:00:00+02:00");
+    let next = |expression: &str| Recurrence::parse(expression).unwrap().next_after(now, now);
+
+    assert_eq!(next("30 7 * * 1"), Some(at("2024-09-16T07:30:00+00:00")));
+    assert_eq!(next("0 8 * * 0"), Some(at("2024-09-15T08:00:00+00:00")));
+    assert_eq!(next("0 8 * * 7"), Some(at("2024-09-15T08:00:00+00:00")));
+    assert_eq!(next("0 8 * * 6-7"), Some(at("2024-09-14T08:00:00+00:00")));
+    assert_eq!(next("0 8 * * 1,3"), Some(at("2024-09-16T08:00:00+00:00")));
+    assert_eq!(next("0 8 * * 1/2"), Some(at("2024-09-16T08:00:00+00:00")));
+    // Sunday, Wednesday and Saturday
+    assert_eq!(next("0 8 * * */3"), Some(at("2024-09-14T08:00:00+00:00")));
+    // six fields keep the numbering of the cron crate, 2 is Monday
+    assert_eq!(next("0 30 7 * * 2"), Some(at("2024-09-16T07:30:00+00:00")));
+    assert!(Recurrence::parse("0 8 * * 8").is_none());
+    assert!(Recurrence::parse("0 8 * * 5-2").is_none());
+}
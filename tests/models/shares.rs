@@ -0,0 +1,53 @@
+use loco_rs::testing;
+use edvinas_notes_app::{
+    app::App,
+    models::{
+        _entities::{
+            note_shares, notes,
+            sea_orm_active_enums::{SharePermission, WorkspaceRole},
+        },
+        shares::{upsert_user_share, ShareGrant},
+        users, workspaces,
+    },
+};
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn sharing_adds_recipients_from_outside_as_guests() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let owner = users::Entity::find_by_id(3).one(db).await.unwrap().unwrap();
+    let workspace = workspaces::Model::create_personal(db, &owner).await.unwrap();
+    workspace.add_member(db, 1, WorkspaceRole::Member).await.unwrap();
+    let note = notes::ActiveModel {
+        title: ActiveValue::set(Some("Personal note".to_string())),
+        content: ActiveValue::set(Some("Personal note content".to_string())),
+        user_id: ActiveValue::set(owner.id),
+        workspace_id: ActiveValue::set(workspace.id),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    for shared_with_user_id in [4, 1] {
+        let grant = ShareGrant {
+            shared_with_user_id,
+            permission: SharePermission::View,
+            expires_at: None,
+            shared_by_user_id: owner.id,
+            update_existing: true,
+        };
+        upsert_user_share::<note_shares::Entity, _>(db, &workspace, note.id, &grant)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(workspace.role_of(db, 4).await.unwrap(), Some(WorkspaceRole::Guest));
+    // members keep their role
+    assert_eq!(workspace.role_of(db, 1).await.unwrap(), Some(WorkspaceRole::Member));
+}
@@ -2,7 +2,11 @@ use insta::assert_debug_snapshot;
 use loco_rs::{model::ModelError, testing};
 use edvinas_notes_app::{
     app::App,
-    models::users::{self, Model, RegisterParams},
+    models::{
+        _entities::sea_orm_active_enums::WorkspaceRole,
+        users::{self, Model, RegisterParams},
+        workspaces,
+    },
 };
 use sea_orm::{ActiveModelTrait, ActiveValue, IntoActiveModel};
 use serial_test::serial;
@@ -55,6 +59,27 @@ async fn can_create_with_password() {
     });
 }
 
+#[tokio::test]
+#[serial]
+async fn create_with_password_adds_personal_workspace() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+
+    let params = RegisterParams {
+        email: "test@framework.com".to_string(),
+        password: "1234".to_string(),
+        name: "framework".to_string(),
+    };
+    let user = Model::create_with_password(&boot.app_context.db, &params)
+        .await
+        .unwrap();
+
+    let (workspace, role) = workspaces::Model::find_default_for_user(&boot.app_context.db, user.id)
+        .await
+        .unwrap();
+    assert_eq!(workspace.name, "framework's workspace");
+    assert_eq!(role, WorkspaceRole::Owner);
+}
+
 #[tokio::test]
 #[serial]
 async fn handle_create_with_password_with_duplicate() {
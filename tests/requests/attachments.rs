@@ -0,0 +1,214 @@
+use axum::http::header;
+use axum_test::multipart::{ MultipartForm, Part };
+use insta::{ assert_debug_snapshot, with_settings };
+use loco_rs::testing;
+use edvinas_notes_app::app::App;
+use serial_test::serial;
+
+use super::prepare_data;
+
+// TODO: see how to dedup / extract this to app-local test utils
+// not to framework, because that would require a runtime dep on insta
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("attachments_request");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn text_file(name: &str, content: &str) -> Part {
+    Part::bytes(content.as_bytes().to_vec()).file_name(name).mime_type("text/plain")
+}
+
+#[tokio::test]
+#[serial]
+async fn can_upload_download_and_delete_attachments() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let (viewer_key, viewer_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+
+        // note 3 belongs to edvinas1 and edvinas2 may view it
+        let upload_response = request
+            .post("/api/notes/3/attachments")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .multipart(
+                MultipartForm::new()
+                    .add_text("comment", "not a file")
+                    .add_part("file", text_file("hello.txt", "hello world"))
+                    .add_part("file", text_file("todo.txt", "- [ ] upload"))
+            ).await;
+        assert_eq!(upload_response.status_code(), 200);
+        let uploaded: serde_json::Value = upload_response.json();
+        let pid = uploaded[0]["pid"].as_str().unwrap().to_string();
+
+        let list_response = request
+            .get("/api/notes/3/attachments")
+            .add_header(viewer_key.clone(), viewer_value.clone()).await;
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!((list_response.status_code(), list_response.text()));
+        });
+
+        let download_response = request
+            .get(&format!("/api/notes/3/attachments/{pid}"))
+            .add_header(viewer_key.clone(), viewer_value.clone()).await;
+        assert_eq!(download_response.status_code(), 200);
+        assert_eq!(download_response.header(header::CONTENT_TYPE), "text/plain");
+        assert_eq!(download_response.header(header::CONTENT_LENGTH), "11");
+        assert_eq!(
+            download_response.header(header::CONTENT_DISPOSITION),
+            "attachment; filename=\"hello.txt\""
+        );
+        assert_eq!(download_response.header(header::X_CONTENT_TYPE_OPTIONS), "nosniff");
+        assert_eq!(download_response.text(), "hello world");
+
+        // viewing a note is not enough to change its attachments
+        let viewer_upload_response = request
+            .post("/api/notes/3/attachments")
+            .add_header(viewer_key.clone(), viewer_value.clone())
+            .multipart(MultipartForm::new().add_part("file", text_file("mine.txt", "mine"))).await;
+        assert_eq!(viewer_upload_response.status_code(), 401);
+        let viewer_delete_response = request
+            .delete(&format!("/api/notes/3/attachments/{pid}"))
+            .add_header(viewer_key.clone(), viewer_value.clone()).await;
+        assert_eq!(viewer_delete_response.status_code(), 401);
+
+        let delete_response = request
+            .delete(&format!("/api/notes/3/attachments/{pid}"))
+            .add_header(owner_key.clone(), owner_value.clone()).await;
+        assert_eq!(delete_response.status_code(), 200);
+        let deleted_response = request
+            .get(&format!("/api/notes/3/attachments/{pid}"))
+            .add_header(owner_key, owner_value).await;
+        assert_eq!(deleted_response.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn attachments_follow_note_access() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let (other_key, other_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+
+        let note: serde_json::Value = request
+            .post("/api/notes")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "title": "private", "content": "not shared" })).await
+            .json();
+        let note_id = note["id"].as_i64().unwrap();
+        let uploaded: serde_json::Value = request
+            .post(&format!("/api/notes/{note_id}/attachments"))
+            .add_header(owner_key.clone(), owner_value.clone())
+            .multipart(MultipartForm::new().add_part("file", text_file("secret.txt", "secret"))).await
+            .json();
+        let pid = uploaded[0]["pid"].as_str().unwrap();
+
+        let download_response = request
+            .get(&format!("/api/notes/{note_id}/attachments/{pid}"))
+            .add_header(other_key.clone(), other_value.clone()).await;
+        assert_eq!(download_response.status_code(), 404);
+
+        // the pid alone does not reach the file through another note
+        let other_note_response = request
+            .get(&format!("/api/notes/3/attachments/{pid}"))
+            .add_header(other_key, other_value).await;
+        assert_eq!(other_note_response.status_code(), 404);
+
+        let empty_response = request
+            .post(&format!("/api/notes/{note_id}/attachments"))
+            .add_header(owner_key.clone(), owner_value.clone())
+            .multipart(MultipartForm::new().add_text("comment", "no files")).await;
+        assert_eq!(empty_response.status_code(), 400);
+
+        // test.yaml limits request bodies to 5mb
+        let oversized_response = request
+            .post(&format!("/api/notes/{note_id}/attachments"))
+            .add_header(owner_key, owner_value)
+            .multipart(
+                MultipartForm::new().add_part(
+                    "file",
+                    Part::bytes(vec![0_u8; 6 * 1024 * 1024]).file_name("big.bin")
+                )
+            ).await;
+        assert_eq!(oversized_response.status_code(), 413);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn only_images_are_shown_inline() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (key, value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+
+        let uploaded: serde_json::Value = request
+            .post("/api/notes/3/attachments")
+            .add_header(key.clone(), value.clone())
+            .multipart(
+                MultipartForm::new()
+                    .add_part("file", Part::bytes(vec![0_u8; 4]).file_name("pixel.png").mime_type("image/png"))
+                    .add_part("file", Part::bytes(b"<svg/>".to_vec()).file_name("logo.svg").mime_type("image/svg+xml"))
+                    .add_part("file", Part::bytes(b"<script/>".to_vec()).file_name("page.html").mime_type("text/html"))
+            ).await
+            .json();
+
+        let dispositions = [
+            "inline; filename=\"pixel.png\"",
+            "attachment; filename=\"logo.svg\"",
+            "attachment; filename=\"page.html\"",
+        ];
+        for (attachment, disposition) in uploaded.as_array().unwrap().iter().zip(dispositions) {
+            let pid = attachment["pid"].as_str().unwrap();
+            let download_response = request
+                .get(&format!("/api/notes/3/attachments/{pid}"))
+                .add_header(key.clone(), value.clone()).await;
+            assert_eq!(download_response.header(header::CONTENT_DISPOSITION), disposition);
+            assert_eq!(download_response.header(header::X_CONTENT_TYPE_OPTIONS), "nosniff");
+        }
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_uploads_keep_none_of_the_files() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (key, value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+
+        // the first file arrives whole, the body ends in the middle of the second
+        let body = "--BOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"whole.txt\"\r\n\
+            Content-Type: text/plain\r\n\r\n\
+            whole\r\n\
+            --BOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"cut.txt\"\r\n\
+            Content-Type: text/plain\r\n\r\n\
+            cut of";
+        let upload_response = request
+            .post("/api/notes/3/attachments")
+            .add_header(key.clone(), value.clone())
+            .content_type("multipart/form-data; boundary=BOUNDARY")
+            .bytes(body.as_bytes().to_vec().into()).await;
+        assert_eq!(upload_response.status_code(), 400);
+
+        let attachments: serde_json::Value = request
+            .get("/api/notes/3/attachments")
+            .add_header(key, value).await
+            .json();
+        assert_eq!(attachments, serde_json::json!([]));
+    }).await;
+}
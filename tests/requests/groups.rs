@@ -0,0 +1,330 @@
+use axum::http::{ HeaderName, HeaderValue };
+use insta::{ assert_debug_snapshot, with_settings };
+use loco_rs::testing;
+use edvinas_notes_app::{
+    app::App,
+    models::_entities::{
+        group_members,
+        groups,
+        note_shares,
+        user_blocks,
+        users,
+        workspace_members,
+        workspaces,
+    },
+};
+use sea_orm::{ ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set };
+use serial_test::serial;
+
+use super::prepare_data;
+
+// TODO: see how to dedup / extract this to app-local test utils
+// not to framework, because that would require a runtime dep on insta
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("groups_request");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_create_group_and_manage_members() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let owner = prepare_data::init_user_login(&request, &ctx).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&owner.token);
+
+        let create_response = request
+            .post("/api/groups")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "name": "Team" })).await;
+        assert_eq!(create_response.status_code(), 200);
+        let group = groups::Entity::find().one(&ctx.db).await.unwrap().unwrap();
+
+        let add_member_response = request
+            .post(&format!("/api/groups/{}/members", group.pid))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "user_pid": "33333333-3333-3333-3333-333333333333" })).await;
+
+        with_settings!({
+            filters => {
+                let mut filters = testing::cleanup_user_model();
+                filters.push((r"[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}", "PID"));
+                filters
+            }
+        }, {
+            assert_debug_snapshot!((add_member_response.status_code(), add_member_response.text()));
+        });
+
+        // members see the group but can not change it
+        let (member_key, member_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let list_response = request
+            .get("/api/groups")
+            .add_header(member_key.clone(), member_value.clone()).await;
+        assert_eq!(list_response.status_code(), 200);
+        assert!(list_response.text().contains("Team"));
+
+        let rename_response = request
+            .post(&format!("/api/groups/{}", group.pid))
+            .add_header(member_key.clone(), member_value.clone())
+            .json(&serde_json::json!({ "name": "Taken over" })).await;
+        assert_eq!(rename_response.status_code(), 401);
+
+        let leave_response = request
+            .delete(&format!("/api/groups/{}/members/33333333-3333-3333-3333-333333333333", group.pid))
+            .add_header(member_key, member_value).await;
+        assert_eq!(leave_response.status_code(), 200);
+        assert_eq!(group_members::Entity::find().all(&ctx.db).await.unwrap().len(), 1);
+
+        let delete_response = request
+            .delete(&format!("/api/groups/{}", group.pid))
+            .add_header(auth_key, auth_value).await;
+        assert_eq!(delete_response.status_code(), 200);
+        assert!(groups::Entity::find().all(&ctx.db).await.unwrap().is_empty());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_see_groups_of_others() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let owner = prepare_data::init_user_login(&request, &ctx).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&owner.token);
+
+        request
+            .post("/api/groups")
+            .add_header(auth_key, auth_value)
+            .json(&serde_json::json!({ "name": "Private" })).await;
+        let group = groups::Entity::find().one(&ctx.db).await.unwrap().unwrap();
+
+        let (other_key, other_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let get_response = request
+            .get(&format!("/api/groups/{}", group.pid))
+            .add_header(other_key, other_value).await;
+        assert_eq!(get_response.status_code(), 404);
+    }).await;
+}
+
+/// Moves user 4 out of the seeded team workspace into a personal one
+async fn move_user_to_own_workspace(ctx: &loco_rs::app::AppContext) {
+    workspace_members::Entity::delete_many()
+        .filter(workspace_members::Column::UserId.eq(4))
+        .exec(&ctx.db).await
+        .unwrap();
+    let user = users::Entity::find_by_id(4).one(&ctx.db).await.unwrap().unwrap();
+    workspaces::Model::create_personal(&ctx.db, &user).await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn group_members_from_other_workspaces_can_open_shared_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        // access to note 3 may only come from the group
+        note_shares::Entity::delete_by_id(1).exec(&ctx.db).await.unwrap();
+        move_user_to_own_workspace(&ctx).await;
+        let team_workspace = (
+            HeaderName::from_static("x-workspace"),
+            HeaderValue::from_static("55555555-5555-5555-5555-555555555555"),
+        );
+
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        request
+            .post("/api/groups")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "name": "Team" })).await;
+        let group = groups::Entity::find().one(&ctx.db).await.unwrap().unwrap();
+        let add_member = || {
+            request
+                .post(&format!("/api/groups/{}/members", group.pid))
+                .add_header(owner_key.clone(), owner_value.clone())
+                .json(&serde_json::json!({ "user_pid": "44444444-4444-4444-4444-444444444444" }))
+        };
+
+        let (member_key, member_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let accept = || {
+            request
+                .post(&format!("/api/groups/{}/accept", group.pid))
+                .add_header(member_key.clone(), member_value.clone())
+        };
+
+        // members at share time join the note's workspace
+        assert_eq!(add_member().await.status_code(), 200);
+        assert_eq!(accept().await.status_code(), 200);
+        let share_response = request
+            .post("/api/notes/3/share")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "shared_with_group_pid": group.pid.to_string() })).await;
+        assert_eq!(share_response.status_code(), 200);
+
+        let get_note = || {
+            request
+                .get("/api/notes/3")
+                .add_header(member_key.clone(), member_value.clone())
+                .add_header(team_workspace.0.clone(), team_workspace.1.clone())
+        };
+        assert_eq!(get_note().await.status_code(), 200);
+
+        // so do members added after the note was shared, once they accept
+        group_members::Entity::delete_many()
+            .filter(group_members::Column::UserId.eq(4))
+            .exec(&ctx.db).await
+            .unwrap();
+        move_user_to_own_workspace(&ctx).await;
+        assert_eq!(get_note().await.status_code(), 404);
+        assert_eq!(add_member().await.status_code(), 200);
+        assert_eq!(get_note().await.status_code(), 404);
+        assert_eq!(accept().await.status_code(), 200);
+        assert_eq!(get_note().await.status_code(), 200);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn group_shares_reach_members_once_they_accept() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        // access to note 3 may only come from the group
+        note_shares::Entity::delete_by_id(1).exec(&ctx.db).await.unwrap();
+
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        request
+            .post("/api/groups")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "name": "Team" })).await;
+        let group = groups::Entity::find().one(&ctx.db).await.unwrap().unwrap();
+        let add_member = || {
+            request
+                .post(&format!("/api/groups/{}/members", group.pid))
+                .add_header(owner_key.clone(), owner_value.clone())
+                .json(&serde_json::json!({ "user_pid": "44444444-4444-4444-4444-444444444444" }))
+        };
+        let add_member_response = add_member().await;
+        assert_eq!(add_member_response.status_code(), 200);
+        assert_eq!(add_member_response.json::<serde_json::Value>()["members"][1]["state"], "pending");
+        request
+            .post("/api/notes/3/share")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "shared_with_group_pid": group.pid.to_string() })).await;
+
+        let (member_key, member_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let get_note = || {
+            request.get("/api/notes/3").add_header(member_key.clone(), member_value.clone())
+        };
+        let answer = |answer: &'static str| {
+            request
+                .post(&format!("/api/groups/{}/{answer}", group.pid))
+                .add_header(member_key.clone(), member_value.clone())
+        };
+
+        // invited members see the group but none of its notes
+        let get_group = request
+            .get(&format!("/api/groups/{}", group.pid))
+            .add_header(member_key.clone(), member_value.clone()).await;
+        assert_eq!(get_group.status_code(), 200);
+        assert_eq!(get_note().await.status_code(), 404);
+
+        // declining keeps it that way, and the owner can not invite them again
+        assert_eq!(answer("decline").await.status_code(), 200);
+        assert_eq!(add_member().await.status_code(), 200);
+        assert_eq!(get_note().await.status_code(), 404);
+
+        // until they change their mind
+        assert_eq!(answer("accept").await.status_code(), 200);
+        assert_eq!(get_note().await.status_code(), 200);
+        assert_eq!(answer("accept").await.status_code(), 400);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn group_shares_skip_members_who_blocked_the_sharer() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        // access to note 3 may only come from the group
+        note_shares::Entity::delete_by_id(1).exec(&ctx.db).await.unwrap();
+
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        request
+            .post("/api/groups")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "name": "Team" })).await;
+        let group = groups::Entity::find().one(&ctx.db).await.unwrap().unwrap();
+        request
+            .post(&format!("/api/groups/{}/members", group.pid))
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "user_pid": "44444444-4444-4444-4444-444444444444" })).await;
+        let (member_key, member_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        request
+            .post(&format!("/api/groups/{}/accept", group.pid))
+            .add_header(member_key.clone(), member_value.clone()).await;
+        let share_response = request
+            .post("/api/notes/3/share")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "shared_with_group_pid": group.pid.to_string() })).await;
+        assert_eq!(share_response.status_code(), 200);
+
+        let get_note = || {
+            request.get("/api/notes/3").add_header(member_key.clone(), member_value.clone())
+        };
+        assert_eq!(get_note().await.status_code(), 200);
+
+        // blocking the sharer after joining stops their group shares
+        (user_blocks::ActiveModel {
+            user_id: Set(4),
+            blocked_user_id: Set(3),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+        assert_eq!(get_note().await.status_code(), 404);
+
+        // the share reaches them again once they unblock the sharer
+        user_blocks::Entity::delete_many().exec(&ctx.db).await.unwrap();
+        assert_eq!(get_note().await.status_code(), 200);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_invite_users_who_blocked_you_to_a_group() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        (user_blocks::ActiveModel {
+            user_id: Set(4),
+            blocked_user_id: Set(3),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        request
+            .post("/api/groups")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "name": "Team" })).await;
+        let group = groups::Entity::find().one(&ctx.db).await.unwrap().unwrap();
+        let add_member_response = request
+            .post(&format!("/api/groups/{}/members", group.pid))
+            .add_header(owner_key, owner_value)
+            .json(&serde_json::json!({ "user_pid": "44444444-4444-4444-4444-444444444444" })).await;
+        assert_eq!(add_member_response.status_code(), 401);
+        assert_eq!(group_members::Entity::find().all(&ctx.db).await.unwrap().len(), 1);
+    }).await;
+}
@@ -1,4 +1,13 @@
+mod attachments;
 mod auth;
+mod groups;
+mod note_items;
+mod notebooks;
 mod notes;
 mod prepare_data;
+mod public;
+mod reminders;
+mod tags;
 mod user;
+mod wiki_links;
+mod workspaces;
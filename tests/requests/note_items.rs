@@ -0,0 +1,135 @@
+use insta::{ assert_debug_snapshot, with_settings };
+use loco_rs::testing;
+use edvinas_notes_app::app::App;
+use serial_test::serial;
+
+use super::prepare_data;
+
+// TODO: see how to dedup / extract this to app-local test utils
+// not to framework, because that would require a runtime dep on insta
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("note_items_request");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn item_texts(items: &serde_json::Value) -> Vec<String> {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["text"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_add_reorder_check_and_delete_items() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+
+        let mut pids = Vec::new();
+        for text in ["milk", " eggs ", "bread"] {
+            let item: serde_json::Value = request
+                .post("/api/notes/3/items")
+                .add_header(owner_key.clone(), owner_value.clone())
+                .json(&serde_json::json!({ "text": text })).await
+                .json();
+            pids.push(item["pid"].as_str().unwrap().to_string());
+        }
+        let blank_response = request
+            .post("/api/notes/3/items")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "text": "  " })).await;
+        assert_eq!(blank_response.status_code(), 400);
+
+        let reordered: serde_json::Value = request
+            .put("/api/notes/3/items/order")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "pids": [pids[2], pids[0], pids[1]] })).await
+            .json();
+        assert_eq!(item_texts(&reordered), vec!["bread", "milk", "eggs"]);
+        let partial_response = request
+            .put("/api/notes/3/items/order")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "pids": [pids[0], pids[1]] })).await;
+        assert_eq!(partial_response.status_code(), 400);
+        let repeated_response = request
+            .put("/api/notes/3/items/order")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "pids": [pids[0], pids[0], pids[1], pids[2]] })).await;
+        assert_eq!(repeated_response.status_code(), 400);
+
+        let check_response = request
+            .post(&format!("/api/notes/3/items/{}/check", pids[0]))
+            .add_header(owner_key.clone(), owner_value.clone()).await;
+        assert_eq!(check_response.status_code(), 200);
+        let uncheck_response = request
+            .delete(&format!("/api/notes/3/items/{}/check", pids[2]))
+            .add_header(owner_key.clone(), owner_value.clone()).await;
+        assert_eq!(uncheck_response.status_code(), 200);
+
+        let delete_response = request
+            .delete(&format!("/api/notes/3/items/{}", pids[1]))
+            .add_header(owner_key.clone(), owner_value.clone()).await;
+        assert_eq!(delete_response.status_code(), 200);
+
+        let list_response = request
+            .get("/api/notes/3/items")
+            .add_header(owner_key, owner_value).await;
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!((list_response.status_code(), list_response.text()));
+        });
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn editors_of_a_shared_note_can_tick_items() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let (recipient_key, recipient_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+
+        let item: serde_json::Value = request
+            .post("/api/notes/3/items")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "text": "milk" })).await
+            .json();
+        let check_path = format!("/api/notes/3/items/{}/check", item["pid"].as_str().unwrap());
+
+        // note 3 is shared with edvinas2 for viewing only
+        let items_response = request
+            .get("/api/notes/3/items")
+            .add_header(recipient_key.clone(), recipient_value.clone()).await;
+        assert_eq!(items_response.status_code(), 200);
+        let viewer_response = request
+            .post(&check_path)
+            .add_header(recipient_key.clone(), recipient_value.clone()).await;
+        assert_eq!(viewer_response.status_code(), 401);
+
+        let share_response = request
+            .patch("/api/notes/3/share/44444444-4444-4444-4444-444444444444")
+            .add_header(owner_key, owner_value)
+            .json(&serde_json::json!({ "permission": "edit" })).await;
+        assert_eq!(share_response.status_code(), 200);
+
+        let checked: serde_json::Value = request
+            .post(&check_path)
+            .add_header(recipient_key, recipient_value).await
+            .json();
+        assert_eq!(checked["checked"], true);
+        assert_eq!(checked["checked_by"]["pid"], "44444444-4444-4444-4444-444444444444");
+        assert!(checked["checked_at"].is_string());
+    }).await;
+}
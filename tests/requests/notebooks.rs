@@ -0,0 +1,342 @@
+use axum::http::{ HeaderName, HeaderValue };
+use insta::{ assert_debug_snapshot, with_settings };
+use loco_rs::{ testing, TestServer };
+use edvinas_notes_app::{ app::App, models::_entities::{ notebook_shares, notes } };
+use sea_orm::{ ActiveModelTrait, EntityTrait, IntoActiveModel, Set };
+use serial_test::serial;
+
+use super::prepare_data;
+
+// TODO: see how to dedup / extract this to app-local test utils
+// not to framework, because that would require a runtime dep on insta
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("notebooks_request");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+/// Creates a notebook as the logged in user and returns its pid
+async fn create_notebook(
+    request: &TestServer,
+    auth: &(HeaderName, HeaderValue),
+    name: &str,
+    parent_pid: Option<&str>
+) -> String {
+    let response = request
+        .post("/api/notebooks")
+        .add_header(auth.0.clone(), auth.1.clone())
+        .json(&serde_json::json!({ "name": name, "parent_pid": parent_pid })).await;
+    assert_eq!(response.status_code(), 200);
+    let body: serde_json::Value = response.json();
+    body["pid"].as_str().unwrap().to_string()
+}
+
+fn note_ids(page: &serde_json::Value) -> Vec<i64> {
+    page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|note| note["id"].as_i64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_nest_rename_and_move_notebooks() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let auth = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let work = create_notebook(&request, &auth, "Work", None).await;
+        let projects = create_notebook(&request, &auth, "Projects", Some(&work)).await;
+
+        let move_note_response = request
+            .post("/api/notes/3/move")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&serde_json::json!({ "notebook_pid": projects })).await;
+        assert_eq!(move_note_response.status_code(), 200);
+        let page: serde_json::Value = request
+            .get("/api/notes")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .add_query_param("notebook", &projects).await
+            .json();
+        assert_eq!(note_ids(&page), vec![3]);
+
+        // a notebook can not end up inside itself
+        let cycle_response = request
+            .post(&format!("/api/notebooks/{work}/move"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&serde_json::json!({ "parent_pid": projects })).await;
+        assert_eq!(cycle_response.status_code(), 400);
+
+        let move_response = request
+            .post(&format!("/api/notebooks/{projects}/move"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&serde_json::json!({ "parent_pid": null })).await;
+        assert_eq!(move_response.status_code(), 200);
+        let rename_response = request
+            .patch(&format!("/api/notebooks/{work}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&serde_json::json!({ "name": "Office" })).await;
+        assert_eq!(rename_response.status_code(), 200);
+
+        let list_response = request
+            .get("/api/notebooks")
+            .add_header(auth.0.clone(), auth.1.clone()).await;
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!((list_response.status_code(), list_response.text()));
+        });
+
+        // notes only go into notebooks of their owner
+        let other_auth = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let foreign_response = request
+            .post("/api/notes/4/move")
+            .add_header(other_auth.0.clone(), other_auth.1.clone())
+            .json(&serde_json::json!({ "notebook_pid": work })).await;
+        assert_eq!(foreign_response.status_code(), 404);
+
+        // nor can others list the notes in them
+        let filter_response = request
+            .get("/api/notes")
+            .add_header(other_auth.0, other_auth.1)
+            .add_query_param("notebook", &work).await;
+        assert_eq!(filter_response.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn sharing_a_notebook_grants_access_to_its_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let owner = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let recipient = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let shared = create_notebook(&request, &owner, "Shared", None).await;
+        let nested = create_notebook(&request, &owner, "Nested", Some(&shared)).await;
+
+        let recipient_pid = "44444444-4444-4444-4444-444444444444";
+        let share_response = request
+            .post(&format!("/api/notebooks/{shared}/share"))
+            .add_header(owner.0.clone(), owner.1.clone())
+            .json(&serde_json::json!({ "shared_with_pid": recipient_pid, "permission": "edit" })).await;
+        assert_eq!(share_response.status_code(), 200);
+
+        // added after sharing, one level down
+        let add_response = request
+            .post("/api/notes")
+            .add_header(owner.0.clone(), owner.1.clone())
+            .json(
+                &serde_json::json!({
+                "title": "Plan",
+                "content": "the plan",
+                "notebook_pid": nested,
+            })
+            ).await;
+        assert_eq!(add_response.status_code(), 200);
+        let note_id = add_response.json::<serde_json::Value>()["id"].as_i64().unwrap();
+
+        // pending shares grant nothing yet
+        let pending_response = request
+            .get(&format!("/api/notes/{note_id}"))
+            .add_header(recipient.0.clone(), recipient.1.clone()).await;
+        assert_eq!(pending_response.status_code(), 404);
+
+        let incoming_response = request
+            .get("/api/notebooks/shares/incoming")
+            .add_header(recipient.0.clone(), recipient.1.clone()).await;
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!((incoming_response.status_code(), incoming_response.text()));
+        });
+        let accept_response = request
+            .post(&format!("/api/notebooks/{shared}/share/accept"))
+            .add_header(recipient.0.clone(), recipient.1.clone()).await;
+        assert_eq!(accept_response.status_code(), 200);
+
+        let get_response = request
+            .get(&format!("/api/notes/{note_id}"))
+            .add_header(recipient.0.clone(), recipient.1.clone()).await;
+        assert_eq!(get_response.status_code(), 200);
+        let update_response = request
+            .post(&format!("/api/notes/{note_id}"))
+            .add_header(recipient.0.clone(), recipient.1.clone())
+            .json(&serde_json::json!({ "title": "Plan", "content": "a better plan" })).await;
+        assert_eq!(update_response.status_code(), 200);
+        let shared_page: serde_json::Value = request
+            .get("/api/notes/shared")
+            .add_header(recipient.0.clone(), recipient.1.clone()).await
+            .json();
+        assert!(note_ids(&shared_page).contains(&note_id));
+        let children: serde_json::Value = request
+            .get("/api/notebooks")
+            .add_header(recipient.0.clone(), recipient.1.clone())
+            .add_query_param("parent", &shared).await
+            .json();
+        assert_eq!(children[0]["pid"], nested.as_str());
+
+        // the recipient can leave the notebook again
+        let leave_response = request
+            .delete(&format!("/api/notebooks/{shared}/share/{recipient_pid}"))
+            .add_header(recipient.0.clone(), recipient.1.clone()).await;
+        assert_eq!(leave_response.status_code(), 200);
+        let gone_response = request
+            .get(&format!("/api/notes/{note_id}"))
+            .add_header(recipient.0, recipient.1).await;
+        assert_eq!(gone_response.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn notebook_recipients_reshare_under_the_owner_policy() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let third = prepare_data::init_user_login(&request, &ctx).await;
+        let owner = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let recipient = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let shared = create_notebook(&request, &owner, "Shared", None).await;
+        request
+            .post(&format!("/api/notebooks/{shared}/share"))
+            .add_header(owner.0.clone(), owner.1.clone())
+            .json(
+                &serde_json::json!({
+                "shared_with_pid": "44444444-4444-4444-4444-444444444444",
+                "permission": "edit",
+            })
+            ).await;
+        request
+            .post(&format!("/api/notebooks/{shared}/share/accept"))
+            .add_header(recipient.0.clone(), recipient.1.clone()).await;
+        let reshare = |permission: &'static str| {
+            request
+                .post(&format!("/api/notebooks/{shared}/share"))
+                .add_header(recipient.0.clone(), recipient.1.clone())
+                .json(
+                    &serde_json::json!({
+                    "shared_with_pid": third.user.pid.to_string(),
+                    "permission": permission,
+                })
+                )
+        };
+
+        // recipients may not reshare until the owner allows it
+        assert_eq!(reshare("view").await.status_code(), 401);
+        let policy = |auth: &(HeaderName, HeaderValue)| {
+            request
+                .patch(&format!("/api/notebooks/{shared}/reshare-policy"))
+                .add_header(auth.0.clone(), auth.1.clone())
+                .json(&serde_json::json!({ "allow_reshare": true }))
+        };
+        assert_eq!(policy(&recipient).await.status_code(), 401);
+        let policy_response = policy(&owner).await;
+        assert_eq!(policy_response.status_code(), 200);
+        assert_eq!(policy_response.json::<serde_json::Value>()["allow_reshare"], true);
+
+        // and then never with more than their own permission
+        assert_eq!(reshare("manage").await.status_code(), 401);
+        assert_eq!(reshare("view").await.status_code(), 200);
+        let share = notebook_shares::Entity::find()
+            .all(&ctx.db).await
+            .unwrap()
+            .into_iter()
+            .find(|share| share.shared_with_user_id == third.user.id)
+            .unwrap();
+        assert_eq!(share.shared_by_user_id, Some(4));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn expired_notebook_shares_can_not_be_accepted() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let owner = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let recipient = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let shared = create_notebook(&request, &owner, "Shared", None).await;
+        request
+            .post(&format!("/api/notebooks/{shared}/share"))
+            .add_header(owner.0.clone(), owner.1.clone())
+            .json(&serde_json::json!({ "shared_with_pid": "44444444-4444-4444-4444-444444444444" })).await;
+
+        let mut share = notebook_shares::Entity::find()
+            .one(&ctx.db).await
+            .unwrap()
+            .unwrap()
+            .into_active_model();
+        share.expires_at = Set(Some((chrono::Local::now() - chrono::Duration::hours(1)).into()));
+        share.update(&ctx.db).await.unwrap();
+
+        let incoming: serde_json::Value = request
+            .get("/api/notebooks/shares/incoming")
+            .add_header(recipient.0.clone(), recipient.1.clone()).await
+            .json();
+        assert_eq!(incoming, serde_json::json!([]));
+        let accept_response = request
+            .post(&format!("/api/notebooks/{shared}/share/accept"))
+            .add_header(recipient.0, recipient.1).await;
+        assert_eq!(accept_response.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn deleting_a_notebook_reparents_or_trashes_its_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let auth = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let outer = create_notebook(&request, &auth, "Outer", None).await;
+        let inner = create_notebook(&request, &auth, "Inner", Some(&outer)).await;
+        let innermost = create_notebook(&request, &auth, "Innermost", Some(&inner)).await;
+        let move_response = request
+            .post("/api/notes/3/move")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .json(&serde_json::json!({ "notebook_pid": inner })).await;
+        assert_eq!(move_response.status_code(), 200);
+
+        let reparent_response = request
+            .delete(&format!("/api/notebooks/{inner}"))
+            .add_header(auth.0.clone(), auth.1.clone()).await;
+        assert_eq!(reparent_response.status_code(), 200);
+        let page: serde_json::Value = request
+            .get("/api/notes")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .add_query_param("notebook", &outer).await
+            .json();
+        assert_eq!(note_ids(&page), vec![3]);
+        let children: serde_json::Value = request
+            .get("/api/notebooks")
+            .add_header(auth.0.clone(), auth.1.clone())
+            .add_query_param("parent", &outer).await
+            .json();
+        assert_eq!(children[0]["pid"], innermost.as_str());
+
+        let cascade_response = request
+            .delete(&format!("/api/notebooks/{outer}"))
+            .add_header(auth.0.clone(), auth.1.clone())
+            .add_query_param("mode", "cascade").await;
+        assert_eq!(cascade_response.status_code(), 200);
+        let note = notes::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert!(note.deleted_at.is_some());
+        assert_eq!(note.notebook_id, None);
+        let notebooks: serde_json::Value = request
+            .get("/api/notebooks")
+            .add_header(auth.0, auth.1).await
+            .json();
+        assert_eq!(notebooks, serde_json::json!([]));
+    }).await;
+}
@@ -2,10 +2,23 @@ use axum::http::{ HeaderName, HeaderValue };
 use insta::{ assert_debug_snapshot, with_settings };
 use loco_rs::testing;
 use edvinas_notes_app::{ app::App, models::_entities::notes::Entity };
-use edvinas_notes_app::models::_entities::note_shares;
+use edvinas_notes_app::models::_entities::{
+    groups,
+    note_group_shares,
+    note_invitations,
+    note_links,
+    note_shares,
+    user_blocks,
+    users,
+};
 use edvinas_notes_app::models::_entities::notes;
+use edvinas_notes_app::models::_entities::sea_orm_active_enums::{
+    NoteFormat,
+    SharePermission,
+    ShareState,
+};
 use sea_orm::entity::prelude::*;
-use sea_orm::Set;
+use sea_orm::{ IntoActiveModel, Set };
 use serial_test::serial;
 
 // TODO: see how to dedup / extract this to app-local test utils
@@ -136,7 +149,7 @@ async fn can_delete_note() {
         testing::seed::<App>(&ctx.db).await.unwrap();
 
         let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
-        let count_before_delete = Entity::find().all(&ctx.db).await.unwrap().len();
+        let count_before_delete = Entity::find_live().all(&ctx.db).await.unwrap().len();
         let delete_note_request = authenticated_request.delete("/api/notes/3").await;
 
         with_settings!({
@@ -151,8 +164,10 @@ async fn can_delete_note() {
         );
         });
 
-        let count_after_delete = Entity::find().all(&ctx.db).await.unwrap().len();
+        let count_after_delete = Entity::find_live().all(&ctx.db).await.unwrap().len();
         assert_eq!(count_after_delete, count_before_delete - 1);
+        // the note is only moved to the trash
+        assert!(Entity::find_by_id(3).one(&ctx.db).await.unwrap().is_some());
     }).await;
 }
 
@@ -294,7 +309,12 @@ async fn can_update_shared_note() {
         // Assume user 1 and 2 exist from seeding
         let note = Entity::find().one(&ctx.db).await.unwrap().unwrap();
 
-        // Share the note
+        // Share the note, replacing the seeded share since there can only be one per user
+        note_shares::Entity::delete_many()
+            .filter(note_shares::Column::NoteId.eq(note.id))
+            .filter(note_shares::Column::SharedWithUserId.eq(4))
+            .exec(&ctx.db).await
+            .unwrap();
         let _share = (note_shares::ActiveModel {
             note_id: Set(note.id),
             shared_with_user_id: Set(4), // Assume user 4
@@ -331,3 +351,1688 @@ async fn can_update_shared_note() {
         assert_eq!(updated_note.title, Some("Updated Shared Note".to_string()));
     }).await;
 }
+
+#[tokio::test]
+#[serial]
+async fn cannot_update_note_shared_with_view_permission() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        // note 3 is shared with edvinas2 (user 4) as view-only in the fixtures
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let payload =
+            serde_json::json!({
+            "title": "Not allowed",
+            "content": "Viewers can not edit",
+        });
+
+        let update_note_request = authenticated_request.post("/api/notes/3").json(&payload).await;
+
+        assert_eq!(update_note_request.status_code(), 401);
+
+        let note = Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(note.title, Some("Loco note 3".to_string()));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_note_shared_with_edit_permission() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let share = note_shares::Entity
+            ::find_by_id(1)
+            .one(&ctx.db).await
+            .unwrap()
+            .unwrap();
+        let mut share = share.into_active_model();
+        share.permission = Set(SharePermission::Edit);
+        share.update(&ctx.db).await.unwrap();
+
+        let payload =
+            serde_json::json!({
+            "title": "Edited by collaborator",
+            "content": "Editors can change shared notes",
+        });
+
+        let update_note_request = authenticated_request.post("/api/notes/3").json(&payload).await;
+
+        with_settings!({
+            filters => {
+                 let mut combined_filters = testing::CLEANUP_DATE.to_vec();
+                 combined_filters.extend(vec![(r#"\"id\\":\d+"#, r#""id\":ID"#)]);
+                 combined_filters
+            }
+        }, {
+            assert_debug_snapshot!(
+                (update_note_request.status_code(), update_note_request.text())
+            );
+        });
+
+        // editors are still not allowed to delete the note
+        let delete_note_request = authenticated_request.delete("/api/notes/3").await;
+        assert_eq!(delete_note_request.status_code(), 401);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_note_shares() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let get_shares_request = authenticated_request.get("/api/notes/3/shares").await;
+
+        assert_debug_snapshot!((get_shares_request.status_code(), get_shares_request.text()));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_update_share_permission() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let payload = serde_json::json!({
+            "permission": "edit"
+        });
+
+        let update_share_request = authenticated_request
+            .patch("/api/notes/3/share/44444444-4444-4444-4444-444444444444")
+            .json(&payload).await;
+
+        assert_debug_snapshot!((update_share_request.status_code(), update_share_request.text()));
+
+        let share = note_shares::Entity::find_by_id(1).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(share.permission, SharePermission::Edit);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_revoke_share() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let revoke_share_request = authenticated_request
+            .delete("/api/notes/3/share/44444444-4444-4444-4444-444444444444").await;
+
+        assert_eq!(revoke_share_request.status_code(), 200);
+
+        // the note itself is kept, only the share is removed
+        assert!(Entity::find_by_id(3).one(&ctx.db).await.unwrap().is_some());
+        let share = note_shares::Entity::find_by_id(1).one(&ctx.db).await.unwrap();
+        assert!(share.is_none());
+
+        let revoke_again_request = authenticated_request
+            .delete("/api/notes/3/share/44444444-4444-4444-4444-444444444444").await;
+        assert_eq!(revoke_again_request.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_revoke_share_without_manage_permission() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let revoke_share_request = authenticated_request
+            .delete("/api/notes/3/share/44444444-4444-4444-4444-444444444444").await;
+
+        assert_eq!(revoke_share_request.status_code(), 401);
+        let share = note_shares::Entity::find_by_id(1).one(&ctx.db).await.unwrap();
+        assert!(share.is_some());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_access_note_after_share_expired() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let share = note_shares::Entity::find_by_id(1).one(&ctx.db).await.unwrap().unwrap();
+        let mut share = share.into_active_model();
+        share.expires_at = Set(Some((chrono::Local::now() - chrono::Duration::hours(1)).into()));
+        share.update(&ctx.db).await.unwrap();
+
+        let get_note_request = authenticated_request.get("/api/notes/3").await;
+        assert_eq!(get_note_request.status_code(), 404);
+
+        let shared_notes: serde_json::Value = authenticated_request.get("/api/notes/shared").await.json();
+        assert_eq!(shared_notes["items"], serde_json::json!([]));
+        assert_eq!(shared_notes["total"], 0);
+
+        let notes: serde_json::Value = authenticated_request.get("/api/notes").await.json();
+        assert!(notes["items"].as_array().unwrap().iter().all(|note| note["id"] != 3));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn notes_shared_by_me_leave_out_declined_and_expired_shares() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+        let shared_by_me_ids = |notes: serde_json::Value| -> Vec<serde_json::Value> {
+            notes["items"].as_array().unwrap().iter().map(|note| note["id"].clone()).collect()
+        };
+
+        let notes: serde_json::Value = authenticated_request.get("/api/notes/shared-by-me").await.json();
+        assert_eq!(shared_by_me_ids(notes), vec![serde_json::json!(3)]);
+
+        let mut share = note_shares::Entity::find_by_id(1).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        share.state = Set(ShareState::Declined);
+        share.update(&ctx.db).await.unwrap();
+
+        let notes: serde_json::Value = authenticated_request.get("/api/notes/shared-by-me").await.json();
+        assert!(shared_by_me_ids(notes).is_empty());
+
+        let mut share = note_shares::Entity::find_by_id(1).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        share.state = Set(ShareState::Accepted);
+        share.expires_at = Set(Some((chrono::Local::now() - chrono::Duration::hours(1)).into()));
+        share.update(&ctx.db).await.unwrap();
+
+        let notes: serde_json::Value = authenticated_request.get("/api/notes/shared-by-me").await.json();
+        assert!(shared_by_me_ids(notes).is_empty());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_share_note_with_expiry_in_the_past() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let payload = serde_json::json!({
+            "shared_with_user_id": 4,
+            "expires_at": "2020-01-01T00:00:00Z"
+        });
+
+        let share_note_request = authenticated_request.post("/api/notes/3/share").json(&payload).await;
+
+        assert_eq!(share_note_request.status_code(), 400);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_share_note_by_pid() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let payload = serde_json::json!({
+            "shared_with_pid": "22222222-2222-2222-2222-222222222222",
+            "permission": "edit"
+        });
+
+        let share_note_request = authenticated_request.post("/api/notes/3/share").json(&payload).await;
+
+        with_settings!({
+            filters => vec![(r#"\"id\\":\d+"#, r#""id\":ID"#)]
+        }, {
+            assert_debug_snapshot!((share_note_request.status_code(), share_note_request.text()));
+        });
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_share_note_by_email_of_existing_user() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let payload = serde_json::json!({
+            "shared_with_email": "user2@example.com"
+        });
+
+        let share_note_request = authenticated_request.post("/api/notes/3/share").json(&payload).await;
+        assert_eq!(share_note_request.status_code(), 200);
+
+        let share = note_shares::Entity
+            ::find()
+            .filter(note_shares::Column::NoteId.eq(3))
+            .filter(note_shares::Column::SharedWithUserId.eq(2))
+            .one(&ctx.db).await
+            .unwrap();
+        assert!(share.is_some());
+        assert_eq!(ctx.mailer.unwrap().deliveries().count, 0);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn invitation_email_escapes_names_and_note_titles() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let mut note = notes::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        note.title = Set(Some("<a href='http://evil.example'>Win</a>".to_string()));
+        note.update(&ctx.db).await.unwrap();
+        let mut user = users::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        user.name = Set("<b>Eve</b>".to_string());
+        user.update(&ctx.db).await.unwrap();
+
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+        let share_note_request = authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({ "shared_with_email": "newcomer@example.com" })).await;
+        assert_eq!(share_note_request.status_code(), 200);
+
+        let deliveries = ctx.mailer.as_ref().unwrap().deliveries();
+        let (_, html) = deliveries.messages[0].split_once("text/html").unwrap();
+        assert!(html.contains("&lt;b&gt;Eve"));
+        assert!(html.contains("&lt;a href"));
+        assert!(!html.contains("<b>Eve"));
+        assert!(!html.contains("<a href=3D'http://evil"));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_invite_by_email_and_share_once_email_is_verified() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let payload = serde_json::json!({
+            "shared_with_email": "Newcomer@Example.com",
+            "permission": "edit"
+        });
+
+        let share_note_request = authenticated_request.post("/api/notes/3/share").json(&payload).await;
+
+        assert_debug_snapshot!((share_note_request.status_code(), share_note_request.text()));
+
+        let invitations = note_invitations::Entity::find().all(&ctx.db).await.unwrap();
+        assert_eq!(invitations.len(), 1);
+
+        with_settings!({
+            filters => testing::cleanup_email()
+        }, {
+            assert_debug_snapshot!(ctx.mailer.as_ref().unwrap().deliveries());
+        });
+
+        let register_payload = serde_json::json!({
+            "name": "newcomer",
+            "email": "newcomer@example.com",
+            "password": "1234"
+        });
+        authenticated_request.post("/api/auth/register").json(&register_payload).await;
+
+        // signing up with the address is not proof of owning it
+        let newcomer = users::Model::find_by_email(&ctx.db, "newcomer@example.com").await.unwrap();
+        let shares_before_verify = note_shares::Entity
+            ::find()
+            .filter(note_shares::Column::SharedWithUserId.eq(newcomer.id))
+            .all(&ctx.db).await
+            .unwrap();
+        assert!(shares_before_verify.is_empty());
+        assert_eq!(note_invitations::Entity::find().all(&ctx.db).await.unwrap().len(), 1);
+
+        authenticated_request
+            .post("/api/auth/verify")
+            .json(&serde_json::json!({ "token": newcomer.email_verification_token })).await;
+
+        let share = note_shares::Entity
+            ::find()
+            .filter(note_shares::Column::NoteId.eq(3))
+            .filter(note_shares::Column::SharedWithUserId.eq(newcomer.id))
+            .one(&ctx.db).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(share.permission, SharePermission::Edit);
+        assert!(note_invitations::Entity::find().all(&ctx.db).await.unwrap().is_empty());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn signing_up_with_invite_token_accepts_the_invitation() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({ "shared_with_email": "newcomer@example.com" })).await;
+        let invitation = note_invitations::Entity::find().one(&ctx.db).await.unwrap().unwrap();
+
+        // a token for another address is ignored
+        authenticated_request
+            .post("/api/auth/register")
+            .json(
+                &serde_json::json!({
+                "name": "someone",
+                "email": "someone@example.com",
+                "password": "1234",
+                "invite_token": invitation.token
+            })
+            ).await;
+        assert_eq!(note_invitations::Entity::find().all(&ctx.db).await.unwrap().len(), 1);
+
+        authenticated_request
+            .post("/api/auth/register")
+            .json(
+                &serde_json::json!({
+                "name": "newcomer",
+                "email": "newcomer@example.com",
+                "password": "1234",
+                "invite_token": invitation.token
+            })
+            ).await;
+
+        let newcomer = users::Model::find_by_email(&ctx.db, "newcomer@example.com").await.unwrap();
+        assert!(newcomer.email_verified_at.is_none());
+        let share = note_shares::Entity
+            ::find()
+            .filter(note_shares::Column::NoteId.eq(3))
+            .filter(note_shares::Column::SharedWithUserId.eq(newcomer.id))
+            .one(&ctx.db).await
+            .unwrap();
+        assert!(share.is_some());
+        assert!(note_invitations::Entity::find().all(&ctx.db).await.unwrap().is_empty());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_share_note_without_target() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let share_note_request = authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({ "permission": "view" })).await;
+
+        assert_eq!(share_note_request.status_code(), 400);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn sharing_note_twice_updates_the_existing_share() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        for permission in ["view", "edit"] {
+            let share_note_request = authenticated_request
+                .post("/api/notes/3/share")
+                .json(&serde_json::json!({
+                    "shared_with_pid": "22222222-2222-2222-2222-222222222222",
+                    "permission": permission
+                })).await;
+            assert_eq!(share_note_request.status_code(), 200);
+        }
+
+        let shares = note_shares::Entity::find()
+            .filter(note_shares::Column::NoteId.eq(3))
+            .filter(note_shares::Column::SharedWithUserId.eq(2))
+            .all(&ctx.db).await
+            .unwrap();
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0].permission, SharePermission::Edit);
+        assert_eq!(shares[0].state, ShareState::Pending);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn sharing_all_notes_twice_does_not_duplicate_shares() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        for _ in 0..2 {
+            let share_all_notes_request = authenticated_request
+                .post("/api/notes/share-all")
+                .json(&serde_json::json!({ "shared_with_user_id": 4 })).await;
+            assert_eq!(share_all_notes_request.status_code(), 200);
+        }
+
+        let shares = note_shares::Entity::find()
+            .filter(note_shares::Column::SharedWithUserId.eq(4))
+            .all(&ctx.db).await
+            .unwrap();
+        let user_notes = notes::Entity::find()
+            .filter(notes::Column::UserId.eq(3))
+            .all(&ctx.db).await
+            .unwrap();
+        assert_eq!(shares.len(), user_notes.len());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_share_note_with_yourself() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let share_note_request = authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({ "shared_with_user_id": 3 })).await;
+        assert_eq!(share_note_request.status_code(), 400);
+
+        let share_all_notes_request = authenticated_request
+            .post("/api/notes/share-all")
+            .json(&serde_json::json!({ "shared_with_email": "edvinas1@gmail.com" })).await;
+        assert_eq!(share_all_notes_request.status_code(), 400);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_share_note_with_unknown_user() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let share_note_request = authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({ "shared_with_user_id": 9999 })).await;
+        assert_eq!(share_note_request.status_code(), 404);
+
+        let share_note_request = authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({
+                "shared_with_pid": "99999999-9999-9999-9999-999999999999"
+            })).await;
+        assert_eq!(share_note_request.status_code(), 404);
+
+        let shares = note_shares::Entity::find()
+            .filter(note_shares::Column::NoteId.eq(3))
+            .all(&ctx.db).await
+            .unwrap();
+        assert_eq!(shares.len(), 1);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_reshare_note_unless_owner_allows_it() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        // note 3 is shared with user 4 with view permission
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+        let payload = serde_json::json!({
+            "shared_with_pid": "22222222-2222-2222-2222-222222222222"
+        });
+
+        let share_note_request = authenticated_request.post("/api/notes/3/share").json(&payload).await;
+        assert_eq!(share_note_request.status_code(), 401);
+
+        let policy_request = authenticated_request
+            .patch("/api/notes/3/reshare-policy")
+            .json(&serde_json::json!({ "allow_reshare": true })).await;
+        assert_eq!(policy_request.status_code(), 401);
+
+        let note = notes::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert!(!note.allow_reshare);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn owner_can_allow_resharing() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let policy_request = authenticated_request
+            .patch("/api/notes/3/reshare-policy")
+            .json(&serde_json::json!({ "allow_reshare": true })).await;
+        assert_eq!(policy_request.status_code(), 200);
+
+        let note = notes::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert!(note.allow_reshare);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_reshare_note_when_owner_allows_it() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let mut note = notes::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        note.allow_reshare = Set(true);
+        note.update(&ctx.db).await.unwrap();
+
+        let recipient_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        // a view share can not be turned into an edit share by resharing
+        let share_note_request = recipient_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({
+                "shared_with_pid": "22222222-2222-2222-2222-222222222222",
+                "permission": "edit"
+            })).await;
+        assert_eq!(share_note_request.status_code(), 401);
+
+        let share_note_request = recipient_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({
+                "shared_with_pid": "22222222-2222-2222-2222-222222222222"
+            })).await;
+
+        with_settings!({
+            filters => vec![(r#"\"id\\":\d+"#, r#""id\":ID"#)]
+        }, {
+            assert_debug_snapshot!((share_note_request.status_code(), share_note_request.text()));
+        });
+
+        let share = note_shares::Entity::find()
+            .filter(note_shares::Column::NoteId.eq(3))
+            .filter(note_shares::Column::SharedWithUserId.eq(2))
+            .one(&ctx.db).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(share.shared_by_user_id, Some(4));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn resharing_does_not_change_existing_shares() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let mut note = notes::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        note.allow_reshare = Set(true);
+        note.update(&ctx.db).await.unwrap();
+
+        (note_shares::ActiveModel {
+            note_id: Set(3),
+            shared_with_user_id: Set(2),
+            permission: Set(SharePermission::Manage),
+            shared_by_user_id: Set(Some(3)),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        // a view recipient can neither downgrade nor expire the manage share
+        let recipient_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+        let reshare_response = recipient_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({
+                "shared_with_pid": "22222222-2222-2222-2222-222222222222",
+                "permission": "view",
+                "expires_at": (chrono::Local::now() + chrono::Duration::minutes(1)).to_rfc3339()
+            })).await;
+        assert_eq!(reshare_response.status_code(), 200);
+
+        let share = note_shares::Entity::find()
+            .filter(note_shares::Column::NoteId.eq(3))
+            .filter(note_shares::Column::SharedWithUserId.eq(2))
+            .one(&ctx.db).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(share.permission, SharePermission::Manage);
+        assert_eq!(share.expires_at, None);
+        assert_eq!(share.shared_by_user_id, Some(3));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn managers_can_reshare_note() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        note_shares::Entity::update_many()
+            .col_expr(note_shares::Column::Permission, sea_orm::sea_query::Expr::value(SharePermission::Manage))
+            .filter(note_shares::Column::Id.eq(1))
+            .exec(&ctx.db).await
+            .unwrap();
+
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+        let share_note_request = authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({
+                "shared_with_pid": "22222222-2222-2222-2222-222222222222",
+                "permission": "edit"
+            })).await;
+        assert_eq!(share_note_request.status_code(), 200);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_transfer_note_and_keep_access() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let transfer_request = authenticated_request
+            .post("/api/notes/3/transfer")
+            .json(&serde_json::json!({
+                "new_owner_pid": "44444444-4444-4444-4444-444444444444",
+                "keep_access": true
+            })).await;
+
+        with_settings!({
+            filters => {
+                 let mut combined_filters = testing::CLEANUP_DATE.to_vec();
+                 combined_filters.extend(vec![(r#"\"id\\":\d+"#, r#""id\":ID"#)]);
+                 combined_filters
+            }
+        }, {
+            assert_debug_snapshot!((transfer_request.status_code(), transfer_request.text()));
+        });
+
+        let shares = note_shares::Entity::find()
+            .filter(note_shares::Column::NoteId.eq(3))
+            .all(&ctx.db).await
+            .unwrap();
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0].shared_with_user_id, 3);
+        assert_eq!(shares[0].permission, SharePermission::Edit);
+        assert_eq!(shares[0].state, ShareState::Accepted);
+
+        // the previous owner can still edit the note
+        let update_request = authenticated_request
+            .post("/api/notes/3")
+            .json(&serde_json::json!({ "title": "Still editable", "content": "by the previous owner" })).await;
+        assert_eq!(update_request.status_code(), 200);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn only_owner_can_transfer_note() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        // note 3 is only shared with user 4
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let transfer_request = authenticated_request
+            .post("/api/notes/3/transfer")
+            .json(&serde_json::json!({
+                "new_owner_pid": "44444444-4444-4444-4444-444444444444"
+            })).await;
+        assert_eq!(transfer_request.status_code(), 401);
+
+        let note = notes::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(note.user_id, 3);
+    }).await;
+}
+
+/// Creates a group owned by user 3 with user 4 as a member
+async fn create_group(ctx: &loco_rs::app::AppContext) -> groups::Model {
+    let group = (groups::ActiveModel {
+        name: Set("Team".to_string()),
+        owner_user_id: Set(3),
+        ..Default::default()
+    })
+        .insert(&ctx.db).await
+        .unwrap();
+    group.add_member(&ctx.db, 3).await.unwrap();
+    group.add_member(&ctx.db, 4).await.unwrap();
+    group
+}
+
+#[tokio::test]
+#[serial]
+async fn can_share_note_with_group() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let group = create_group(&ctx).await;
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let share_note_request = authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({
+                "shared_with_group_pid": group.pid.to_string(),
+                "permission": "edit"
+            })).await;
+
+        with_settings!({
+            filters => vec![(r"[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}", "PID")]
+        }, {
+            assert_debug_snapshot!((share_note_request.status_code(), share_note_request.text()));
+        });
+
+        let get_group_shares_request = authenticated_request.get("/api/notes/3/group-shares").await;
+        assert_eq!(get_group_shares_request.status_code(), 200);
+        assert!(get_group_shares_request.text().contains(&group.pid.to_string()));
+
+        let revoke_request = authenticated_request
+            .delete(&format!("/api/notes/3/group-shares/{}", group.pid)).await;
+        assert_eq!(revoke_request.status_code(), 200);
+        assert!(note_group_shares::Entity::find().all(&ctx.db).await.unwrap().is_empty());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_share_note_with_group_you_are_not_in() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let group = create_group(&ctx).await;
+        // user 2 is not a member
+        let authenticated_request = authenticate_user(request, "user2@example.com", "12341234").await;
+
+        let share_note_request = authenticated_request
+            .post("/api/notes/3/share")
+            .json(&serde_json::json!({ "shared_with_group_pid": group.pid.to_string() })).await;
+        assert_eq!(share_note_request.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn group_members_can_access_notes_shared_with_group() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let group = create_group(&ctx).await;
+        // drop the direct share so access can only come from the group
+        note_shares::Entity::delete_by_id(1).exec(&ctx.db).await.unwrap();
+        (note_group_shares::ActiveModel {
+            note_id: Set(3),
+            group_id: Set(group.id),
+            permission: Set(SharePermission::Edit),
+            shared_by_user_id: Set(Some(3)),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let get_note_request = authenticated_request.get("/api/notes/3").await;
+        assert_eq!(get_note_request.status_code(), 200);
+
+        let update_note_request = authenticated_request
+            .post("/api/notes/3")
+            .json(&serde_json::json!({ "title": "Team edit", "content": "Edited through the group" })).await;
+        assert_eq!(update_note_request.status_code(), 200);
+
+        let list_request = authenticated_request.get("/api/notes").await;
+        assert!(list_request.text().contains("Team edit"));
+
+        let get_shared_notes_request = authenticated_request.get("/api/notes/shared").await;
+        assert!(get_shared_notes_request.text().contains("Team edit"));
+
+        // leaving the group takes the access away
+        edvinas_notes_app::models::_entities::group_members::Entity::delete_many()
+            .filter(edvinas_notes_app::models::_entities::group_members::Column::UserId.eq(4))
+            .exec(&ctx.db).await
+            .unwrap();
+        let get_note_request = authenticated_request.get("/api/notes/3").await;
+        assert_eq!(get_note_request.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_accept_incoming_share() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let note = (notes::ActiveModel {
+            title: Set(Some("Pending note".to_string())),
+            content: Set(Some("Waiting for an answer".to_string())),
+            user_id: Set(3),
+            workspace_id: Set(1),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+        (note_shares::ActiveModel {
+            note_id: Set(note.id),
+            shared_with_user_id: Set(4),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        // pending shares do not grant access yet
+        let get_note_request = authenticated_request.get(&format!("/api/notes/{}", note.id)).await;
+        assert_eq!(get_note_request.status_code(), 404);
+
+        let incoming_request = authenticated_request.get("/api/notes/shares/incoming").await;
+
+        with_settings!({
+            filters => vec![(r#"\"note_id\\":\d+"#, r#""note_id\":ID"#)]
+        }, {
+            assert_debug_snapshot!((incoming_request.status_code(), incoming_request.text()));
+        });
+
+        let accept_request = authenticated_request
+            .post(&format!("/api/notes/{}/share/accept", note.id)).await;
+        assert_eq!(accept_request.status_code(), 200);
+
+        let get_note_request = authenticated_request.get(&format!("/api/notes/{}", note.id)).await;
+        assert_eq!(get_note_request.status_code(), 200);
+
+        // accepting twice is not a valid transition
+        let accept_again_request = authenticated_request
+            .post(&format!("/api/notes/{}/share/accept", note.id)).await;
+        assert_eq!(accept_again_request.status_code(), 400);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_decline_share() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let decline_request = authenticated_request.post("/api/notes/3/share/decline").await;
+        assert_eq!(decline_request.status_code(), 200);
+
+        let share = note_shares::Entity::find_by_id(1).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(share.state, ShareState::Declined);
+
+        let get_note_request = authenticated_request.get("/api/notes/3").await;
+        assert_eq!(get_note_request.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_share_with_user_who_blocked_you() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        (user_blocks::ActiveModel {
+            user_id: Set(4),
+            blocked_user_id: Set(3),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        let payload = serde_json::json!({
+            "shared_with_pid": "44444444-4444-4444-4444-444444444444"
+        });
+        let share_note_request = authenticated_request.post("/api/notes/3/share").json(&payload).await;
+
+        assert_eq!(share_note_request.status_code(), 401);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_create_and_revoke_public_link() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let payload = serde_json::json!({
+            "password": "secret"
+        });
+        let create_link_request = authenticated_request.post("/api/notes/3/link").json(&payload).await;
+
+        with_settings!({
+            filters => vec![(r"[0-9a-f]{64}", "TOKEN")]
+        }, {
+            assert_debug_snapshot!((create_link_request.status_code(), create_link_request.text()));
+        });
+
+        let link = note_links::Entity::find().one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(link.note_id, 3);
+        assert_ne!(link.password, Some("secret".to_string()));
+
+        let revoke_link_request = authenticated_request.delete("/api/notes/3/link").await;
+        assert_eq!(revoke_link_request.status_code(), 200);
+
+        let public_request = authenticated_request
+            .get(&format!("/api/public/notes/{}", link.token))
+            .add_query_param("password", "secret").await;
+        assert_eq!(public_request.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_create_public_link_without_manage_permission() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let create_link_request = authenticated_request
+            .post("/api/notes/3/link")
+            .json(&serde_json::json!({})).await;
+
+        assert_eq!(create_link_request.status_code(), 401);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_share_multiple_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        // note 4 belongs to another user and 999 does not exist
+        let payload = serde_json::json!({
+            "note_ids": [3, 4, 999],
+            "shared_with_pid": "22222222-2222-2222-2222-222222222222",
+            "permission": "comment"
+        });
+
+        let share_multiple_request = authenticated_request
+            .post("/api/notes/share-multiple")
+            .json(&payload).await;
+
+        with_settings!({
+            filters => vec![(r#"\"id\\":\d+"#, r#""id\":ID"#)]
+        }, {
+            assert_debug_snapshot!(
+                (share_multiple_request.status_code(), share_multiple_request.text())
+            );
+        });
+
+        let shares = note_shares::Entity
+            ::find()
+            .filter(note_shares::Column::SharedWithUserId.eq(2))
+            .all(&ctx.db).await
+            .unwrap();
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0].note_id, 3);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn sharing_multiple_notes_sends_one_invitation() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let add_note_request = authenticated_request
+            .post("/api/notes")
+            .json(&serde_json::json!({ "title": "Loco note 5", "content": "Loco note 5 content" })).await;
+        let note: serde_json::Value = serde_json::from_str(&add_note_request.text()).unwrap();
+
+        let share_multiple_request = authenticated_request
+            .post("/api/notes/share-multiple")
+            .json(
+                &serde_json::json!({
+                "note_ids": [3, note["id"], 4],
+                "shared_with_email": "newcomer@example.com"
+            })
+            ).await;
+        assert_eq!(share_multiple_request.status_code(), 200);
+        assert_eq!(note_invitations::Entity::find().all(&ctx.db).await.unwrap().len(), 2);
+
+        with_settings!({
+            filters => testing::cleanup_email()
+        }, {
+            assert_debug_snapshot!(ctx.mailer.as_ref().unwrap().deliveries());
+        });
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_list_and_diff_note_revisions() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        authenticated_request
+            .post("/api/notes/3")
+            .json(&serde_json::json!({ "title": "Loco note 3", "content": "Loco note 3 content\nsecond line" })).await;
+        // saving the same title and content again does not add a revision
+        authenticated_request
+            .post("/api/notes/3")
+            .json(&serde_json::json!({ "title": "Loco note 3", "content": "Loco note 3 content\nsecond line" })).await;
+        authenticated_request
+            .post("/api/notes/3")
+            .json(&serde_json::json!({ "title": "Renamed note 3", "content": "Loco note 3 content\nsecond line" })).await;
+
+        let list_response = authenticated_request.get("/api/notes/3/revisions").await;
+        let diff_response = authenticated_request
+            .get("/api/notes/3/revisions/diff")
+            .add_query_param("from", 1)
+            .add_query_param("to", 3).await;
+
+        with_settings!({
+            filters => testing::CLEANUP_DATE.to_vec()
+        }, {
+            assert_debug_snapshot!((list_response.status_code(), list_response.text()));
+            assert_debug_snapshot!((diff_response.status_code(), diff_response.text()));
+        });
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_restore_note_revision() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        authenticated_request
+            .post("/api/notes/3")
+            .json(&serde_json::json!({ "title": "Oops", "content": "Lost everything" })).await;
+
+        let restore_response = authenticated_request.post("/api/notes/3/revisions/1/restore").await;
+        assert_eq!(restore_response.status_code(), 200);
+
+        let note = Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(note.content.as_deref(), Some("Loco note 3 content"));
+
+        // the restored state is a new revision, the history is kept
+        let list_response = authenticated_request.get("/api/notes/3/revisions").await;
+        let revisions: Vec<serde_json::Value> = serde_json::from_str(&list_response.text()).unwrap();
+        assert_eq!(
+            revisions
+                .iter()
+                .map(|revision| revision["revision"].as_i64().unwrap())
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        let missing_response = authenticated_request.post("/api/notes/3/revisions/9/restore").await;
+        assert_eq!(missing_response.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn restoring_a_revision_restores_its_format() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        authenticated_request
+            .post("/api/notes/3")
+            .json(
+                &serde_json::json!({ "title": "Loco note 3", "content": "# Heading", "format": "markdown" })
+            ).await;
+        // switching the format alone is a revision too
+        authenticated_request
+            .post("/api/notes/3")
+            .json(
+                &serde_json::json!({ "title": "Loco note 3", "content": "# Heading", "format": "plain" })
+            ).await;
+        let note = Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(note.format, NoteFormat::Plain);
+
+        let list_response = authenticated_request.get("/api/notes/3/revisions").await;
+        let revisions: Vec<serde_json::Value> = serde_json::from_str(&list_response.text()).unwrap();
+        assert_eq!(
+            revisions
+                .iter()
+                .map(|revision| revision["format"].as_str().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["plain", "markdown", "plain"]
+        );
+
+        let restore_response = authenticated_request.post("/api/notes/3/revisions/2/restore").await;
+        assert_eq!(restore_response.status_code(), 200);
+
+        let note = Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(note.content.as_deref(), Some("# Heading"));
+        assert_eq!(note.format, NoteFormat::Markdown);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_restore_note_from_trash() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        authenticated_request.delete("/api/notes/3").await;
+
+        let trash_response = authenticated_request.get("/api/notes/trash").await;
+        with_settings!({
+            filters => {
+                 let mut combined_filters = testing::CLEANUP_DATE.to_vec();
+                 combined_filters.extend(vec![(r#"\"id\\":\d+"#, r#""id\":ID"#)]);
+                 combined_filters
+            }
+        }, {
+            assert_debug_snapshot!((trash_response.status_code(), trash_response.text()));
+        });
+
+        // trashed notes can not be opened until they are restored
+        assert_eq!(authenticated_request.get("/api/notes/3").await.status_code(), 404);
+
+        let restore_response = authenticated_request.post("/api/notes/3/restore").await;
+        assert_eq!(restore_response.status_code(), 200);
+        assert_eq!(authenticated_request.get("/api/notes/3").await.status_code(), 200);
+        assert_eq!(authenticated_request.get("/api/notes/trash").await.text(), "[]");
+
+        // only notes in the trash can be restored or purged
+        assert_eq!(authenticated_request.post("/api/notes/3/restore").await.status_code(), 404);
+        assert_eq!(authenticated_request.delete("/api/notes/trash/3").await.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_purge_note_from_trash() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        authenticated_request.delete("/api/notes/3").await;
+        let purge_response = authenticated_request.delete("/api/notes/trash/3").await;
+        assert_eq!(purge_response.status_code(), 200);
+
+        assert!(Entity::find_by_id(3).one(&ctx.db).await.unwrap().is_none());
+        let share = note_shares::Entity::find_by_id(1).one(&ctx.db).await.unwrap();
+        assert!(share.is_none());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn recipients_do_not_see_trashed_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        let note = Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        note.move_to_trash(&ctx.db).await.unwrap();
+
+        assert_eq!(authenticated_request.get("/api/notes/3").await.status_code(), 404);
+        let shared_response = authenticated_request.get("/api/notes/shared").await;
+        assert!(!shared_response.text().contains("Loco note 3"));
+        let list_response = authenticated_request.get("/api/notes").await;
+        assert!(!list_response.text().contains("Loco note 3"));
+
+        // the share survives and works again once the note is restored
+        let note = Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        note.restore_from_trash(&ctx.db).await.unwrap();
+        assert_eq!(authenticated_request.get("/api/notes/3").await.status_code(), 200);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn stale_note_update_is_rejected() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let get_response = authenticated_request.get("/api/notes/3").await;
+        let etag = get_response.header("etag");
+        assert_eq!(etag, "\"1\"");
+
+        let update_response = authenticated_request
+            .post("/api/notes/3")
+            .add_header(HeaderName::from_static("if-match"), etag.clone())
+            .json(&serde_json::json!({ "title": "First writer", "content": "wins" })).await;
+        assert_eq!(update_response.status_code(), 200);
+        assert_eq!(update_response.header("etag"), "\"2\"");
+
+        // a second writer still holding version 1 gets the current note back
+        let stale_response = authenticated_request
+            .post("/api/notes/3")
+            .add_header(HeaderName::from_static("if-match"), etag)
+            .json(&serde_json::json!({ "title": "Second writer", "content": "loses" })).await;
+
+        with_settings!({
+            filters => {
+                 let mut combined_filters = testing::CLEANUP_DATE.to_vec();
+                 combined_filters.extend(vec![(r#"\"id\\":\d+"#, r#""id\":ID"#)]);
+                 combined_filters
+            }
+        }, {
+            assert_debug_snapshot!((stale_response.status_code(), stale_response.text()));
+        });
+
+        // the version can be sent in the body too
+        let stale_body_response = authenticated_request
+            .post("/api/notes/3")
+            .json(&serde_json::json!({ "title": "Second writer", "content": "loses", "version": 1 })).await;
+        assert_eq!(stale_body_response.status_code(), 409);
+
+        let note = Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(note.title.as_deref(), Some("First writer"));
+        assert_eq!(note.version, 2);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_search_owned_and_shared_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas2@gmail.com", "1234").await;
+
+        // note 3 is shared with the searching user, note 4 is their own
+        let mut shared = Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        shared.title = Set(Some("Potato salad".to_string()));
+        shared.update(&ctx.db).await.unwrap();
+        let mut owned = Entity::find_by_id(4).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        owned.content = Set(Some("Grandma's <b>potato</b> soup, with plenty of potatoes".to_string()));
+        owned.update(&ctx.db).await.unwrap();
+        (notes::ActiveModel {
+            title: Set(Some("Private potato pie".to_string())),
+            user_id: Set(3),
+            workspace_id: Set(1),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        let search_response = authenticated_request
+            .get("/api/notes/search")
+            .add_query_param("q", "potato").await;
+
+        with_settings!({
+            filters => vec![(r#"\"rank\\":[0-9.e-]+"#, r#""rank\":RANK"#)]
+        }, {
+            assert_debug_snapshot!((search_response.status_code(), search_response.text()));
+        });
+
+        let empty_response = authenticated_request
+            .get("/api/notes/search")
+            .add_query_param("q", " ").await;
+        assert_eq!(empty_response.status_code(), 400);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_page_sort_and_filter_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        for (title, created_at) in [
+            ("Banana", "2024-01-02T00:00:00Z"),
+            ("Apple", "2024-01-01T00:00:00Z"),
+            ("Cherry", "2024-01-03T00:00:00Z"),
+        ] {
+            let created_at = chrono::DateTime::parse_from_rfc3339(created_at).unwrap();
+            (notes::ActiveModel {
+                title: Set(Some(title.to_string())),
+                user_id: Set(3),
+                workspace_id: Set(1),
+                created_at: Set(created_at),
+                updated_at: Set(created_at),
+                ..Default::default()
+            })
+                .insert(&ctx.db).await
+                .unwrap();
+        }
+
+        let titles = |page: &serde_json::Value| {
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|note| note["title"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let first_page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("sort", "title")
+            .add_query_param("order", "desc")
+            .add_query_param("limit", 3).await
+            .json();
+        assert_eq!(first_page["total"], 5);
+        assert_eq!(titles(&first_page), vec!["Loco note 4", "Loco note 3", "Cherry"]);
+
+        let second_page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("sort", "title")
+            .add_query_param("order", "desc")
+            .add_query_param("limit", 3)
+            .add_query_param("after", first_page["next_cursor"].as_str().unwrap()).await
+            .json();
+        assert_eq!(titles(&second_page), vec!["Banana", "Apple"]);
+        assert_eq!(second_page["next_cursor"], serde_json::Value::Null);
+
+        let shared_page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("owner", "shared").await
+            .json();
+        assert_eq!(titles(&shared_page), vec!["Loco note 4"]);
+
+        let dated_page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("owner", "mine")
+            .add_query_param("created_after", "2024-01-02T00:00:00Z")
+            .add_query_param("created_before", "2024-02-01T00:00:00Z").await
+            .json();
+        assert_eq!(titles(&dated_page), vec!["Banana", "Cherry"]);
+        assert_eq!(dated_page["total"], 2);
+
+        // cursors only work for the sort they were handed out for
+        let wrong_sort_response = authenticated_request
+            .get("/api/notes")
+            .add_query_param("sort", "created_at")
+            .add_query_param("after", first_page["next_cursor"].as_str().unwrap()).await;
+        assert_eq!(wrong_sort_response.status_code(), 400);
+        let bad_cursor_response = authenticated_request
+            .get("/api/notes")
+            .add_query_param("after", "not-a-cursor").await;
+        assert_eq!(bad_cursor_response.status_code(), 400);
+        let bad_limit_response = authenticated_request
+            .get("/api/notes")
+            .add_query_param("limit", 0).await;
+        assert_eq!(bad_limit_response.status_code(), 400);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn editing_a_note_moves_its_updated_at() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        for (title, created_at) in [
+            ("Apple", "2024-01-01T00:00:00Z"),
+            ("Banana", "2024-01-02T00:00:00Z"),
+        ] {
+            let created_at = chrono::DateTime::parse_from_rfc3339(created_at).unwrap();
+            (notes::ActiveModel {
+                title: Set(Some(title.to_string())),
+                user_id: Set(3),
+                workspace_id: Set(1),
+                created_at: Set(created_at),
+                updated_at: Set(created_at),
+                ..Default::default()
+            })
+                .insert(&ctx.db).await
+                .unwrap();
+        }
+        let apple = Entity::find()
+            .filter(notes::Column::Title.eq("Apple"))
+            .one(&ctx.db).await
+            .unwrap()
+            .unwrap();
+
+        let titles = |page: &serde_json::Value| {
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|note| note["title"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let edited_since = chrono::Local::now().fixed_offset();
+        let update_response = authenticated_request
+            .post(&format!("/api/notes/{}", apple.id))
+            .json(&serde_json::json!({ "title": "Apple", "content": "now with content" })).await;
+        assert_eq!(update_response.status_code(), 200);
+
+        let by_update: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("owner", "mine")
+            .add_query_param("sort", "updated_at")
+            .add_query_param("order", "desc").await
+            .json();
+        assert_eq!(titles(&by_update)[0], "Apple");
+
+        // pinning is not an edit
+        authenticated_request.post("/api/notes/3/pin").await;
+
+        let updated_page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("updated_after", edited_since.to_rfc3339()).await
+            .json();
+        assert_eq!(titles(&updated_page), vec!["Apple"]);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_pin_archive_and_favourite_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        for (title, created_at) in [
+            ("Apple", "2024-01-01T00:00:00Z"),
+            ("Banana", "2024-01-02T00:00:00Z"),
+        ] {
+            let created_at = chrono::DateTime::parse_from_rfc3339(created_at).unwrap();
+            (notes::ActiveModel {
+                title: Set(Some(title.to_string())),
+                user_id: Set(3),
+                workspace_id: Set(1),
+                created_at: Set(created_at),
+                updated_at: Set(created_at),
+                ..Default::default()
+            })
+                .insert(&ctx.db).await
+                .unwrap();
+        }
+        let banana = Entity::find()
+            .filter(notes::Column::Title.eq("Banana"))
+            .one(&ctx.db).await
+            .unwrap()
+            .unwrap();
+
+        let titles = |page: &serde_json::Value| {
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|note| note["title"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let pin_response = authenticated_request.post(&format!("/api/notes/{}/pin", banana.id)).await;
+        assert_eq!(pin_response.status_code(), 200);
+        let pinned: serde_json::Value = pin_response.json();
+        assert_eq!(pinned["pinned"], true);
+        // toggles are not edits
+        assert_eq!(pinned["version"], banana.version);
+
+        // pinned notes lead every page sequence, also across cursors
+        let first_page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("sort", "title")
+            .add_query_param("limit", 2).await
+            .json();
+        assert_eq!(titles(&first_page), vec!["Banana", "Apple"]);
+        let second_page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("sort", "title")
+            .add_query_param("limit", 2)
+            .add_query_param("after", first_page["next_cursor"].as_str().unwrap()).await
+            .json();
+        assert_eq!(titles(&second_page), vec!["Loco note 3", "Loco note 4"]);
+
+        let archive_response = authenticated_request.post("/api/notes/3/archive").await;
+        assert_eq!(archive_response.status_code(), 200);
+        let page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("sort", "title").await
+            .json();
+        assert_eq!(titles(&page), vec!["Banana", "Apple", "Loco note 4"]);
+        let page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("sort", "title")
+            .add_query_param("archived", true).await
+            .json();
+        assert_eq!(titles(&page), vec!["Banana", "Apple", "Loco note 3", "Loco note 4"]);
+
+        // note 4 is only shared with edvinas1, who can still favourite it
+        let favourite_response = authenticated_request.post("/api/notes/4/favourite").await;
+        assert_eq!(favourite_response.status_code(), 200);
+        let page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("favourite", true).await
+            .json();
+        assert_eq!(titles(&page), vec!["Loco note 4"]);
+        let unfavourite_response = authenticated_request.delete("/api/notes/4/favourite").await;
+        assert_eq!(unfavourite_response.status_code(), 200);
+        let page: serde_json::Value = authenticated_request
+            .get("/api/notes")
+            .add_query_param("favourite", true).await
+            .json();
+        assert_eq!(page["total"], 0);
+
+        // pinning someone else's note needs edit permission
+        let view_only_response = authenticated_request.post("/api/notes/4/pin").await;
+        assert_eq!(view_only_response.status_code(), 401);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_render_notes_as_html() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let authenticated_request = authenticate_user(request, "edvinas1@gmail.com", "1234").await;
+
+        let content = "# Plan\n\n\
+            Read [the docs](https://loco.rs) and [this](javascript:alert(1)).\n\n\
+            <script>alert('hi')</script><b onclick=\"steal()\">bold</b>\n\n\
+            ## Code `main`\n\n\
+            ```rust\nfn main() {}\n```\n\n\
+            ## Plan\n\n\
+            | a | b |\n|---|---|\n| 1 | 2 |\n";
+        let create_response = authenticated_request
+            .post("/api/notes")
+            .json(&serde_json::json!({ "title": "Markdown", "content": content, "format": "markdown" })).await;
+        assert_eq!(create_response.status_code(), 200);
+        let created: serde_json::Value = create_response.json();
+        assert_eq!(created["format"], "markdown");
+
+        let render_response = authenticated_request
+            .get(&format!("/api/notes/{}", created["id"]))
+            .add_query_param("render", "html").await;
+        assert_eq!(render_response.status_code(), 200);
+        let rendered: serde_json::Value = render_response.json();
+        assert_eq!(rendered["content"], content);
+        assert_debug_snapshot!((rendered["html"].as_str().unwrap(), &rendered["toc"]));
+
+        // updates without `format` keep it, plain notes are escaped as they are
+        let update_response = authenticated_request
+            .post(&format!("/api/notes/{}", created["id"]))
+            .json(&serde_json::json!({ "title": "Markdown", "content": "# not a heading" })).await;
+        assert_eq!(update_response.json::<serde_json::Value>()["format"], "markdown");
+        authenticated_request
+            .post("/api/notes/3")
+            .json(
+                &serde_json::json!({ "title": "Plain", "content": "# <b>as is</b>\nnext line\n\nnext paragraph" })
+            ).await;
+        let plain: serde_json::Value = authenticated_request
+            .get("/api/notes/3")
+            .add_query_param("render", "html").await
+            .json();
+        assert_eq!(plain["format"], "plain");
+        assert_eq!(
+            plain["html"],
+            "<p># &lt;b&gt;as is&lt;/b&gt;<br>\nnext line</p>\n<p>next paragraph</p>\n"
+        );
+        assert_eq!(plain["toc"], serde_json::json!([]));
+    }).await;
+}
@@ -45,6 +45,15 @@ pub async fn init_user_login(request: &TestServer, ctx: &AppContext) -> LoggedIn
     }
 }
 
+/// Logs in a seeded user, who all have the password `1234`
+pub async fn login(request: &TestServer, email: &str) -> (HeaderName, HeaderValue) {
+    let response = request
+        .post("/api/auth/login")
+        .json(&serde_json::json!({ "email": email, "password": USER_PASSWORD })).await;
+    let body: serde_json::Value = serde_json::from_str(&response.text()).unwrap();
+    auth_header(body["token"].as_str().unwrap())
+}
+
 pub fn auth_header(token: &str) -> (HeaderName, HeaderValue) {
     let auth_header_value = HeaderValue::from_str(&format!("Bearer {}", &token)).unwrap();
 
@@ -0,0 +1,157 @@
+use axum::http::{ header, HeaderName, HeaderValue };
+use base64::{ engine::general_purpose::STANDARD, Engine };
+use insta::{ assert_debug_snapshot, with_settings };
+use loco_rs::{ hash, testing };
+use edvinas_notes_app::{
+    app::App,
+    models::_entities::{ note_links, notes, sea_orm_active_enums::NoteFormat },
+};
+use sea_orm::{ ActiveModelTrait, EntityTrait, IntoActiveModel, Set };
+use serial_test::serial;
+
+// TODO: see how to dedup / extract this to app-local test utils
+// not to framework, because that would require a runtime dep on insta
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("public_request");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_public_note() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let link = (note_links::ActiveModel {
+            note_id: Set(3),
+            created_by_user_id: Set(3),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        let json_response = request.get(&format!("/api/public/notes/{}", link.token)).await;
+
+        with_settings!({
+            filters => testing::CLEANUP_DATE.to_vec()
+        }, {
+            assert_debug_snapshot!((json_response.status_code(), json_response.text()));
+        });
+
+        let html_response = request
+            .get(&format!("/api/public/notes/{}", link.token))
+            .add_header(header::ACCEPT, HeaderValue::from_static("text/html")).await;
+
+        assert_debug_snapshot!((html_response.status_code(), html_response.text()));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn public_page_renders_markdown_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let mut note = notes::Entity::find_by_id(3).one(&ctx.db).await.unwrap().unwrap().into_active_model();
+        note.format = Set(NoteFormat::Markdown);
+        note.content = Set(Some("## Plan\n\n**Ship** it <script>alert(1)</script>".to_string()));
+        note.update(&ctx.db).await.unwrap();
+        let link = (note_links::ActiveModel {
+            note_id: Set(3),
+            created_by_user_id: Set(3),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        let json_response = request.get(&format!("/api/public/notes/{}", link.token)).await;
+        let body: serde_json::Value = serde_json::from_str(&json_response.text()).unwrap();
+        assert_eq!(body["format"], "markdown");
+
+        let html_response = request
+            .get(&format!("/api/public/notes/{}", link.token))
+            .add_header(header::ACCEPT, HeaderValue::from_static("text/html")).await;
+
+        assert_debug_snapshot!((html_response.status_code(), html_response.text()));
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_get_password_protected_public_note() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let link = (note_links::ActiveModel {
+            note_id: Set(3),
+            created_by_user_id: Set(3),
+            password: Set(Some(hash::hash_password("secret").unwrap())),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        // browsers are asked to prompt for the password
+        let without_password = request.get(&format!("/api/public/notes/{}", link.token)).await;
+        assert_eq!(without_password.status_code(), 401);
+        assert!(
+            without_password
+                .header(header::WWW_AUTHENTICATE)
+                .to_str()
+                .unwrap()
+                .starts_with("Basic ")
+        );
+
+        // the password is not read from the query string
+        let query_password = request
+            .get(&format!("/api/public/notes/{}", link.token))
+            .add_query_param("password", "secret").await;
+        assert_eq!(query_password.status_code(), 401);
+
+        let password_header = HeaderName::from_static("x-link-password");
+        let wrong_password = request
+            .get(&format!("/api/public/notes/{}", link.token))
+            .add_header(password_header.clone(), HeaderValue::from_static("wrong")).await;
+        assert_eq!(wrong_password.status_code(), 401);
+
+        let with_password = request
+            .get(&format!("/api/public/notes/{}", link.token))
+            .add_header(password_header, HeaderValue::from_static("secret")).await;
+        assert_eq!(with_password.status_code(), 200);
+
+        // and send it back as Basic authorization, the user name is ignored
+        let basic = |credentials: &str| {
+            request
+                .get(&format!("/api/public/notes/{}", link.token))
+                .add_header(
+                    header::AUTHORIZATION,
+                    HeaderValue::from_str(&format!("Basic {}", STANDARD.encode(credentials))).unwrap()
+                )
+        };
+        assert_eq!(basic("anyone:wrong").await.status_code(), 401);
+        assert_eq!(basic("anyone:secret").await.status_code(), 200);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_get_public_note_with_unknown_token() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+
+        let response = request.get("/api/public/notes/not-a-token").await;
+        assert_eq!(response.status_code(), 404);
+    }).await;
+}
@@ -0,0 +1,83 @@
+use chrono::{ DateTime, Duration, FixedOffset, Local };
+use loco_rs::testing;
+use edvinas_notes_app::app::App;
+use serial_test::serial;
+
+use super::prepare_data;
+
+fn time(value: &serde_json::Value) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(value.as_str().unwrap()).unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn can_add_snooze_and_delete_reminders() {
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let (viewer_key, viewer_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let tomorrow = Local::now() + Duration::days(1);
+
+        for (params, status) in [
+            (serde_json::json!({ "remind_at": Local::now() - Duration::hours(1) }), 400),
+            (serde_json::json!({ "remind_at": tomorrow, "recurrence": "hourly" }), 400),
+            (serde_json::json!({ "remind_at": tomorrow, "recurrence": "0 9 * * Mon" }), 200),
+        ] {
+            let response = request
+                .post("/api/notes/3/reminders")
+                .add_header(owner_key.clone(), owner_value.clone())
+                .json(&params).await;
+            assert_eq!(response.status_code(), status);
+        }
+
+        // note 3 is shared with edvinas2, who can set their own reminders on it
+        let reminder: serde_json::Value = request
+            .post("/api/notes/3/reminders")
+            .add_header(viewer_key.clone(), viewer_value.clone())
+            .json(&serde_json::json!({ "remind_at": tomorrow, "recurrence": "daily" })).await
+            .json();
+        let pid = reminder["pid"].as_str().unwrap();
+        assert_eq!(time(&reminder["due_at"]), time(&reminder["remind_at"]));
+
+        let owner_reminders: serde_json::Value = request
+            .get("/api/notes/3/reminders")
+            .add_header(owner_key.clone(), owner_value.clone()).await
+            .json();
+        assert_eq!(owner_reminders.as_array().unwrap().len(), 1);
+        assert_eq!(owner_reminders[0]["recurrence"], "0 9 * * Mon");
+
+        let snoozed: serde_json::Value = request
+            .post(&format!("/api/notes/3/reminders/{pid}/snooze"))
+            .add_header(viewer_key.clone(), viewer_value.clone())
+            .json(&serde_json::json!({ "minutes": 90 })).await
+            .json();
+        assert_eq!(time(&snoozed["due_at"]), time(&reminder["due_at"]));
+        assert!(time(&snoozed["remind_at"]) < time(&snoozed["due_at"]));
+        let bad_snooze_response = request
+            .post(&format!("/api/notes/3/reminders/{pid}/snooze"))
+            .add_header(viewer_key.clone(), viewer_value.clone())
+            .json(&serde_json::json!({ "minutes": 0 })).await;
+        assert_eq!(bad_snooze_response.status_code(), 400);
+
+        // reminders are personal
+        let other_snooze_response = request
+            .post(&format!("/api/notes/3/reminders/{pid}/snooze"))
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "minutes": 5 })).await;
+        assert_eq!(other_snooze_response.status_code(), 404);
+        let other_delete_response = request
+            .delete(&format!("/api/notes/3/reminders/{pid}"))
+            .add_header(owner_key, owner_value).await;
+        assert_eq!(other_delete_response.status_code(), 404);
+
+        let delete_response = request
+            .delete(&format!("/api/notes/3/reminders/{pid}"))
+            .add_header(viewer_key.clone(), viewer_value.clone()).await;
+        assert_eq!(delete_response.status_code(), 200);
+        let viewer_reminders: serde_json::Value = request
+            .get("/api/notes/3/reminders")
+            .add_header(viewer_key, viewer_value).await
+            .json();
+        assert_eq!(viewer_reminders, serde_json::json!([]));
+    }).await;
+}
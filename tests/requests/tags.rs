@@ -0,0 +1,177 @@
+use insta::{ assert_debug_snapshot, with_settings };
+use loco_rs::testing;
+use edvinas_notes_app::app::App;
+use serial_test::serial;
+
+use super::prepare_data;
+
+// TODO: see how to dedup / extract this to app-local test utils
+// not to framework, because that would require a runtime dep on insta
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("tags_request");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn note_ids(page: &serde_json::Value) -> Vec<i64> {
+    page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|note| note["id"].as_i64().unwrap())
+        .collect()
+}
+
+fn tag_pid(tags: &serde_json::Value, name: &str) -> String {
+    tags.as_array()
+        .unwrap()
+        .iter()
+        .find(|tag| tag["name"] == name)
+        .unwrap()["pid"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+#[serial]
+async fn tags_are_personal_to_each_user() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let (recipient_key, recipient_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+
+        // note 3 belongs to edvinas1 and is shared with edvinas2
+        let update_response = request
+            .post("/api/notes/3")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(
+                &serde_json::json!({
+                "title": "Loco note 3",
+                "content": "Loco note 3 content",
+                "tags": ["work", " work ", "todo"],
+            })
+            ).await;
+        assert_eq!(update_response.status_code(), 200);
+
+        let tag_response = request
+            .put("/api/notes/3/tags")
+            .add_header(recipient_key.clone(), recipient_value.clone())
+            .json(&serde_json::json!({ "tags": ["later"] })).await;
+        assert_eq!(tag_response.status_code(), 200);
+
+        let owner_tags_response = request
+            .get("/api/notes/3/tags")
+            .add_header(owner_key.clone(), owner_value.clone()).await;
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!((owner_tags_response.status_code(), owner_tags_response.text()));
+        });
+
+        let owner_page: serde_json::Value = request
+            .get("/api/notes")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .add_query_param("tag", "work").await
+            .json();
+        assert_eq!(note_ids(&owner_page), vec![3]);
+        let owner_page: serde_json::Value = request
+            .get("/api/notes")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .add_query_param("tag", "later").await
+            .json();
+        assert_eq!(note_ids(&owner_page), Vec::<i64>::new());
+
+        let recipient_page: serde_json::Value = request
+            .get("/api/notes/shared")
+            .add_header(recipient_key.clone(), recipient_value.clone())
+            .add_query_param("tag", "later").await
+            .json();
+        assert_eq!(note_ids(&recipient_page), vec![3]);
+
+        let blank_response = request
+            .put("/api/notes/3/tags")
+            .add_header(recipient_key, recipient_value)
+            .json(&serde_json::json!({ "tags": ["  "] })).await;
+        assert_eq!(blank_response.status_code(), 400);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_rename_merge_and_delete_tags() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (auth_key, auth_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+
+        let add_response = request
+            .post("/api/notes")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(
+                &serde_json::json!({
+                "title": "Standup",
+                "content": "notes from the standup",
+                "tags": ["job"],
+            })
+            ).await;
+        assert_eq!(add_response.status_code(), 200);
+        let tag_response = request
+            .put("/api/notes/3/tags")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "tags": ["work"] })).await;
+        assert_eq!(tag_response.status_code(), 200);
+
+        let tags: serde_json::Value = request
+            .get("/api/tags")
+            .add_header(auth_key.clone(), auth_value.clone()).await
+            .json();
+        let job_pid = tag_pid(&tags, "job");
+        let work_pid = tag_pid(&tags, "work");
+
+        // renaming onto an existing tag asks for a merge
+        let clash_response = request
+            .patch(&format!("/api/tags/{job_pid}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "name": "work" })).await;
+        assert_eq!(clash_response.status_code(), 400);
+
+        let rename_response = request
+            .patch(&format!("/api/tags/{job_pid}"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "name": "meetings" })).await;
+        assert_eq!(rename_response.status_code(), 200);
+
+        let merge_response = request
+            .post(&format!("/api/tags/{job_pid}/merge"))
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "into_pid": work_pid })).await;
+        assert_eq!(merge_response.status_code(), 200);
+
+        let list_response = request
+            .get("/api/tags")
+            .add_header(auth_key.clone(), auth_value.clone()).await;
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!((list_response.status_code(), list_response.text()));
+        });
+
+        let delete_response = request
+            .delete(&format!("/api/tags/{work_pid}"))
+            .add_header(auth_key.clone(), auth_value.clone()).await;
+        assert_eq!(delete_response.status_code(), 200);
+        let page: serde_json::Value = request
+            .get("/api/notes")
+            .add_header(auth_key, auth_value)
+            .add_query_param("tag", "work").await
+            .json();
+        assert_eq!(page["total"], 0);
+    }).await;
+}
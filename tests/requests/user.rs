@@ -1,6 +1,8 @@
 use insta::{ assert_debug_snapshot, with_settings };
 use loco_rs::testing;
-use edvinas_notes_app::app::App;
+use edvinas_notes_app::{ app::App, models::_entities::{ note_shares, user_blocks } };
+use edvinas_notes_app::models::_entities::sea_orm_active_enums::ShareState;
+use sea_orm::{ ActiveModelTrait, EntityTrait, Set };
 use serial_test::serial;
 
 use super::prepare_data;
@@ -34,3 +36,81 @@ async fn can_get_current_user() {
         });
     }).await;
 }
+
+#[tokio::test]
+#[serial]
+async fn can_block_and_unblock_user() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let user = prepare_data::init_user_login(&request, &ctx).await;
+
+        let (auth_key, auth_value) = prepare_data::auth_header(&user.token);
+        let block_response = request
+            .post("/api/user/blocks")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .json(&serde_json::json!({ "user_pid": "33333333-3333-3333-3333-333333333333" })).await;
+        assert_eq!(block_response.status_code(), 200);
+
+        let list_response = request
+            .get("/api/user/blocks")
+            .add_header(auth_key.clone(), auth_value.clone()).await;
+
+        assert_debug_snapshot!((list_response.status_code(), list_response.text()));
+
+        let unblock_response = request
+            .delete("/api/user/blocks/33333333-3333-3333-3333-333333333333")
+            .add_header(auth_key, auth_value).await;
+        assert_eq!(unblock_response.status_code(), 200);
+        assert!(user_blocks::Entity::find().all(&ctx.db).await.unwrap().is_empty());
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn blocking_declines_pending_shares_made_by_the_blocked_user() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let user = prepare_data::init_user_login(&request, &ctx).await;
+
+        // user 4 reshares a note of user 3
+        let reshare = (note_shares::ActiveModel {
+            note_id: Set(3),
+            shared_with_user_id: Set(user.user.id),
+            shared_by_user_id: Set(Some(4)),
+            state: Set(ShareState::Pending),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+        // user 2 shares a note of user 4
+        let third_party_share = (note_shares::ActiveModel {
+            note_id: Set(4),
+            shared_with_user_id: Set(user.user.id),
+            shared_by_user_id: Set(Some(2)),
+            state: Set(ShareState::Pending),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        let (auth_key, auth_value) = prepare_data::auth_header(&user.token);
+        let block_response = request
+            .post("/api/user/blocks")
+            .add_header(auth_key, auth_value)
+            .json(&serde_json::json!({ "user_pid": "44444444-4444-4444-4444-444444444444" })).await;
+        assert_eq!(block_response.status_code(), 200);
+
+        let reshare = note_shares::Entity::find_by_id(reshare.id).one(&ctx.db).await.unwrap().unwrap();
+        assert_eq!(reshare.state, ShareState::Declined);
+        let third_party_share = note_shares::Entity
+            ::find_by_id(third_party_share.id)
+            .one(&ctx.db).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(third_party_share.state, ShareState::Pending);
+    }).await;
+}
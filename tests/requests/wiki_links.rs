@@ -0,0 +1,130 @@
+use insta::assert_debug_snapshot;
+use loco_rs::testing;
+use edvinas_notes_app::{ app::App, models::note_references::{ parse_wiki_links, WikiLink } };
+use serial_test::serial;
+
+use super::prepare_data;
+
+// TODO: see how to dedup / extract this to app-local test utils
+// not to framework, because that would require a runtime dep on insta
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("wiki_links_request");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn titles(notes: &serde_json::Value) -> Vec<String> {
+    notes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|note| note["title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+/// Names the notes created by the test, their ids differ between runs
+fn redact_ids(text: &str, notes: &[(&serde_json::Value, &str)]) -> String {
+    notes.iter().fold(text.to_string(), |text, (note, name)| {
+        text.replace(&format!(":{},", note["id"]), &format!(":\"{name}\","))
+            .replace(&format!(":{}}}", note["id"]), &format!(":\"{name}\"}}"))
+    })
+}
+
+#[test]
+fn parses_wiki_links() {
+    assert_eq!(
+        parse_wiki_links("See [[Plan]], [[ #12 ]] and [[#x]].\n[[]] [[not\nclosed]] [[a [[Last]]"),
+        vec![
+            WikiLink::Title("Plan".to_string()),
+            WikiLink::Id(12),
+            WikiLink::Title("#x".to_string()),
+            WikiLink::Title("Last".to_string())
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn links_are_only_revealed_between_visible_notes() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let (owner_key, owner_value) = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let (other_key, other_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+
+        // edvinas1 owns note 3, edvinas2 owns note 4, each can see the other's
+        let hub: serde_json::Value = request
+            .post("/api/notes")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(
+                &serde_json::json!({
+                "title": "Hub",
+                "content": "See [[Loco note 4]], [[#3]] and [[Nothing by that name]]",
+            })
+            ).await
+            .json();
+        let private: serde_json::Value = request
+            .post("/api/notes")
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "title": "Private", "content": "About [[#4]]" })).await
+            .json();
+
+        let owner_backlinks: serde_json::Value = request
+            .get("/api/notes/4/backlinks")
+            .add_header(owner_key.clone(), owner_value.clone()).await
+            .json();
+        assert_eq!(titles(&owner_backlinks), vec!["Hub", "Private"]);
+
+        // neither note linking to note 4 is shared with its owner
+        let other_backlinks: serde_json::Value = request
+            .get("/api/notes/4/backlinks")
+            .add_header(other_key.clone(), other_value.clone()).await
+            .json();
+        assert_eq!(titles(&other_backlinks), Vec::<String>::new());
+        let other_graph: serde_json::Value = request
+            .get("/api/notes/graph")
+            .add_header(other_key.clone(), other_value.clone()).await
+            .json();
+        assert_eq!(other_graph["edges"], serde_json::json!([]));
+
+        let share_response = request
+            .post(&format!("/api/notes/{}/share", hub["id"]))
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "shared_with_user_id": 4 })).await;
+        assert_eq!(share_response.status_code(), 200);
+        request
+            .post(&format!("/api/notes/{}/share/accept", hub["id"]))
+            .add_header(other_key.clone(), other_value.clone()).await;
+
+        let other_graph_response = request
+            .get("/api/notes/graph")
+            .add_header(other_key.clone(), other_value.clone()).await;
+        assert_debug_snapshot!((
+            other_graph_response.status_code(),
+            redact_ids(&other_graph_response.text(), &[(&hub, "HUB"), (&private, "PRIVATE")]),
+        ));
+
+        // the private note can't be reached through its id either
+        let private_backlinks_response = request
+            .get(&format!("/api/notes/{}/backlinks", private["id"]))
+            .add_header(other_key, other_value).await;
+        assert_eq!(private_backlinks_response.status_code(), 404);
+
+        let update_response = request
+            .post(&format!("/api/notes/{}", hub["id"]))
+            .add_header(owner_key.clone(), owner_value.clone())
+            .json(&serde_json::json!({ "title": "Hub", "content": "Only [[Private]] now" })).await;
+        assert_eq!(update_response.status_code(), 200);
+        let owner_graph_response = request
+            .get("/api/notes/graph")
+            .add_header(owner_key, owner_value).await;
+        assert_debug_snapshot!((
+            owner_graph_response.status_code(),
+            redact_ids(&owner_graph_response.text(), &[(&hub, "HUB"), (&private, "PRIVATE")]),
+        ));
+    }).await;
+}
@@ -0,0 +1,308 @@
+use axum::http::{ HeaderName, HeaderValue };
+use insta::{ assert_debug_snapshot, with_settings };
+use loco_rs::{ testing, TestServer };
+use edvinas_notes_app::{
+    app::App,
+    models::_entities::{
+        group_members,
+        groups,
+        note_group_shares,
+        notes,
+        sea_orm_active_enums::{ SharePermission, WorkspaceRole },
+        workspaces,
+    },
+};
+use migration::{ Migrator, MigratorTrait };
+use sea_orm::{ ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set };
+use serial_test::serial;
+
+use super::prepare_data;
+
+// TODO: see how to dedup / extract this to app-local test utils
+// not to framework, because that would require a runtime dep on insta
+macro_rules! configure_insta {
+    ($($expr:expr),*) => {
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_suffix("workspaces_request");
+        let _guard = settings.bind_to_scope();
+    };
+}
+
+fn workspace_header(workspace: &workspaces::Model) -> (HeaderName, HeaderValue) {
+    (
+        HeaderName::from_static("x-workspace"),
+        HeaderValue::from_str(&workspace.pid.to_string()).unwrap(),
+    )
+}
+
+/// Creates a workspace as the logged in user and returns it
+async fn create_workspace(
+    request: &TestServer,
+    db: &DatabaseConnection,
+    auth: &(HeaderName, HeaderValue),
+    name: &str
+) -> workspaces::Model {
+    let response = request
+        .post("/api/workspaces")
+        .add_header(auth.0.clone(), auth.1.clone())
+        .json(&serde_json::json!({ "name": name })).await;
+    assert_eq!(response.status_code(), 200);
+    let body: serde_json::Value = serde_json::from_str(&response.text()).unwrap();
+    workspaces::Model::find_by_pid(db, body["pid"].as_str().unwrap()).await.unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn registered_users_get_a_personal_workspace() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        let user = prepare_data::init_user_login(&request, &ctx).await;
+        let (auth_key, auth_value) = prepare_data::auth_header(&user.token);
+
+        let response = request.get("/api/workspaces").add_header(auth_key, auth_value).await;
+
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!((response.status_code(), response.text()));
+        });
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn notes_are_scoped_to_the_active_workspace() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let auth = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let workspace = create_workspace(&request, &ctx.db, &auth, "Side project").await;
+
+        // switching hands out a token that works in the new workspace
+        let switch_response = request
+            .post(&format!("/api/workspaces/{}/switch", workspace.pid))
+            .add_header(auth.0.clone(), auth.1.clone()).await;
+        assert_eq!(switch_response.status_code(), 200);
+        let body: serde_json::Value = serde_json::from_str(&switch_response.text()).unwrap();
+        let (switched_key, switched_value) = prepare_data::auth_header(body["token"].as_str().unwrap());
+
+        let list_response = request
+            .get("/api/notes")
+            .add_header(switched_key.clone(), switched_value.clone()).await;
+        let notes: serde_json::Value = list_response.json();
+        assert_eq!(notes["items"], serde_json::json!([]));
+
+        let add_response = request
+            .post("/api/notes")
+            .add_header(switched_key.clone(), switched_value.clone())
+            .json(&serde_json::json!({ "title": "Side note", "content": "only here" })).await;
+        assert_eq!(add_response.status_code(), 200);
+        let side_notes = notes::Entity::find()
+            .filter(notes::Column::WorkspaceId.eq(workspace.id))
+            .all(&ctx.db).await
+            .unwrap();
+        assert_eq!(side_notes.len(), 1);
+
+        // notes of the default workspace can not be opened from the new one
+        let get_response = request
+            .get("/api/notes/3")
+            .add_header(switched_key.clone(), switched_value.clone()).await;
+        assert_eq!(get_response.status_code(), 404);
+
+        // the header wins over the token
+        let default_workspace = workspaces::Entity::find_by_id(1).one(&ctx.db).await.unwrap().unwrap();
+        let (header_key, header_value) = workspace_header(&default_workspace);
+        let get_response = request
+            .get("/api/notes/3")
+            .add_header(switched_key, switched_value)
+            .add_header(header_key, header_value).await;
+        assert_eq!(get_response.status_code(), 200);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn cannot_use_workspace_you_are_not_a_member_of() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let owner_auth = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let workspace = create_workspace(&request, &ctx.db, &owner_auth, "Private").await;
+
+        let (auth_key, auth_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let (header_key, header_value) = workspace_header(&workspace);
+        let response = request
+            .get("/api/notes")
+            .add_header(auth_key.clone(), auth_value.clone())
+            .add_header(header_key, header_value).await;
+        assert_eq!(response.status_code(), 404);
+
+        let switch_response = request
+            .post(&format!("/api/workspaces/{}/switch", workspace.pid))
+            .add_header(auth_key, auth_value).await;
+        assert_eq!(switch_response.status_code(), 404);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn sharing_with_outsiders_adds_them_as_guests() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let owner_auth = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let workspace = create_workspace(&request, &ctx.db, &owner_auth, "Side project").await;
+        let (header_key, header_value) = workspace_header(&workspace);
+
+        let add_response = request
+            .post("/api/notes")
+            .add_header(owner_auth.0.clone(), owner_auth.1.clone())
+            .add_header(header_key.clone(), header_value.clone())
+            .json(&serde_json::json!({ "title": "Side note", "content": "for a guest" })).await;
+        let note: serde_json::Value = serde_json::from_str(&add_response.text()).unwrap();
+
+        let share_response = request
+            .post(&format!("/api/notes/{}/share", note["id"]))
+            .add_header(owner_auth.0.clone(), owner_auth.1.clone())
+            .add_header(header_key.clone(), header_value.clone())
+            .json(&serde_json::json!({ "shared_with_pid": "44444444-4444-4444-4444-444444444444" })).await;
+        assert_eq!(share_response.status_code(), 200);
+        assert_eq!(workspace.role_of(&ctx.db, 4).await.unwrap(), Some(WorkspaceRole::Guest));
+
+        // guests can switch to the workspace but can not create notes in it
+        let (guest_key, guest_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let add_response = request
+            .post("/api/notes")
+            .add_header(guest_key, guest_value)
+            .add_header(header_key, header_value)
+            .json(&serde_json::json!({ "title": "Guest note", "content": "not allowed" })).await;
+        assert_eq!(add_response.status_code(), 401);
+    }).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn admins_can_not_grant_roles_above_their_own() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        let owner_auth = prepare_data::login(&request, "edvinas1@gmail.com").await;
+        let workspace = create_workspace(&request, &ctx.db, &owner_auth, "Team").await;
+
+        let add_response = request
+            .post(&format!("/api/workspaces/{}/members", workspace.pid))
+            .add_header(owner_auth.0.clone(), owner_auth.1.clone())
+            .json(&serde_json::json!({
+                "user_pid": "44444444-4444-4444-4444-444444444444",
+                "role": "admin"
+            })).await;
+        assert_eq!(add_response.status_code(), 200);
+
+        let (admin_key, admin_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let add_response = request
+            .post(&format!("/api/workspaces/{}/members", workspace.pid))
+            .add_header(admin_key.clone(), admin_value.clone())
+            .json(&serde_json::json!({
+                "user_pid": "22222222-2222-2222-2222-222222222222",
+                "role": "owner"
+            })).await;
+        assert_eq!(add_response.status_code(), 401);
+
+        let demote_response = request
+            .patch(&format!("/api/workspaces/{}/members/33333333-3333-3333-3333-333333333333", workspace.pid))
+            .add_header(admin_key, admin_value)
+            .json(&serde_json::json!({ "role": "guest" })).await;
+        assert_eq!(demote_response.status_code(), 401);
+
+        let members_response = request
+            .get(&format!("/api/workspaces/{}/members", workspace.pid))
+            .add_header(owner_auth.0, owner_auth.1).await;
+
+        with_settings!({
+            filters => testing::cleanup_user_model()
+        }, {
+            assert_debug_snapshot!((members_response.status_code(), members_response.text()));
+        });
+    }).await;
+}
+
+/// Header selecting the workspace note `note_id` ended up in
+async fn workspace_of_note(db: &DatabaseConnection, note_id: i32) -> (HeaderName, HeaderValue) {
+    let note = notes::Entity::find_by_id(note_id).one(db).await.unwrap().unwrap();
+    let workspace = workspaces::Entity::find_by_id(note.workspace_id).one(db).await.unwrap().unwrap();
+    workspace_header(&workspace)
+}
+
+#[tokio::test]
+#[serial]
+async fn migrating_to_workspaces_keeps_existing_shares_working() {
+    configure_insta!();
+
+    testing::request::<App, _, _>(|request, ctx| async move {
+        testing::seed::<App>(&ctx.db).await.unwrap();
+        // back to before notes belonged to workspaces, when nobody shared one
+        let steps = Migrator::migrations()
+            .iter()
+            .rev()
+            .position(|migration| migration.name() == "m20240902_000003_add_workspace_id_to_notes")
+            .unwrap() + 1;
+        Migrator::down(&ctx.db, Some(u32::try_from(steps).unwrap())).await.unwrap();
+        workspaces::Entity::delete_many().exec(&ctx.db).await.unwrap();
+
+        // note 3 is shared with user 4 by the fixtures, note 4 goes to a group with user 2
+        let group = (groups::ActiveModel {
+            name: Set("Team".to_string()),
+            owner_user_id: Set(4),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+        // memberships had no state yet, the migration accepts them for everyone
+        for user_id in [4, 2] {
+            group_members::Entity::insert(group_members::ActiveModel {
+                group_id: Set(group.id),
+                user_id: Set(user_id),
+                ..Default::default()
+            })
+                .exec_without_returning(&ctx.db).await
+                .unwrap();
+        }
+        (note_group_shares::ActiveModel {
+            note_id: Set(4),
+            group_id: Set(group.id),
+            permission: Set(SharePermission::View),
+            shared_by_user_id: Set(Some(4)),
+            ..Default::default()
+        })
+            .insert(&ctx.db).await
+            .unwrap();
+
+        Migrator::up(&ctx.db, None).await.unwrap();
+
+        let (recipient_key, recipient_value) = prepare_data::login(&request, "edvinas2@gmail.com").await;
+        let (workspace_key, workspace_value) = workspace_of_note(&ctx.db, 3).await;
+        let direct_response = request
+            .get("/api/notes/3")
+            .add_header(recipient_key, recipient_value)
+            .add_header(workspace_key, workspace_value).await;
+        assert_eq!(direct_response.status_code(), 200);
+
+        let group_member_response = request
+            .post("/api/auth/login")
+            .json(&serde_json::json!({ "email": "user2@example.com", "password": "12341234" })).await;
+        let body: serde_json::Value = serde_json::from_str(&group_member_response.text()).unwrap();
+        let (member_key, member_value) = prepare_data::auth_header(body["token"].as_str().unwrap());
+        let (workspace_key, workspace_value) = workspace_of_note(&ctx.db, 4).await;
+        let group_response = request
+            .get("/api/notes/4")
+            .add_header(member_key, member_value)
+            .add_header(workspace_key, workspace_value).await;
+        assert_eq!(group_response.status_code(), 200);
+    }).await;
+}
@@ -0,0 +1,108 @@
+use std::{ collections::HashMap, path::Path, sync::{ Arc, Mutex } };
+
+use axum::{
+    body::Bytes,
+    extract::{ Path as UrlPath, State },
+    http::{ header, HeaderMap, StatusCode },
+    response::IntoResponse,
+    routing::put,
+    Router,
+};
+use edvinas_notes_app::storage::{ S3Settings, StorageSettings };
+use loco_rs::storage::drivers::StoreDriver;
+
+type Objects = Arc<Mutex<HashMap<String, Bytes>>>;
+
+/// Stands in for an S3 compatible service: keeps objects in memory and
+/// answers the few requests the S3 client makes, ignoring auth
+async fn s3_stand_in() -> String {
+    fn object_headers(content: &Bytes) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ETAG, format!("\"{}\"", content.len()).parse().unwrap());
+        headers.insert(header::LAST_MODIFIED, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        headers.insert(header::CONTENT_LENGTH, content.len().into());
+        headers
+    }
+
+    async fn put_object(
+        State(objects): State<Objects>,
+        UrlPath((_bucket, key)): UrlPath<(String, String)>,
+        body: Bytes
+    ) -> impl IntoResponse {
+        let etag = format!("\"{}\"", body.len());
+        objects.lock().unwrap().insert(key, body);
+        [(header::ETAG, etag)]
+    }
+
+    async fn get_object(
+        State(objects): State<Objects>,
+        UrlPath((_bucket, key)): UrlPath<(String, String)>
+    ) -> Result<impl IntoResponse, StatusCode> {
+        let content = objects.lock().unwrap().get(&key).cloned().ok_or(StatusCode::NOT_FOUND)?;
+        Ok((object_headers(&content), content))
+    }
+
+    async fn head_object(
+        State(objects): State<Objects>,
+        UrlPath((_bucket, key)): UrlPath<(String, String)>
+    ) -> Result<impl IntoResponse, StatusCode> {
+        let content = objects.lock().unwrap().get(&key).cloned().ok_or(StatusCode::NOT_FOUND)?;
+        Ok(object_headers(&content))
+    }
+
+    async fn delete_object(
+        State(objects): State<Objects>,
+        UrlPath((_bucket, key)): UrlPath<(String, String)>
+    ) -> StatusCode {
+        objects.lock().unwrap().remove(&key);
+        StatusCode::NO_CONTENT
+    }
+
+    let router = Router::new()
+        .route(
+            "/:bucket/*key",
+            put(put_object).get(get_object).head(head_object).delete(delete_object)
+        )
+        .with_state(Objects::default());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{address}")
+}
+
+async fn assert_round_trip(driver: &dyn StoreDriver) {
+    let path = Path::new("attachments/1/hello.txt");
+    driver.upload(path, &Bytes::from_static(b"hello world")).await.unwrap();
+    assert!(driver.exists(path).await.unwrap());
+
+    let content = driver.get(path).await.unwrap().bytes().await.unwrap();
+    assert_eq!(content, Bytes::from_static(b"hello world"));
+
+    driver.delete(path).await.unwrap();
+    assert!(!driver.exists(path).await.unwrap());
+}
+
+#[tokio::test]
+async fn local_driver_round_trips_files() {
+    let path = std::env::temp_dir().join(format!("notes-storage-{}", uuid::Uuid::new_v4()));
+    let settings: StorageSettings = serde_json::from_value(
+        serde_json::json!({ "driver": "local", "path": path })
+    ).unwrap();
+
+    assert_round_trip(settings.driver().unwrap().as_ref()).await;
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[tokio::test]
+async fn s3_driver_round_trips_files() {
+    let settings = StorageSettings::S3(S3Settings {
+        bucket: "attachments".to_string(),
+        region: "us-east-1".to_string(),
+        endpoint: Some(s3_stand_in().await),
+        allow_http: true,
+        access_key_id: "access-key".to_string(),
+        secret_access_key: "secret-key".to_string(),
+    });
+
+    assert_round_trip(settings.driver().unwrap().as_ref()).await;
+}
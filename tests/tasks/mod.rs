@@ -1 +1,5 @@
+pub mod purge_expired_shares;
+pub mod purge_trash;
 pub mod seed;
+pub mod send_reminders;
+pub mod transfer_notes;
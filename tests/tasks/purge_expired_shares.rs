@@ -0,0 +1,94 @@
+use chrono::{ Duration, Local };
+use loco_rs::{ boot::run_task, task, testing };
+use edvinas_notes_app::{
+    app::App,
+    models::_entities::{ note_shares, notebook_shares, notebooks, notes, sea_orm_active_enums::{ SharePermission, ShareState } },
+};
+use sea_orm::{ ActiveModelTrait, EntityTrait, IntoActiveModel, Set };
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn test_can_purge_expired_shares() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let share = note_shares::Entity::find_by_id(1).one(&boot.app_context.db).await.unwrap().unwrap();
+    let mut share = share.into_active_model();
+    share.expires_at = Set(Some((Local::now() - Duration::days(1)).into()));
+    share.update(&boot.app_context.db).await.unwrap();
+    let mut note = notes::Entity::find_by_id(3).one(&boot.app_context.db).await.unwrap().unwrap().into_active_model();
+    note.title = Set(Some("<b>Win</b>".to_string()));
+    note.update(&boot.app_context.db).await.unwrap();
+
+    assert!(
+        run_task::<App>(
+            &boot.app_context,
+            Some(&"purge_expired_shares".to_string()),
+            &task::Vars::default()
+        ).await.is_ok()
+    );
+
+    let shares = note_shares::Entity::find().all(&boot.app_context.db).await.unwrap();
+    assert_eq!(shares.len(), 1);
+    assert_eq!(shares[0].id, 2);
+
+    let deliveries = boot.app_context.mailer.unwrap().deliveries();
+    assert_eq!(deliveries.count, 1);
+    // note titles are escaped in the html part
+    let (_, html) = deliveries.messages[0].split_once("text/html").unwrap();
+    assert!(html.contains("&lt;b&gt;Win"));
+    assert!(!html.contains("<b>Win"));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_can_purge_expired_notebook_shares() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let notebook = (notebooks::ActiveModel {
+        user_id: Set(3),
+        workspace_id: Set(1),
+        name: Set("<i>Plans</i>".to_string()),
+        allow_reshare: Set(false),
+        ..Default::default()
+    })
+        .insert(&boot.app_context.db).await
+        .unwrap();
+    for (shared_with_user_id, expires_at) in [
+        (4, Local::now() - Duration::days(1)),
+        (2, Local::now() + Duration::days(1)),
+    ] {
+        (notebook_shares::ActiveModel {
+            notebook_id: Set(notebook.id),
+            shared_with_user_id: Set(shared_with_user_id),
+            permission: Set(SharePermission::View),
+            state: Set(ShareState::Accepted),
+            expires_at: Set(Some(expires_at.into())),
+            shared_by_user_id: Set(Some(3)),
+            ..Default::default()
+        })
+            .insert(&boot.app_context.db).await
+            .unwrap();
+    }
+
+    assert!(
+        run_task::<App>(
+            &boot.app_context,
+            Some(&"purge_expired_shares".to_string()),
+            &task::Vars::default()
+        ).await.is_ok()
+    );
+
+    let shares = notebook_shares::Entity::find().all(&boot.app_context.db).await.unwrap();
+    assert_eq!(shares.len(), 1);
+    assert_eq!(shares[0].shared_with_user_id, 2);
+
+    let deliveries = boot.app_context.mailer.unwrap().deliveries();
+    assert_eq!(deliveries.count, 1);
+    assert!(deliveries.messages[0].contains("To: edvinas1@gmail.com"));
+    let (_, html) = deliveries.messages[0].split_once("text/html").unwrap();
+    assert!(html.contains("&lt;i&gt;Plans"));
+    assert!(!html.contains("<i>Plans"));
+}
@@ -0,0 +1,59 @@
+use chrono::{ Duration, Local };
+use loco_rs::{ boot::run_task, task, testing };
+use std::path::Path;
+
+use edvinas_notes_app::{
+    app::App,
+    models::{ _entities::{ note_shares, notes }, attachments },
+    storage::STORE,
+};
+use sea_orm::{ ActiveModelTrait, EntityTrait, IntoActiveModel, Set };
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn test_can_purge_old_trash() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    // note 3 was trashed long ago, note 4 just now
+    for (id, days_ago) in [(3, 10), (4, 0)] {
+        let note = notes::Entity::find_by_id(id).one(&boot.app_context.db).await.unwrap().unwrap();
+        let mut note = note.into_active_model();
+        note.deleted_at = Set(Some((Local::now() - Duration::days(days_ago)).into()));
+        note.update(&boot.app_context.db).await.unwrap();
+    }
+
+    let storage_key = attachments::new_storage_key(3);
+    boot.app_context.storage
+        .upload(Path::new(&storage_key), &"attached".into()).await
+        .unwrap();
+    (attachments::ActiveModel {
+        note_id: Set(3),
+        file_name: Set("attached.txt".to_string()),
+        content_type: Set("text/plain".to_string()),
+        size: Set(8),
+        storage_key: Set(storage_key.clone()),
+        ..Default::default()
+    })
+        .insert(&boot.app_context.db).await
+        .unwrap();
+
+    let vars = task::Vars::from_cli_args(vec![("retention_days".to_string(), "7".to_string())]);
+    assert!(
+        run_task::<App>(&boot.app_context, Some(&"purge_trash".to_string()), &vars).await.is_ok()
+    );
+
+    let remaining = notes::Entity::find().all(&boot.app_context.db).await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].id, 4);
+
+    // shares of the purged note went with it
+    let shares = note_shares::Entity::find().all(&boot.app_context.db).await.unwrap();
+    assert_eq!(shares.len(), 1);
+    assert_eq!(shares[0].note_id, 4);
+
+    // and so did the files attached to it
+    let store = boot.app_context.storage.as_store_err(STORE).unwrap();
+    assert!(!store.exists(Path::new(&storage_key)).await.unwrap());
+}
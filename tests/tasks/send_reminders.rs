@@ -0,0 +1,101 @@
+use chrono::{ DateTime, Duration, FixedOffset, Local, Timelike };
+use loco_rs::{ boot::run_task, task, testing };
+use edvinas_notes_app::{ app::App, models::{ notes, reminders } };
+use sea_orm::{ ActiveModelTrait, ConnectionTrait, Database, EntityTrait, IntoActiveModel, Set, TransactionTrait };
+use serial_test::serial;
+
+async fn add_reminder<C: ConnectionTrait>(
+    db: &C,
+    note_id: i32,
+    user_id: i32,
+    remind_at: DateTime<FixedOffset>,
+    recurrence: Option<&str>
+) -> reminders::Model {
+    (reminders::ActiveModel {
+        note_id: Set(note_id),
+        user_id: Set(user_id),
+        due_at: Set(remind_at),
+        remind_at: Set(remind_at),
+        recurrence: Set(recurrence.map(ToString::to_string)),
+        ..Default::default()
+    })
+        .insert(db).await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_can_send_due_reminders() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let db = &boot.app_context.db;
+    testing::seed::<App>(db).await.unwrap();
+    let now: DateTime<FixedOffset> = Local::now().with_nanosecond(0).unwrap().into();
+
+    // users 3 and 4 own notes 3 and 4, user 2 can see neither
+    let once = add_reminder(db, 3, 3, now - Duration::hours(1), None).await;
+    let daily = add_reminder(db, 4, 4, now - Duration::hours(25), Some("daily")).await;
+    let later = add_reminder(db, 3, 3, now + Duration::hours(1), None).await;
+    let no_access = add_reminder(db, 3, 2, now - Duration::hours(1), None).await;
+    let mut note = notes::Entity::find_by_id(3).one(db).await.unwrap().unwrap().into_active_model();
+    note.title = Set(Some("<b>Win</b>".to_string()));
+    note.update(db).await.unwrap();
+
+    let vars = task::Vars::default();
+    for _ in 0..2 {
+        assert!(
+            run_task::<App>(&boot.app_context, Some(&"send_reminders".to_string()), &vars).await.is_ok()
+        );
+    }
+
+    // sent once each, however often the task runs
+    let deliveries = boot.app_context.mailer.as_ref().unwrap().deliveries();
+    assert_eq!(deliveries.count, 2);
+    // note titles are escaped in the html part
+    let (_, html) = deliveries.messages
+        .iter()
+        .find(|message| message.contains("Win"))
+        .unwrap()
+        .split_once("text/html")
+        .unwrap();
+    assert!(html.contains("&lt;b&gt;Win"));
+    assert!(!html.contains("<b>Win"));
+
+    let find = |id| async move {
+        reminders::Entity::find_by_id(id).one(db).await.unwrap().unwrap()
+    };
+    let once = find(once.id).await;
+    assert!(once.sent_at.is_some());
+    assert!(!once.is_pending());
+
+    // the missed occurrence is skipped, the next one keeps the time of day
+    let daily = find(daily.id).await;
+    assert_eq!(daily.due_at, now + Duration::hours(23));
+    assert_eq!(daily.remind_at, daily.due_at);
+    assert!(daily.is_pending());
+
+    assert!(find(later.id).await.sent_at.is_none());
+    assert!(find(no_access.id).await.sent_at.is_some());
+}
+
+#[tokio::test]
+#[serial]
+async fn overlapping_runs_claim_each_reminder_once() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    let db = &boot.app_context.db;
+    testing::seed::<App>(db).await.unwrap();
+    let now: DateTime<FixedOffset> = Local::now().with_nanosecond(0).unwrap().into();
+    let due = add_reminder(db, 3, 3, now - Duration::hours(1), None).await;
+
+    // the first run holds its claim until it commits
+    let first_run = db.begin().await.unwrap();
+    let claimed = reminders::Entity::claim_due(&first_run, now).await.unwrap();
+    assert_eq!(claimed.iter().map(|reminder| reminder.id).collect::<Vec<_>>(), vec![due.id]);
+
+    // a run overlapping it, on its own connection, skips the locked reminder
+    // instead of waiting for it
+    let other = Database::connect(&boot.app_context.config.database.uri).await.unwrap();
+    assert!(reminders::Entity::claim_due(&other, now).await.unwrap().is_empty());
+
+    first_run.commit().await.unwrap();
+    assert!(reminders::Entity::claim_due(db, now).await.unwrap().is_empty());
+}
@@ -0,0 +1,36 @@
+use loco_rs::{ boot::run_task, task, testing };
+use edvinas_notes_app::{ app::App, models::_entities::{ note_shares, notes } };
+use sea_orm::{ ColumnTrait, EntityTrait, QueryFilter };
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn test_can_transfer_notes() {
+    let boot = testing::boot_test::<App>().await.unwrap();
+    testing::seed::<App>(&boot.app_context.db).await.unwrap();
+
+    let vars = task::Vars::from_cli_args(vec![
+        ("from".to_string(), "33333333-3333-3333-3333-333333333333".to_string()),
+        ("to".to_string(), "22222222-2222-2222-2222-222222222222".to_string()),
+    ]);
+    assert!(
+        run_task::<App>(&boot.app_context, Some(&"transfer_notes".to_string()), &vars).await.is_ok()
+    );
+
+    let remaining = notes::Entity::find()
+        .filter(notes::Column::UserId.eq(3))
+        .all(&boot.app_context.db).await
+        .unwrap();
+    assert!(remaining.is_empty());
+
+    let note = notes::Entity::find_by_id(3).one(&boot.app_context.db).await.unwrap().unwrap();
+    assert_eq!(note.user_id, 2);
+
+    // shares of the transferred notes are kept, the previous owner is not added
+    let shares = note_shares::Entity::find()
+        .filter(note_shares::Column::NoteId.eq(3))
+        .all(&boot.app_context.db).await
+        .unwrap();
+    assert_eq!(shares.len(), 1);
+    assert_eq!(shares[0].shared_with_user_id, 4);
+}